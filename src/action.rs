@@ -0,0 +1,145 @@
+//! This module defines actions that Rectangular can perform on windows.
+//!
+//! A [`WindowAction`] describes *where* a window should land, relatively to the work area of the
+//! monitor it's on. Actions are independent of the way they are triggered, so the same action can
+//! be invoked with a keyboard shortcut, a leader-key sequence, or from the Control Center.
+
+use std::fmt;
+
+use serde::{de, Deserialize, Deserializer};
+
+use crate::geometry::FracRect;
+
+/// All window arrangements supported by Rectangular.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub enum WindowAction {
+    LeftHalf,
+    RightHalf,
+    TopHalf,
+    BottomHalf,
+    TopLeft,
+    TopRight,
+    BottomLeft,
+    BottomRight,
+    FirstThird,
+    CenterThird,
+    LastThird,
+    FirstTwoThirds,
+    LastTwoThirds,
+    Maximize,
+    Center,
+}
+
+impl WindowAction {
+    /// All actions, in the order they are presented to the user.
+    pub const ALL: [WindowAction; 15] = [
+        WindowAction::LeftHalf,
+        WindowAction::RightHalf,
+        WindowAction::TopHalf,
+        WindowAction::BottomHalf,
+        WindowAction::TopLeft,
+        WindowAction::TopRight,
+        WindowAction::BottomLeft,
+        WindowAction::BottomRight,
+        WindowAction::FirstThird,
+        WindowAction::CenterThird,
+        WindowAction::LastThird,
+        WindowAction::FirstTwoThirds,
+        WindowAction::LastTwoThirds,
+        WindowAction::Maximize,
+        WindowAction::Center,
+    ];
+
+    /// A stable, machine-friendly name of the action.
+    ///
+    /// This is the name used in configuration files and on the command line.
+    pub const fn name(self) -> &'static str {
+        match self {
+            WindowAction::LeftHalf => "left-half",
+            WindowAction::RightHalf => "right-half",
+            WindowAction::TopHalf => "top-half",
+            WindowAction::BottomHalf => "bottom-half",
+            WindowAction::TopLeft => "top-left",
+            WindowAction::TopRight => "top-right",
+            WindowAction::BottomLeft => "bottom-left",
+            WindowAction::BottomRight => "bottom-right",
+            WindowAction::FirstThird => "first-third",
+            WindowAction::CenterThird => "center-third",
+            WindowAction::LastThird => "last-third",
+            WindowAction::FirstTwoThirds => "first-two-thirds",
+            WindowAction::LastTwoThirds => "last-two-thirds",
+            WindowAction::Maximize => "maximize",
+            WindowAction::Center => "center",
+        }
+    }
+
+    /// A human-readable name of the action.
+    pub const fn label(self) -> &'static str {
+        match self {
+            WindowAction::LeftHalf => "Left Half",
+            WindowAction::RightHalf => "Right Half",
+            WindowAction::TopHalf => "Top Half",
+            WindowAction::BottomHalf => "Bottom Half",
+            WindowAction::TopLeft => "Top Left",
+            WindowAction::TopRight => "Top Right",
+            WindowAction::BottomLeft => "Bottom Left",
+            WindowAction::BottomRight => "Bottom Right",
+            WindowAction::FirstThird => "First Third",
+            WindowAction::CenterThird => "Center Third",
+            WindowAction::LastThird => "Last Third",
+            WindowAction::FirstTwoThirds => "First Two Thirds",
+            WindowAction::LastTwoThirds => "Last Two Thirds",
+            WindowAction::Maximize => "Maximize",
+            WindowAction::Center => "Center",
+        }
+    }
+
+    /// Find an action by its [`name`](WindowAction::name).
+    pub fn from_name(name: &str) -> Option<WindowAction> {
+        WindowAction::ALL.into_iter().find(|action| action.name() == name)
+    }
+
+    /// The region of the work area the window should occupy after applying the action.
+    ///
+    /// Returns `None` for actions which don't resize the window, and whose target depends on the
+    /// current window size instead (like [`WindowAction::Center`]).
+    pub const fn fraction(self) -> Option<FracRect> {
+        const THIRD: f64 = 1.0 / 3.0;
+        const TWO_THIRDS: f64 = 2.0 / 3.0;
+
+        let fraction = match self {
+            WindowAction::LeftHalf => FracRect::new(0.0, 0.0, 0.5, 1.0),
+            WindowAction::RightHalf => FracRect::new(0.5, 0.0, 0.5, 1.0),
+            WindowAction::TopHalf => FracRect::new(0.0, 0.0, 1.0, 0.5),
+            WindowAction::BottomHalf => FracRect::new(0.0, 0.5, 1.0, 0.5),
+            WindowAction::TopLeft => FracRect::new(0.0, 0.0, 0.5, 0.5),
+            WindowAction::TopRight => FracRect::new(0.5, 0.0, 0.5, 0.5),
+            WindowAction::BottomLeft => FracRect::new(0.0, 0.5, 0.5, 0.5),
+            WindowAction::BottomRight => FracRect::new(0.5, 0.5, 0.5, 0.5),
+            WindowAction::FirstThird => FracRect::new(0.0, 0.0, THIRD, 1.0),
+            WindowAction::CenterThird => FracRect::new(THIRD, 0.0, THIRD, 1.0),
+            WindowAction::LastThird => FracRect::new(TWO_THIRDS, 0.0, THIRD, 1.0),
+            WindowAction::FirstTwoThirds => FracRect::new(0.0, 0.0, TWO_THIRDS, 1.0),
+            WindowAction::LastTwoThirds => FracRect::new(THIRD, 0.0, TWO_THIRDS, 1.0),
+            WindowAction::Maximize => FracRect::FULL,
+            WindowAction::Center => return None,
+        };
+
+        Some(fraction)
+    }
+}
+
+impl fmt::Display for WindowAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl<'de> Deserialize<'de> for WindowAction {
+    /// Deserialize an action from its [`name`](WindowAction::name).
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<WindowAction, D::Error> {
+        let name = String::deserialize(deserializer)?;
+        WindowAction::from_name(&name)
+            .ok_or_else(|| de::Error::custom(format!("unknown action \"{name}\"")))
+    }
+}
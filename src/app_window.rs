@@ -1,6 +1,7 @@
 //! This module it entirely dedicated to [`AppWindow`] trait.
 
-use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, RECT, WPARAM};
+use windows::Win32::Graphics::Gdi::HDC;
 
 /// A trait defining methods common to all application windows.
 ///
@@ -25,4 +26,14 @@ pub trait AppWindow {
     /// [`LRESULT`].
     fn process_message(&self, window: HWND, message: u32, wparam: WPARAM,
                        lparam: LPARAM) -> LRESULT;
+
+    /// Paint the part of the window which needs repainting.
+    ///
+    /// The method is invoked by [`wndproc`](crate::wndproc::wndproc) while handling `WM_PAINT`,
+    /// between `BeginPaint` and `EndPaint`, with the device context and the area to paint. It
+    /// returns `false` if the window doesn't paint itself, in which case the area is simply filled
+    /// with the default background, which is also what the default implementation does.
+    fn paint(&self, _hdc: HDC, _area: &RECT) -> bool {
+        false
+    }
 }
\ No newline at end of file
@@ -0,0 +1,166 @@
+//! This module starts Rectangular together with Windows.
+//!
+//! Windows starts every program listed under the `Run` key of the current user when they sign in.
+//! Rectangular adds itself there as a value with the path of its executable, and removes the value
+//! when starting at login is turned off. The entry isn't updated by itself when the executable is
+//! moved, e.g. after unpacking a new version somewhere else, so such an entry is recognized as
+//! [stale](AutostartState::Stale). The context menu shows it as moved, and the entry is pointed to
+//! the new executable only when the user turns starting at login on again.
+//!
+//! The registry is accessed through [`AutostartStore`], so the logic can be tested with an
+//! in-memory store. [`RegistryStore`] is the store of Windows.
+
+use std::ffi::c_void;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use windows::core::HSTRING;
+use windows::Win32::Foundation::{ERROR_FILE_NOT_FOUND, ERROR_SUCCESS, WIN32_ERROR};
+use windows::Win32::System::Registry::{
+    HKEY_CURRENT_USER, REG_SZ, RRF_RT_REG_SZ, RegDeleteKeyValueW, RegGetValueW, RegSetKeyValueW,
+};
+
+/// The key of programs started at login, under `HKEY_CURRENT_USER`.
+pub const RUN_KEY: &str = r"Software\Microsoft\Windows\CurrentVersion\Run";
+
+/// The name of the value of Rectangular under [`RUN_KEY`].
+pub const VALUE_NAME: &str = "Rectangular";
+
+/// Where the entry starting Rectangular at login is kept.
+pub trait AutostartStore {
+    /// Read the command line of the entry, `None` if there's no entry.
+    fn read(&self) -> io::Result<Option<String>>;
+
+    /// Create the entry, or replace it, with the command line.
+    fn write(&self, command_line: &str) -> io::Result<()>;
+
+    /// Remove the entry. Removing a missing entry isn't an error.
+    fn remove(&self) -> io::Result<()>;
+}
+
+/// Whether Rectangular starts at login.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub enum AutostartState {
+    #[default]
+    Disabled,
+    /// The entry starts the executable Rectangular runs from.
+    Enabled,
+    /// The entry starts an executable from another path, usually because Rectangular has been
+    /// moved since the entry was written.
+    Stale { registered: PathBuf },
+}
+
+impl AutostartState {
+    /// Whether there's an entry at all, even a stale one.
+    pub fn is_enabled(&self) -> bool {
+        *self != AutostartState::Disabled
+    }
+}
+
+/// Get the command line starting the executable, quoted, as paths often contain spaces.
+pub fn command_line(executable: &Path) -> String {
+    format!("\"{}\"", executable.display())
+}
+
+/// Get the executable from a command line, the opposite of [`command_line`]. Arguments following
+/// the executable are skipped.
+pub fn executable_of(command_line: &str) -> PathBuf {
+    let command_line = command_line.trim_start();
+    let executable = match command_line.strip_prefix('"') {
+        Some(quoted) => quoted.split('"').next().unwrap_or(quoted),
+        None => command_line.split_whitespace().next().unwrap_or_default(),
+    };
+
+    PathBuf::from(executable)
+}
+
+/// Check whether the entry starts the executable.
+pub fn state(store: &impl AutostartStore, executable: &Path) -> io::Result<AutostartState> {
+    let Some(command_line) = store.read()? else {
+        return Ok(AutostartState::Disabled);
+    };
+
+    let registered = executable_of(&command_line);
+    // Paths on Windows are compared case-insensitively.
+    let same = registered.to_string_lossy().eq_ignore_ascii_case(&executable.to_string_lossy());
+    match same {
+        true => Ok(AutostartState::Enabled),
+        false => Ok(AutostartState::Stale { registered }),
+    }
+}
+
+/// Turn starting at login on or off for the executable.
+pub fn set_enabled(store: &impl AutostartStore, executable: &Path, enabled: bool)
+                   -> io::Result<()> {
+    match enabled {
+        true => store.write(&command_line(executable)),
+        false => store.remove(),
+    }
+}
+
+/// Turn starting at login off if it's on, and on otherwise. A stale entry doesn't start this
+/// executable, so it's pointed to it. Returns whether it's on now.
+pub fn toggle(store: &impl AutostartStore, executable: &Path) -> io::Result<bool> {
+    let enabled = state(store, executable)? != AutostartState::Enabled;
+    set_enabled(store, executable, enabled)?;
+
+    Ok(enabled)
+}
+
+/// The store of Windows: a value under [`RUN_KEY`] of the current user.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct RegistryStore;
+
+impl AutostartStore for RegistryStore {
+    fn read(&self) -> io::Result<Option<String>> {
+        let (key, name) = (HSTRING::from(RUN_KEY), HSTRING::from(VALUE_NAME));
+        let mut size = 0u32;
+
+        unsafe {
+            let result = RegGetValueW(HKEY_CURRENT_USER, &key, &name, RRF_RT_REG_SZ, None, None,
+                                      Some(&mut size));
+            if result == ERROR_FILE_NOT_FOUND {
+                return Ok(None);
+            }
+            check(result)?;
+
+            let mut buffer = vec![0u16; size as usize / 2 + 1];
+            size = (buffer.len() * 2) as u32;
+            check(RegGetValueW(HKEY_CURRENT_USER, &key, &name, RRF_RT_REG_SZ, None,
+                               Some(buffer.as_mut_ptr() as *mut c_void), Some(&mut size)))?;
+
+            let length = buffer.iter().position(|unit| *unit == 0).unwrap_or(buffer.len());
+            Ok(Some(String::from_utf16_lossy(&buffer[..length])))
+        }
+    }
+
+    fn write(&self, command_line: &str) -> io::Result<()> {
+        let data: Vec<u16> = command_line.encode_utf16().chain([0]).collect();
+
+        unsafe {
+            check(RegSetKeyValueW(HKEY_CURRENT_USER, &HSTRING::from(RUN_KEY),
+                                  &HSTRING::from(VALUE_NAME), REG_SZ.0,
+                                  Some(data.as_ptr() as *const c_void), (data.len() * 2) as u32))
+        }
+    }
+
+    fn remove(&self) -> io::Result<()> {
+        let result = unsafe {
+            RegDeleteKeyValueW(HKEY_CURRENT_USER, &HSTRING::from(RUN_KEY),
+                               &HSTRING::from(VALUE_NAME))
+        };
+
+        match result {
+            ERROR_FILE_NOT_FOUND => Ok(()),
+            result => check(result),
+        }
+    }
+}
+
+/// Turn a result of a registry function into an error, if it's one.
+fn check(result: WIN32_ERROR) -> io::Result<()> {
+    match result {
+        ERROR_SUCCESS => Ok(()),
+        error => Err(io::Error::from_raw_os_error(error.0 as i32)),
+    }
+}
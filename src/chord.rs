@@ -0,0 +1,391 @@
+//! This module contains the leader-key chord state machine.
+//!
+//! There are only so many free modifier combinations, so besides regular shortcuts Rectangular
+//! offers a "leader key" mode. The user presses the leader shortcut (`Ctrl+Alt+Space` by default),
+//! and then types a short sequence of plain keys, like `h` or `tl`, which selects an action.
+//!
+//! [`ChordMachine`] is a pure state machine: it's fed with key presses and the current time, and
+//! it answers what happened. It never talks to Windows, so grabbing the keys, running the timeout
+//! timer and showing the hint is left to the caller.
+
+use std::error::Error;
+use std::fmt;
+use std::time::{Duration, Instant};
+
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+
+use crate::action::WindowAction;
+use crate::hotkey::{Hotkey, KeySpec, Modifiers, VK_ESCAPE, VK_SPACE};
+
+/// A sequence of keys bound to an action.
+///
+/// Keys of a sequence are ASCII letters and digits, written as a string without separators, so
+/// `"tl"` means `t` followed by `l`. Letters are case-insensitive.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ChordBinding {
+    pub keys: String,
+    pub action: WindowAction,
+}
+
+impl ChordBinding {
+    pub fn new(keys: &str, action: WindowAction) -> ChordBinding {
+        ChordBinding { keys: keys.to_string(), action }
+    }
+}
+
+/// Configuration of the leader-key mode.
+///
+/// In the settings file, sequences are given as a table of keys and action names. Unlike
+/// shortcuts, sequences from the file replace the default ones instead of being merged with them,
+/// and the hint lists them in the order they are written:
+///
+/// ```toml
+/// [leader]
+/// hotkey = "Ctrl+Alt+Space"
+/// timeout-ms = 1500
+///
+/// [leader.sequences]
+/// h = "left-half"
+/// tl = "top-left"
+/// ```
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct LeaderConfig {
+    /// The shortcut which starts a sequence.
+    pub hotkey: Hotkey,
+    /// How long to wait for the next key before giving up.
+    #[serde(rename = "timeout-ms", deserialize_with = "deserialize_millis")]
+    pub timeout: Duration,
+    /// Whether to show a hint listing keys that can be pressed next.
+    pub show_hint: bool,
+    #[serde(rename = "sequences", deserialize_with = "deserialize_bindings")]
+    pub bindings: Vec<ChordBinding>,
+}
+
+fn deserialize_millis<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Duration, D::Error> {
+    u64::deserialize(deserializer).map(Duration::from_millis)
+}
+
+/// Read the table of sequences, keeping them in the order they are written in the file.
+fn deserialize_bindings<'de, D: Deserializer<'de>>(deserializer: D)
+    -> Result<Vec<ChordBinding>, D::Error> {
+    struct SequencesVisitor;
+
+    impl<'de> Visitor<'de> for SequencesVisitor {
+        type Value = Vec<ChordBinding>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a table of key sequences and actions")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Self::Value, A::Error> {
+            let mut bindings = Vec::new();
+            while let Some((keys, action)) = map.next_entry::<String, WindowAction>()? {
+                bindings.push(ChordBinding { keys, action });
+            }
+
+            Ok(bindings)
+        }
+    }
+
+    deserializer.deserialize_map(SequencesVisitor)
+}
+
+impl Default for LeaderConfig {
+    fn default() -> Self {
+        LeaderConfig {
+            hotkey: Hotkey::new(Modifiers::CTRL | Modifiers::ALT, KeySpec::Virtual(VK_SPACE)),
+            timeout: Duration::from_millis(1500),
+            show_hint: true,
+            bindings: vec![
+                ChordBinding::new("h", WindowAction::LeftHalf),
+                ChordBinding::new("l", WindowAction::RightHalf),
+                ChordBinding::new("k", WindowAction::TopHalf),
+                ChordBinding::new("j", WindowAction::BottomHalf),
+                ChordBinding::new("1", WindowAction::FirstThird),
+                ChordBinding::new("2", WindowAction::CenterThird),
+                ChordBinding::new("3", WindowAction::LastThird),
+                ChordBinding::new("m", WindowAction::Maximize),
+                ChordBinding::new("c", WindowAction::Center),
+            ],
+        }
+    }
+}
+
+/// An error in the chord bindings, detected when a [`ChordMachine`] is created.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ChordConfigError {
+    /// A binding has no keys at all.
+    EmptySequence(WindowAction),
+    /// A sequence contains a character which isn't an ASCII letter or digit.
+    InvalidKey { keys: String, key: char },
+    /// The same sequence is bound more than once.
+    Duplicate(String),
+    /// One sequence is a prefix of another, so the longer one could never be typed.
+    Prefix { shorter: String, longer: String },
+}
+
+impl fmt::Display for ChordConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ChordConfigError::EmptySequence(action) =>
+                write!(f, "the sequence for \"{action}\" is empty"),
+            ChordConfigError::InvalidKey { keys, key } =>
+                write!(f, "the sequence \"{keys}\" contains \"{key}\", only letters and digits \
+                           are allowed"),
+            ChordConfigError::Duplicate(keys) =>
+                write!(f, "the sequence \"{keys}\" is bound more than once"),
+            ChordConfigError::Prefix { shorter, longer } =>
+                write!(f, "the sequence \"{shorter}\" is a prefix of \"{longer}\""),
+        }
+    }
+}
+
+impl Error for ChordConfigError {}
+
+/// A key pressed while a sequence is being typed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChordKey {
+    /// A letter or digit, always lowercase.
+    Char(char),
+    Escape,
+}
+
+impl ChordKey {
+    /// Convert a virtual-key code to a chord key.
+    ///
+    /// Returns `None` for keys which can't be a part of a sequence.
+    pub fn from_vk(key: u16) -> Option<ChordKey> {
+        match key {
+            VK_ESCAPE => Some(ChordKey::Escape),
+            0x30..=0x39 | 0x41..=0x5A => {
+                Some(ChordKey::Char((key as u8 as char).to_ascii_lowercase()))
+            },
+            _ => None,
+        }
+    }
+
+    /// List every key which can be pressed while a sequence is being typed: all letters and
+    /// digits, whether they continue a sequence or not, and Escape.
+    pub fn all() -> Vec<ChordKey> {
+        ('a'..='z').chain('0'..='9')
+            .map(ChordKey::Char)
+            .chain([ChordKey::Escape])
+            .collect()
+    }
+
+    /// Get the virtual-key code of the key.
+    pub fn vk(self) -> u16 {
+        match self {
+            ChordKey::Char(ch) => ch.to_ascii_uppercase() as u16,
+            ChordKey::Escape => VK_ESCAPE,
+        }
+    }
+}
+
+/// The reason a sequence was abandoned.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CancelReason {
+    Escape,
+    Timeout,
+    /// A key that doesn't continue any sequence was pressed.
+    UnknownKey(char),
+}
+
+/// The result of feeding the [`ChordMachine`] with an input.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ChordEvent {
+    /// The input didn't change anything.
+    Ignored,
+    /// The leader key was pressed, the machine waits for the first key of a sequence.
+    Started,
+    /// A key was accepted, but more keys are needed to select an action.
+    Advanced,
+    /// A sequence has been completed, the action should be applied.
+    Completed(WindowAction),
+    Cancelled(CancelReason),
+}
+
+/// A key which can be pressed next, presented to the user as a hint.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ChordHint {
+    pub key: char,
+    /// The action selected by the key, or `None` if the key only continues longer sequences.
+    pub action: Option<WindowAction>,
+}
+
+/// The leader-key state machine.
+pub struct ChordMachine {
+    bindings: Vec<ChordBinding>,
+    timeout: Duration,
+    /// Keys typed so far, `None` when the machine is idle.
+    typed: Option<String>,
+    deadline: Option<Instant>,
+}
+
+impl Default for ChordMachine {
+    fn default() -> Self {
+        let config = LeaderConfig::default();
+        ChordMachine::new(config.bindings, config.timeout)
+            .expect("Default chord bindings should be valid.")
+    }
+}
+
+impl ChordMachine {
+    /// Create a machine for the given bindings.
+    ///
+    /// The bindings are validated, and their keys are normalized to lowercase.
+    pub fn new(bindings: Vec<ChordBinding>, timeout: Duration)
+        -> Result<ChordMachine, ChordConfigError> {
+        let mut normalized: Vec<ChordBinding> = Vec::with_capacity(bindings.len());
+
+        for binding in bindings {
+            if binding.keys.is_empty() {
+                return Err(ChordConfigError::EmptySequence(binding.action));
+            }
+
+            if let Some(key) = binding.keys.chars().find(|ch| !ch.is_ascii_alphanumeric()) {
+                return Err(ChordConfigError::InvalidKey { keys: binding.keys, key });
+            }
+
+            let keys = binding.keys.to_ascii_lowercase();
+            for other in &normalized {
+                if other.keys == keys {
+                    return Err(ChordConfigError::Duplicate(keys));
+                }
+
+                let (shorter, longer) = if other.keys.len() < keys.len() {
+                    (&other.keys, &keys)
+                } else {
+                    (&keys, &other.keys)
+                };
+
+                if longer.starts_with(shorter.as_str()) {
+                    return Err(ChordConfigError::Prefix {
+                        shorter: shorter.clone(),
+                        longer: longer.clone(),
+                    });
+                }
+            }
+
+            normalized.push(ChordBinding { keys, action: binding.action });
+        }
+
+        Ok(ChordMachine { bindings: normalized, timeout, typed: None, deadline: None })
+    }
+
+    /// Check whether a sequence is being typed.
+    pub fn is_active(&self) -> bool {
+        self.typed.is_some()
+    }
+
+    /// The moment the current sequence times out, if any.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Handle the leader key.
+    ///
+    /// Pressing the leader key in the middle of a sequence starts it over.
+    pub fn leader(&mut self, now: Instant) -> ChordEvent {
+        self.typed = Some(String::new());
+        self.deadline = Some(now + self.timeout);
+        ChordEvent::Started
+    }
+
+    /// Handle a key pressed after the leader key.
+    pub fn key(&mut self, key: ChordKey, now: Instant) -> ChordEvent {
+        if let ChordEvent::Cancelled(reason) = self.tick(now) {
+            return ChordEvent::Cancelled(reason);
+        }
+
+        let Some(typed) = &self.typed else {
+            return ChordEvent::Ignored;
+        };
+
+        let ch = match key {
+            ChordKey::Escape => return self.cancel(CancelReason::Escape),
+            ChordKey::Char(ch) => ch.to_ascii_lowercase(),
+        };
+
+        let mut candidate = typed.clone();
+        candidate.push(ch);
+
+        if let Some(binding) = self.bindings.iter().find(|binding| binding.keys == candidate) {
+            let action = binding.action;
+            self.reset();
+            return ChordEvent::Completed(action);
+        }
+
+        if self.bindings.iter().any(|binding| binding.keys.starts_with(candidate.as_str())) {
+            self.typed = Some(candidate);
+            self.deadline = Some(now + self.timeout);
+            return ChordEvent::Advanced;
+        }
+
+        self.cancel(CancelReason::UnknownKey(ch))
+    }
+
+    /// Check whether the current sequence has timed out.
+    ///
+    /// The caller is expected to invoke this method when the timer set to [`deadline`] fires.
+    ///
+    /// [`deadline`]: ChordMachine::deadline
+    pub fn tick(&mut self, now: Instant) -> ChordEvent {
+        match self.deadline {
+            Some(deadline) if now >= deadline => self.cancel(CancelReason::Timeout),
+            _ => ChordEvent::Ignored,
+        }
+    }
+
+    /// List the keys which can be pressed next, in the order of the bindings.
+    ///
+    /// The list is empty when the machine is idle. The Escape key isn't included, although it can
+    /// always be pressed to cancel the sequence.
+    pub fn next_keys(&self) -> Vec<ChordHint> {
+        let Some(typed) = &self.typed else {
+            return Vec::new();
+        };
+
+        let mut hints: Vec<ChordHint> = Vec::new();
+        for binding in &self.bindings {
+            let Some(rest) = binding.keys.strip_prefix(typed.as_str()) else {
+                continue;
+            };
+
+            let mut chars = rest.chars();
+            let Some(key) = chars.next() else {
+                continue;
+            };
+
+            if hints.iter().all(|hint| hint.key != key) {
+                let action = chars.next().is_none().then_some(binding.action);
+                hints.push(ChordHint { key, action });
+            }
+        }
+
+        hints
+    }
+
+    /// Format [`next_keys`](ChordMachine::next_keys) as a human-readable text, one key per line.
+    pub fn hint_text(&self) -> String {
+        self.next_keys().iter()
+            .map(|hint| match hint.action {
+                Some(action) => format!("{}  {}", hint.key, action.label()),
+                None => format!("{}  ...", hint.key),
+            })
+            .collect::<Vec<String>>()
+            .join("\n")
+    }
+
+    fn cancel(&mut self, reason: CancelReason) -> ChordEvent {
+        self.reset();
+        ChordEvent::Cancelled(reason)
+    }
+
+    fn reset(&mut self) {
+        self.typed = None;
+        self.deadline = None;
+    }
+}
@@ -0,0 +1,311 @@
+//! This module handles the command line of Rectangular.
+//!
+//! Rectangular is a tray application, so it's normally started without arguments. The command line
+//! is there for scripts and troubleshooting: it can apply a single action and exit, check a
+//! settings file before it's put in place, upgrade an old one, print the default settings or their
+//! schema, or convert settings exported from other applications.
+//!
+//! [`parse`] turns the arguments into a [`Startup`] plan, and never talks to Windows, so `main` is
+//! left with carrying out the plan. Commands which don't need Windows at all, like
+//! [`check_config`], live here too.
+
+use std::error::Error;
+use std::ffi::OsString;
+use std::fmt;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::action::WindowAction;
+use crate::hotkey::Modifiers;
+use crate::migration::{self, CURRENT_VERSION};
+use crate::rectangle_import::RectangleImportOptions;
+use crate::settings::Diagnostic;
+use crate::{fancyzones_import, rectangle_import, settings};
+
+/// The exit code of a command which found a problem, e.g. an invalid settings file.
+pub const EXIT_FAILURE: i32 = 1;
+
+/// The exit code used when the command line itself is invalid.
+pub const EXIT_USAGE: i32 = 2;
+
+/// A description of the command line, printed by `--help`.
+pub const USAGE: &str = "\
+Usage: rectangular [OPTIONS]
+
+Options:
+  --config <path>               Use the given settings file
+  --no-tray                     Run without the notification icon, with shortcuts only
+  --action <name>               Apply an action to the foreground window and exit
+  --layout <group>/<action>     Apply a custom action to the foreground window and exit
+  --check-config <path>         Check a settings file and exit
+  --upgrade-config <path>       Upgrade an old settings file, keeping a backup, and exit
+  --print-default-config        Print the default settings and exit
+  --print-schema                Print the JSON Schema of the settings file and exit
+  --import-rectangle <path>     Convert settings exported from Rectangle and print them
+  --command-modifier <key>      The key replacing Command in imported shortcuts: win (default),
+                                ctrl or alt
+  --import-fancyzones <path>    Convert FancyZones custom layouts and print them
+  -h, --help                    Print this help and exit";
+
+/// What Rectangular should do, according to the command line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Startup {
+    /// Run the application, which is what happens without arguments.
+    Run(RunOptions),
+    PrintHelp,
+    PrintDefaultConfig,
+    PrintSchema,
+    /// Check the given settings file.
+    CheckConfig(PathBuf),
+    /// Upgrade the given settings file to the current version.
+    UpgradeConfig(PathBuf),
+    /// Apply the action to the foreground window, using settings from the given file, or the
+    /// default one.
+    ApplyAction { action: WindowAction, config: Option<PathBuf> },
+    /// Apply the custom action of the group to the foreground window, using settings from the
+    /// given file, or the default one.
+    ApplyLayout { group: String, action: String, config: Option<PathBuf> },
+    /// Convert the given file exported from another application to a settings fragment.
+    Import { source: ImportSource, path: PathBuf },
+}
+
+/// Options of a regular run.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RunOptions {
+    /// The settings file, `None` for the default one.
+    pub config: Option<PathBuf>,
+    /// Whether to show the notification icon.
+    pub tray: bool,
+}
+
+impl Default for RunOptions {
+    fn default() -> Self {
+        RunOptions { config: None, tray: true }
+    }
+}
+
+/// Applications whose settings can be imported.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ImportSource {
+    Rectangle(RectangleImportOptions),
+    FancyZones,
+}
+
+/// An error in the command line.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CliError(pub String);
+
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl Error for CliError {}
+
+/// Parse the command line, without the name of the program.
+///
+/// Options taking a value accept it both as the next argument, and after `=`, like
+/// `--config=settings.toml`. Only one command can be given, and options which don't apply to the
+/// command are rejected rather than ignored.
+pub fn parse<I: IntoIterator<Item = OsString>>(args: I) -> Result<Startup, CliError> {
+    let mut args = args.into_iter();
+    let mut config: Option<PathBuf> = None;
+    let mut no_tray = false;
+    let mut command_modifier: Option<Modifiers> = None;
+    let mut command: Option<(String, Startup)> = None;
+
+    while let Some(arg) = args.next() {
+        let Some(text) = arg.to_str() else {
+            return Err(CliError(format!("unexpected argument {}", arg.to_string_lossy())));
+        };
+
+        let (flag, inline_value) = match text.split_once('=') {
+            Some((flag, value)) if flag.starts_with("--") => (flag, Some(OsString::from(value))),
+            _ => (text, None),
+        };
+
+        let takes_value = matches!(flag, "--config" | "--check-config" | "--upgrade-config"
+                                         | "--action" | "--layout" | "--import-rectangle"
+                                         | "--import-fancyzones" | "--command-modifier");
+        let value = if takes_value {
+            match inline_value.or_else(|| args.next()) {
+                Some(value) if !value.is_empty() => Some(value),
+                _ => return Err(CliError(format!("{flag} requires a value"))),
+            }
+        } else if inline_value.is_some() {
+            return Err(CliError(format!("{flag} doesn't take a value")));
+        } else {
+            None
+        };
+
+        let next = match flag {
+            "--config" => {
+                if config.is_some() {
+                    return Err(CliError("--config can be given only once".to_string()));
+                }
+
+                config = value.map(PathBuf::from);
+                continue;
+            },
+            "--command-modifier" => {
+                let name = value.unwrap_or_default();
+                command_modifier = Some(match name.to_string_lossy().as_ref() {
+                    "win" => Modifiers::WIN,
+                    "ctrl" => Modifiers::CTRL,
+                    "alt" => Modifiers::ALT,
+                    other => {
+                        return Err(CliError(format!("unknown command modifier \"{other}\", \
+                                                     expected one of: win, ctrl, alt")));
+                    },
+                });
+                continue;
+            },
+            "--no-tray" => {
+                no_tray = true;
+                continue;
+            },
+            "-h" | "--help" => Startup::PrintHelp,
+            "--print-default-config" => Startup::PrintDefaultConfig,
+            "--print-schema" => Startup::PrintSchema,
+            "--check-config" => Startup::CheckConfig(path(value)),
+            "--upgrade-config" => Startup::UpgradeConfig(path(value)),
+            "--action" => {
+                let name = value.unwrap_or_default();
+                let name = name.to_string_lossy();
+                let action = WindowAction::from_name(&name).ok_or_else(|| {
+                    let names: Vec<&str> = WindowAction::ALL.iter().map(|action| action.name())
+                        .collect();
+                    CliError(format!("unknown action \"{name}\", expected one of: {}",
+                                     names.join(", ")))
+                })?;
+
+                Startup::ApplyAction { action, config: None }
+            },
+            "--layout" => {
+                let value = value.unwrap_or_default();
+                let value = value.to_string_lossy();
+                // Names of zones, like "Zone 1", never have a slash, names of groups might.
+                match value.rsplit_once('/') {
+                    Some((group, action)) if !group.is_empty() && !action.is_empty() => {
+                        Startup::ApplyLayout { group: group.to_string(),
+                                               action: action.to_string(), config: None }
+                    },
+                    _ => {
+                        return Err(CliError(format!("invalid layout \"{value}\", expected \
+                                                     <group>/<action>")));
+                    },
+                }
+            },
+            "--import-rectangle" => {
+                let source = ImportSource::Rectangle(RectangleImportOptions::default());
+                Startup::Import { source, path: path(value) }
+            },
+            "--import-fancyzones" => {
+                Startup::Import { source: ImportSource::FancyZones, path: path(value) }
+            },
+            _ if flag.starts_with('-') => {
+                return Err(CliError(format!("unknown option {flag}")));
+            },
+            _ => return Err(CliError(format!("unexpected argument {flag}"))),
+        };
+
+        if let Some((previous, _)) = &command {
+            return Err(CliError(format!("{flag} can't be combined with {previous}")));
+        }
+        command = Some((flag.to_string(), next));
+    }
+
+    if let Some(modifier) = command_modifier {
+        match &mut command {
+            Some((_, Startup::Import { source: ImportSource::Rectangle(options), .. })) => {
+                options.command = modifier;
+            },
+            _ => {
+                return Err(CliError("--command-modifier can be given only with --import-rectangle"
+                    .to_string()));
+            },
+        }
+    }
+
+    match command {
+        None => Ok(Startup::Run(RunOptions { config, tray: !no_tray })),
+        Some((flag, _)) if no_tray => {
+            Err(CliError(format!("--no-tray can't be combined with {flag}")))
+        },
+        Some((_, Startup::ApplyAction { action, .. })) => {
+            Ok(Startup::ApplyAction { action, config })
+        },
+        Some((_, Startup::ApplyLayout { group, action, .. })) => {
+            Ok(Startup::ApplyLayout { group, action, config })
+        },
+        Some((flag, _)) if config.is_some() => {
+            Err(CliError(format!("--config can't be combined with {flag}")))
+        },
+        Some((_, startup)) => Ok(startup),
+    }
+}
+
+fn path(value: Option<OsString>) -> PathBuf {
+    PathBuf::from(value.unwrap_or_default())
+}
+
+/// Check a settings file.
+///
+/// Returns the report to print, which is an error if the file can't be used. Every diagnostic is
+/// prefixed with the path, so editors can jump to it.
+pub fn check_config(path: &Path) -> Result<String, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|error| format!("{}: error: cannot read the file: {error}", path.display()))?;
+
+    let report = |diagnostics: &[Diagnostic]| -> String {
+        diagnostics.iter().map(|diagnostic| diagnostic_line(path, diagnostic) + "\n").collect()
+    };
+
+    match settings::parse(&text) {
+        Ok(loaded) => {
+            Ok(format!("{}{}: the settings are valid", report(&loaded.warnings), path.display()))
+        },
+        Err(error) => Err(report(&error.0).trim_end().to_string()),
+    }
+}
+
+/// Upgrade a settings file written by an older version of Rectangular, see
+/// [`migration::upgrade_file`].
+pub fn upgrade_config(path: &Path) -> Result<String, String> {
+    match migration::upgrade_file(path) {
+        Ok(true) => {
+            let backup = migration::backup_path(path);
+            Ok(format!("{}: upgraded to version {CURRENT_VERSION}, the original is kept in {}",
+                       path.display(), backup.display()))
+        },
+        Ok(false) => Ok(format!("{}: the settings are up to date", path.display())),
+        Err(error) => Err(format!("{}: error: cannot upgrade the file: {error}", path.display())),
+    }
+}
+
+/// Format a diagnostic found in a settings file, prefixed with the path of the file.
+pub fn diagnostic_line(path: &Path, diagnostic: &Diagnostic) -> String {
+    match diagnostic.location {
+        Some(_) => format!("{}:{diagnostic}", path.display()),
+        None => format!("{}: {diagnostic}", path.display()),
+    }
+}
+
+/// Convert a file exported from another application to a fragment of the settings file.
+pub fn import(source: ImportSource, path: &Path) -> Result<String, String> {
+    let text = fs::read_to_string(path)
+        .map_err(|error| format!("{}: error: cannot read the file: {error}", path.display()))?;
+
+    let fragment = match source {
+        ImportSource::Rectangle(options) => {
+            rectangle_import::import(&text, &options)
+                .map(|imported| imported.to_toml())
+        },
+        ImportSource::FancyZones => {
+            fancyzones_import::import(&text).map(|imported| imported.to_toml())
+        },
+    };
+
+    fragment.map_err(|error| format!("{}: error: {error}", path.display()))
+}
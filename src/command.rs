@@ -0,0 +1,221 @@
+//! This module contains commands: everything the user can ask Rectangular to do.
+//!
+//! A [`Command`] can come from the context menu, a shortcut, or the command line. Each of them
+//! turns what the user did into a command, and hands it to [`dispatch`], which carries it out on a
+//! [`CommandTarget`]. The running application is one target, a single run from the command line
+//! is another, see [`ForegroundTarget`], and tests use a fake one, so the routing can be tested
+//! without Windows.
+//!
+//! Items of the context menu are identified by numbers, see [`Command::menu_id`]. Commands without
+//! arguments have fixed numbers. Actions and custom actions get consecutive numbers starting from
+//! a base, like shortcuts of actions do in the main window, so a custom action is found by its
+//! position in the settings.
+
+use std::error::Error;
+use std::fmt;
+
+use crate::action::WindowAction;
+use crate::engine;
+use crate::geometry::FracRect;
+use crate::settings::{CustomAction, Settings};
+use crate::window_system::{Win32WindowSystem, WindowSystem};
+
+const MENU_EXIT: usize = 1;
+const MENU_OPEN_SETTINGS: usize = 2;
+const MENU_RELOAD_SETTINGS: usize = 3;
+const MENU_ABOUT: usize = 4;
+const MENU_PAUSE: usize = 5;
+const MENU_IGNORE_APP: usize = 6;
+const MENU_START_AT_LOGIN: usize = 7;
+
+/// Menu IDs of actions start from this value.
+///
+/// The ID of such item is this value plus the index of the action in [`WindowAction::ALL`].
+const MENU_ACTION_BASE: usize = 0x100;
+
+/// Menu IDs of custom actions start from this value.
+///
+/// The ID of such item is this value plus the position of the custom action, counting actions of
+/// all groups in the order of the settings file.
+const MENU_CUSTOM_ACTION_BASE: usize = 0x1000;
+
+/// Something the user asked for.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Command {
+    Exit,
+    /// Pause Rectangular, or resume it if it's paused, see [`pause`](crate::pause).
+    Pause,
+    /// Add the application of the window commands are meant for to the ignore list, or remove it
+    /// if it's there, see [`ignore`](crate::ignore).
+    IgnoreApp,
+    /// Turn starting Rectangular at login on or off, see [`autostart`](crate::autostart).
+    StartAtLogin,
+    /// Open the settings file in the associated editor.
+    OpenSettings,
+    /// Load the settings file again, without waiting for the watcher to notice a change.
+    ReloadSettings,
+    About,
+    /// Apply the action to the window commands are meant for.
+    Apply(WindowAction),
+    /// Move the window commands are meant for to a zone of a layout, that is a custom action of a
+    /// [group](crate::settings::CustomGroup), both given by name.
+    ApplyLayout { group: String, action: String },
+}
+
+impl Command {
+    /// Get the ID of the menu item choosing the command.
+    ///
+    /// Returns `None` for a custom action which isn't in the settings.
+    pub fn menu_id(&self, settings: &Settings) -> Option<usize> {
+        match self {
+            Command::Exit => Some(MENU_EXIT),
+            Command::Pause => Some(MENU_PAUSE),
+            Command::IgnoreApp => Some(MENU_IGNORE_APP),
+            Command::StartAtLogin => Some(MENU_START_AT_LOGIN),
+            Command::OpenSettings => Some(MENU_OPEN_SETTINGS),
+            Command::ReloadSettings => Some(MENU_RELOAD_SETTINGS),
+            Command::About => Some(MENU_ABOUT),
+            Command::Apply(action) => WindowAction::ALL.iter()
+                .position(|other| other == action)
+                .map(|index| MENU_ACTION_BASE + index),
+            Command::ApplyLayout { group, action } => custom_actions(settings)
+                .position(|(other_group, other)| other_group == group && other.name == *action)
+                .map(|index| MENU_CUSTOM_ACTION_BASE + index),
+        }
+    }
+
+    /// Get the command chosen with the menu item with the given ID, the opposite of
+    /// [`Command::menu_id`].
+    pub fn from_menu_id(id: usize, settings: &Settings) -> Option<Command> {
+        match id {
+            MENU_EXIT => Some(Command::Exit),
+            MENU_PAUSE => Some(Command::Pause),
+            MENU_IGNORE_APP => Some(Command::IgnoreApp),
+            MENU_START_AT_LOGIN => Some(Command::StartAtLogin),
+            MENU_OPEN_SETTINGS => Some(Command::OpenSettings),
+            MENU_RELOAD_SETTINGS => Some(Command::ReloadSettings),
+            MENU_ABOUT => Some(Command::About),
+            _ if id >= MENU_CUSTOM_ACTION_BASE => {
+                custom_actions(settings).nth(id - MENU_CUSTOM_ACTION_BASE)
+                    .map(|(group, action)| Command::ApplyLayout {
+                        group: group.to_string(),
+                        action: action.name.clone(),
+                    })
+            },
+            _ if id >= MENU_ACTION_BASE => {
+                WindowAction::ALL.get(id - MENU_ACTION_BASE).map(|action| Command::Apply(*action))
+            },
+            _ => None,
+        }
+    }
+}
+
+/// A command which couldn't be carried out.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum CommandError {
+    /// The custom action isn't in the settings, e.g. because they have changed since the command
+    /// was chosen.
+    UnknownLayout { group: String, action: String },
+}
+
+impl fmt::Display for CommandError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CommandError::UnknownLayout { group, action } => {
+                write!(f, "there's no custom action \"{action}\" in the group \"{group}\"")
+            },
+        }
+    }
+}
+
+impl Error for CommandError {}
+
+/// Something commands are carried out on.
+pub trait CommandTarget {
+    /// Apply the action to the window commands are meant for.
+    fn apply(&self, action: WindowAction, settings: &Settings);
+
+    /// Move the window commands are meant for to the fraction of its work area.
+    fn apply_fraction(&self, fraction: &FracRect, settings: &Settings);
+
+    fn exit(&self);
+
+    fn toggle_pause(&self);
+
+    /// Add the application of the window commands are meant for to the ignore list, or remove it.
+    fn toggle_ignored_app(&self);
+
+    fn toggle_start_at_login(&self);
+
+    fn open_settings(&self);
+
+    fn reload_settings(&self);
+
+    fn show_about(&self);
+}
+
+/// Carry out the command on the target.
+///
+/// `settings` are the active ones, they are used to find custom actions.
+pub fn dispatch(command: &Command, settings: &Settings, target: &impl CommandTarget)
+                -> Result<(), CommandError> {
+    match command {
+        Command::Exit => target.exit(),
+        Command::Pause => target.toggle_pause(),
+        Command::IgnoreApp => target.toggle_ignored_app(),
+        Command::StartAtLogin => target.toggle_start_at_login(),
+        Command::OpenSettings => target.open_settings(),
+        Command::ReloadSettings => target.reload_settings(),
+        Command::About => target.show_about(),
+        Command::Apply(action) => target.apply(*action, settings),
+        Command::ApplyLayout { group, action } => {
+            let fraction = custom_actions(settings)
+                .find(|(other_group, other)| other_group == group && other.name == *action)
+                .map(|(_, custom)| custom.rect)
+                .ok_or_else(|| CommandError::UnknownLayout { group: group.clone(),
+                                                             action: action.clone() })?;
+
+            target.apply_fraction(&fraction, settings);
+        },
+    }
+
+    Ok(())
+}
+
+/// The target of a single run from the command line, which arranges the foreground window.
+///
+/// There's no running application to control, so commands like [`Command::Exit`] do nothing.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct ForegroundTarget;
+
+impl CommandTarget for ForegroundTarget {
+    fn apply(&self, action: WindowAction, settings: &Settings) {
+        engine::apply_to_foreground(action, settings);
+    }
+
+    fn apply_fraction(&self, fraction: &FracRect, settings: &Settings) {
+        if let Some(window) = Win32WindowSystem.foreground() {
+            engine::apply_fraction(window, fraction, settings);
+        }
+    }
+
+    fn exit(&self) {}
+
+    fn toggle_pause(&self) {}
+
+    fn toggle_ignored_app(&self) {}
+
+    fn toggle_start_at_login(&self) {}
+
+    fn open_settings(&self) {}
+
+    fn reload_settings(&self) {}
+
+    fn show_about(&self) {}
+}
+
+/// Get all custom actions, with names of their groups, in the order of the settings file.
+fn custom_actions(settings: &Settings) -> impl Iterator<Item = (&str, &CustomAction)> {
+    settings.custom_groups.iter()
+        .flat_map(|group| group.actions.iter().map(|action| (group.name.as_str(), action)))
+}
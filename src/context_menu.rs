@@ -3,46 +3,290 @@
 //! The context menu appears when a user clicks a notification icon with a secondary mouse button.
 //! The menu is the primary control center of the Rectangular application, as Rectangular does not
 //! have any windows.
+//!
+//! What the menu contains is described by a [`MenuModel`]: a tree of items, separators and
+//! submenus, free of any Windows API, so it can be built and compared anywhere. [`ContextMenu`]
+//! turns the model into a Win32 menu, and builds it again whenever it's given a different model.
+//! Labels are shown as they are, names of applications and custom actions included, so `&` in them
+//! doesn't turn the next letter into a mnemonic.
+
+use std::cell::RefCell;
+use std::collections::HashSet;
+use std::error::Error;
+use std::fmt;
 
-use windows::core::PCSTR;
-use windows::s;
+use windows::core::PCWSTR;
 use windows::Win32::{
     Foundation::*, UI::WindowsAndMessaging::*,
 };
 
+use crate::autostart::AutostartState;
+use crate::command::Command;
+use crate::control_center_layout::ROWS;
+use crate::keyboard_layout::{hotkey_label, KeyMap};
+use crate::settings::Settings;
 use crate::{high_word_signed, low_word_signed};
 
-/// An array of tuples, each representing a descriptor of the context menu item.
+/// An item of the menu, which sends its command when chosen.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MenuItem {
+    /// Identifier of the item, this will be used by `WM_COMMAND` message when user selects the
+    /// item. It must be greater than zero, and unique in the whole menu, see
+    /// [`Command::menu_id`].
+    pub command: usize,
+    pub label: String,
+    /// Text describing a keyboard shortcut of the item, shown aligned to the right, e.g.
+    /// `Ctrl+Alt+Left`.
+    pub shortcut: Option<String>,
+    pub enabled: bool,
+    pub checked: bool,
+    /// Whether the check mark is a dot, for items which are one of several options.
+    pub radio: bool,
+}
+
+impl MenuItem {
+    /// Create an enabled item without a check mark or a shortcut.
+    pub fn new(command: usize, label: &str) -> MenuItem {
+        MenuItem {
+            command,
+            label: label.to_string(),
+            shortcut: None,
+            enabled: true,
+            checked: false,
+            radio: false,
+        }
+    }
+
+    /// Get the text of the item, with the shortcut separated by a tab, see [`menu_text`] for the
+    /// way Windows expects it.
+    pub fn text(&self) -> String {
+        match &self.shortcut {
+            Some(shortcut) => format!("{}\t{shortcut}", self.label),
+            None => self.label.clone(),
+        }
+    }
+}
+
+/// A node of a [`MenuModel`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MenuNode {
+    Item(MenuItem),
+    Separator,
+    Submenu { label: String, model: MenuModel },
+}
+
+/// A description of a menu, in the order the entries are shown.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MenuModel {
+    nodes: Vec<MenuNode>,
+}
+
+impl MenuModel {
+    /// Create an empty menu.
+    pub fn new() -> MenuModel {
+        MenuModel { nodes: Vec::new() }
+    }
+
+    pub fn item(&mut self, item: MenuItem) {
+        self.nodes.push(MenuNode::Item(item));
+    }
+
+    pub fn separator(&mut self) {
+        self.nodes.push(MenuNode::Separator);
+    }
+
+    pub fn submenu(&mut self, label: &str, model: MenuModel) {
+        self.nodes.push(MenuNode::Submenu { label: label.to_string(), model });
+    }
+
+    /// Get the entries of the menu, without entries of submenus.
+    pub fn nodes(&self) -> &[MenuNode] {
+        &self.nodes
+    }
+
+    /// Get all items of the menu, including ones in submenus, in the order they are shown.
+    pub fn items(&self) -> Vec<&MenuItem> {
+        self.nodes.iter()
+            .flat_map(|node| match node {
+                MenuNode::Item(item) => vec![item],
+                MenuNode::Separator => vec![],
+                MenuNode::Submenu { model, .. } => model.items(),
+            })
+            .collect()
+    }
+
+    /// Find the item with the command, wherever it is.
+    pub fn find(&self, command: usize) -> Option<&MenuItem> {
+        self.items().into_iter().find(|item| item.command == command)
+    }
+
+    /// Find the first command which is used by more than one item, or is zero, which Windows
+    /// reserves for a menu dismissed without choosing anything.
+    pub fn invalid_command(&self) -> Option<usize> {
+        let mut seen = HashSet::new();
+        self.items().into_iter()
+            .map(|item| item.command)
+            .find(|command| *command == 0 || !seen.insert(*command))
+    }
+
+    /// Check that the menu can be built, that is every item can be told apart when it's chosen.
+    pub fn validate(&self) -> Result<(), MenuError> {
+        match self.invalid_command() {
+            Some(command) => Err(MenuError::InvalidCommand(command)),
+            None => Ok(()),
+        }
+    }
+}
+
+/// A model which can't be turned into a menu.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum MenuError {
+    /// The command is zero or is used by more than one item, see [`MenuModel::invalid_command`].
+    InvalidCommand(usize),
+}
+
+impl fmt::Display for MenuError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            MenuError::InvalidCommand(0) => write!(f, "an item of the menu has no command"),
+            MenuError::InvalidCommand(command) => {
+                write!(f, "more than one item of the menu has the command {command}")
+            },
+        }
+    }
+}
+
+impl Error for MenuError {}
+
+impl Extend<MenuItem> for MenuModel {
+    fn extend<T: IntoIterator<Item = MenuItem>>(&mut self, items: T) {
+        self.nodes.extend(items.into_iter().map(MenuNode::Item));
+    }
+}
+
+/// The state of the application shown in the menu, like check marks of toggles.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MenuState {
+    pub paused: bool,
+    /// The executable name of the window commands are meant for, `None` if there's no such window.
+    pub app: Option<String>,
+    /// Whether the application is on the ignore list.
+    pub app_ignored: bool,
+    /// Whether Rectangular starts at login, see [`autostart`](crate::autostart).
+    pub start_at_login: AutostartState,
+}
+
+/// Get the model of the context menu for the given settings and state.
+///
+/// Shortcuts are labeled with the keys they are on in the given keyboard layout, see
+/// [`hotkey_label`]. Models are compared when the settings, the state or the layout change, and
+/// the menu is rebuilt only if they differ.
 ///
-/// The tuple members are:
-/// 0. Menu item flags ([`MENU_ITEM_FLAGS`]).
-/// 1. Index of the menu entry, this will be used by `WM_COMMAND` message when user selects an
-/// entry.
-/// 2. Title of the entry.
-const MENU_ENTRIES: [(MENU_ITEM_FLAGS, usize, PCSTR); 1] = [
-    (MF_STRING, 1, s!("Exit"))
-];
+/// Commands without a menu ID, see [`Command::menu_id`], are left out, as choosing them couldn't
+/// be told apart from dismissing the menu.
+pub fn model(settings: &Settings, state: &MenuState, keys: &dyn KeyMap) -> MenuModel {
+    let item = |command: Command, label: &str| {
+        command.menu_id(settings).map(|id| MenuItem::new(id, label))
+    };
+
+    let mut model = MenuModel::new();
+    model.submenu("Arrange", arrange_model(settings, keys));
+    for group in &settings.custom_groups {
+        let mut actions = MenuModel::new();
+        for action in &group.actions {
+            let command = Command::ApplyLayout { group: group.name.clone(),
+                                                 action: action.name.clone() };
+            actions.extend(item(command, &action.name));
+        }
+        if !actions.nodes().is_empty() {
+            model.submenu(&group.name, actions);
+        }
+    }
+    model.separator();
+    if let Some(pause) = item(Command::Pause, "Pause") {
+        model.item(MenuItem {
+            checked: state.paused,
+            shortcut: settings.pause.hotkey.map(|hotkey| hotkey_label(&hotkey, keys)),
+            ..pause
+        });
+    }
+    let ignore_label = match &state.app {
+        Some(app) => format!("Ignore {app}"),
+        None => "Ignore Application".to_string(),
+    };
+    if let Some(ignore) = item(Command::IgnoreApp, &ignore_label) {
+        model.item(MenuItem { checked: state.app_ignored, enabled: state.app.is_some(), ..ignore });
+    }
+    model.separator();
+    // A stale entry doesn't start this executable, choosing the item points it here.
+    let start_at_login_label = match state.start_at_login {
+        AutostartState::Stale { .. } => "Start at Login (moved)",
+        _ => "Start at Login",
+    };
+    if let Some(start_at_login) = item(Command::StartAtLogin, start_at_login_label) {
+        model.item(MenuItem {
+            checked: state.start_at_login == AutostartState::Enabled,
+            ..start_at_login
+        });
+    }
+    model.extend(item(Command::OpenSettings, "Open Settings"));
+    model.extend(item(Command::ReloadSettings, "Reload Settings"));
+    model.separator();
+    model.extend(item(Command::About, "About Rectangular"));
+    model.extend(item(Command::Exit, "Exit"));
+
+    model
+}
+
+/// Get the model of the submenu with all actions, grouped like in the Control Center, with their
+/// shortcuts next to them.
+fn arrange_model(settings: &Settings, keys: &dyn KeyMap) -> MenuModel {
+    let mut model = MenuModel::new();
+
+    for (index, (_, actions)) in ROWS.iter().enumerate() {
+        if index > 0 {
+            model.separator();
+        }
+
+        for action in *actions {
+            if let Some(id) = Command::Apply(*action).menu_id(settings) {
+                let shortcut = settings.hotkeys.get(*action)
+                    .map(|hotkey| hotkey_label(hotkey, keys));
+                model.item(MenuItem { shortcut, ..MenuItem::new(id, action.label()) });
+            }
+        }
+    }
+
+    model
+}
+
+/// Encode the text for a Win32 menu: as a null-terminated UTF-16 string, with `&` doubled, so it's
+/// shown rather than underlining the next letter.
+pub fn menu_text(text: &str) -> Vec<u16> {
+    text.replace('&', "&&").encode_utf16().chain([0]).collect()
+}
 
 /// ContextMenu includes data and methods strictly related to the context menu of a notification
 /// icon.
 pub struct ContextMenu {
     menu_handle: HMENU,
     window_handle: HWND,
+    /// The model the menu has been built from.
+    model: RefCell<MenuModel>,
 }
 
 impl Default for ContextMenu {
     fn default() -> Self {
         ContextMenu {
             menu_handle: Default::default(),
-            window_handle: Default::default()
+            window_handle: Default::default(),
+            model: Default::default(),
         }
     }
 }
 
 impl ContextMenu {
-    /// Create a new context menu.
-    ///
-    /// Upon creating, all menu entries from [`MENU_ENTRIES`] are added to the menu.
+    /// Create a new, empty context menu, see [`ContextMenu::rebuild`] for filling it.
     ///
     /// # Panics
     ///
@@ -50,13 +294,36 @@ impl ContextMenu {
     pub fn new(window_handle: HWND) -> ContextMenu {
         unsafe {
             let menu_handle = CreatePopupMenu().expect("Could not create popup menu.");
+            ContextMenu { menu_handle, window_handle, model: Default::default() }
+        }
+    }
 
-            for (flags, id, title) in &MENU_ENTRIES {
-                AppendMenuA(menu_handle, *flags, *id, *title);
-            }
+    /// Build the menu from the given model, unless the menu already shows it.
+    ///
+    /// The menu handle stays the same, so the menu can be rebuilt while it's shared. A model which
+    /// isn't valid, see [`MenuModel::validate`], is not built, and the menu stays as it was.
+    pub fn rebuild(&self, model: &MenuModel) -> Result<(), MenuError> {
+        model.validate()?;
+        if *self.model.borrow() == *model {
+            return Ok(());
+        }
 
-            ContextMenu { menu_handle, window_handle }
+        unsafe {
+            // Submenus are destroyed together with their items.
+            while GetMenuItemCount(self.menu_handle) > 0 {
+                DeleteMenu(self.menu_handle, 0, MF_BYPOSITION);
+            }
         }
+
+        append(self.menu_handle, model);
+        *self.model.borrow_mut() = model.clone();
+
+        Ok(())
+    }
+
+    /// Get the model the menu has been built from.
+    pub fn model(&self) -> MenuModel {
+        self.model.borrow().clone()
     }
 
     /// Show the context menu.
@@ -85,4 +352,46 @@ impl Drop for ContextMenu {
             DestroyMenu(self.menu_handle);
         }
     }
-}
\ No newline at end of file
+}
+
+/// Append entries of the model to the menu, creating submenus on the way.
+fn append(menu_handle: HMENU, model: &MenuModel) {
+    for node in &model.nodes {
+        unsafe {
+            match node {
+                MenuNode::Item(item) => {
+                    let mut flags = MF_STRING;
+                    if !item.enabled {
+                        flags |= MF_GRAYED;
+                    }
+                    if item.checked && !item.radio {
+                        flags |= MF_CHECKED;
+                    }
+
+                    let text = menu_text(&item.text());
+                    AppendMenuW(menu_handle, flags, item.command, PCWSTR(text.as_ptr()));
+
+                    // Only this call gives the check mark the shape of a dot.
+                    if item.checked && item.radio {
+                        let command = item.command as u32;
+                        CheckMenuRadioItem(menu_handle, command, command, command,
+                                           MF_BYCOMMAND.0);
+                    }
+                },
+                MenuNode::Separator => {
+                    AppendMenuW(menu_handle, MF_SEPARATOR, 0, PCWSTR::null());
+                },
+                MenuNode::Submenu { label, model } => {
+                    let Ok(submenu) = CreatePopupMenu() else {
+                        continue;
+                    };
+                    append(submenu, model);
+
+                    let label = menu_text(label);
+                    AppendMenuW(menu_handle, MF_STRING | MF_POPUP, submenu.0 as usize,
+                                PCWSTR(label.as_ptr()));
+                },
+            }
+        }
+    }
+}
@@ -2,29 +2,147 @@
 //!
 //! "Control Center" is a pop-up window which shows up when user clicks on the tray icon with a left
 //! mouse button. The window presents possible options of arranging windows on a monitor.
+//!
+//! Buttons are placed by [`ControlCenterLayout`], which also tells which button is under the mouse,
+//! and drawn by [`control_center_view`]. Keys move the [`Focus`] between them. The window only
+//! forwards mouse and keyboard messages to them, paints what they produce, and posts the chosen
+//! action to the main window as [`WM_CONTROL_CENTER_ACTION`].
+//!
+//! Next to the buttons, there's a [grid picker](crate::grid_picker), which follows the mouse with
+//! a [`GridDrag`]. A span selected in it is posted to the main window as
+//! [`WM_CONTROL_CENTER_GRID`].
+//!
+//! The action of the highlighted or focused button is previewed on the screen. The Control Center
+//! only tells the main window which action that is, as [`WM_CONTROL_CENTER_PREVIEW`].
+//!
+//! When there's no window the actions could be applied to, e.g. because it has been closed, the
+//! Control Center shows an empty state explaining it instead of the buttons.
+//!
+//! The window can be resized, and its content reflows, see [`ControlCenterLayout`]. It can't get
+//! smaller than the layout allows, and the size chosen by the user is posted to the main window as
+//! [`WM_CONTROL_CENTER_RESIZED`], which remembers it in the settings. Sizes are scaled for the
+//! DPI of the display the window is on, and the content is laid out again when the window moves
+//! to a display with a different scaling.
+//!
+//! Colors come from a [`Theme`], which follows the system unless the settings pick one, so it's
+//! resolved again whenever the Control Center is shown, and whenever the system settings change.
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::ffi::c_void;
 use std::rc::Rc;
 
 use windows::s;
-use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, POINT, RECT, SIZE, WPARAM};
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, POINT, RECT, SIZE,
+                                 WPARAM};
+use windows::Win32::Graphics::Gdi::{GetMonitorInfoA, HDC, InvalidateRect,
+                                    MONITOR_DEFAULTTONEAREST, MONITORINFO, MonitorFromPoint};
+use windows::Win32::UI::HiDpi::{AdjustWindowRectExForDpi, GetDpiForMonitor, MDT_EFFECTIVE_DPI};
+use windows::Win32::UI::Input::KeyboardAndMouse::{GetKeyState, ReleaseCapture, SetCapture,
+                                                  TME_LEAVE, TRACKMOUSEEVENT, TrackMouseEvent,
+                                                  VIRTUAL_KEY, VK_DOWN, VK_ESCAPE, VK_LEFT,
+                                                  VK_RETURN, VK_RIGHT, VK_SHIFT, VK_SPACE, VK_TAB,
+                                                  VK_UP};
 use windows::Win32::UI::WindowsAndMessaging::*;
 
+use crate::action::WindowAction;
 use crate::app_window::AppWindow;
-use crate::{high_word_signed, low_word, low_word_signed, make_window_object, WINDOW_CLASS_NAME};
+use crate::control_center_focus::{Direction, Focus, Key, KeyOutcome};
+use crate::control_center_layout::{scale, unscale, ControlCenterLayout, GridPanel, BASE_DPI};
+use crate::control_center_view::{self, ButtonStates, Screen};
+use crate::geometry::Rect;
+use crate::grid_picker::{CellSpan, GridDrag};
+use crate::gdi_painter;
+use crate::settings::{ControlCenterPosition, ControlCenterSettings, ThemeSettings};
+use crate::theme::{SystemAppearance, Theme};
+use crate::{high_word, high_word_signed, low_word, low_word_signed, make_window_object,
+            WINDOW_CLASS_NAME};
+
+/// A message posted to the main window when an action has been chosen in the Control Center.
+///
+/// `wparam` holds the index of the action in [`WindowAction::ALL`].
+pub const WM_CONTROL_CENTER_ACTION: u32 = WM_USER + 3;
+
+/// A message posted to the main window when a span of cells has been selected in the grid picker.
+///
+/// `wparam` holds the span [packed](CellSpan::pack) into a number, and `lparam` the number of
+/// columns in the low word and rows in the high word of the grid it has been selected in.
+pub const WM_CONTROL_CENTER_GRID: u32 = WM_USER + 5;
 
-const WINDOW_WIDTH: i32 = 300;
-const WINDOW_HEIGHT: i32 = 200;
+/// A message posted to the main window when the previewed action changes, see
+/// [`preview`](crate::preview).
+///
+/// `wparam` holds the index of the action in [`WindowAction::ALL`] plus one, or zero when no action
+/// should be previewed anymore.
+pub const WM_CONTROL_CENTER_PREVIEW: u32 = WM_USER + 6;
+
+/// A message posted to the main window when the user has finished resizing the Control Center.
+///
+/// `wparam` holds the width of the client area in the low word, and the height in the high word,
+/// both in pixels at [`BASE_DPI`](crate::control_center_layout::BASE_DPI).
+pub const WM_CONTROL_CENTER_RESIZED: u32 = WM_USER + 7;
+
+/// Styles of the Control Center window, needed to compute the window size from the client size.
+const STYLE: WINDOW_STYLE = WINDOW_STYLE(WS_POPUP.0 | WS_THICKFRAME.0);
+const EX_STYLE: WINDOW_EX_STYLE = WS_EX_PALETTEWINDOW;
+
+/// Sent when the mouse leaves the window, after asking for it with `TrackMouseEvent`.
+///
+/// The `windows` crate declares it among common controls, which aren't used otherwise.
+const WM_MOUSELEAVE: u32 = 0x02A3;
 
 pub struct ControlCenter {
     window_handle: HWND,
+    settings: ControlCenterSettings,
+    theme_settings: ThemeSettings,
+    /// The theme resolved from [`ControlCenter::theme_settings`] and the system appearance.
+    theme: Cell<Theme>,
+
+    /// The window which is told about chosen actions, see [`WM_CONTROL_CENTER_ACTION`].
+    notify_window: HWND,
+
+    layout: RefCell<ControlCenterLayout>,
+    /// The action whose button is under the mouse cursor.
+    hovered: Cell<Option<WindowAction>>,
+    /// The action whose button has been pressed, and not released yet.
+    pressed: Cell<Option<WindowAction>>,
+    /// Whether the window has asked for `WM_MOUSELEAVE`, which has to be done after every leave.
+    tracking_mouse: Cell<bool>,
+    /// The monitor shown in miniatures on buttons, the one the Control Center is shown on.
+    screen: Cell<Screen>,
+    focus: RefCell<Focus>,
+    /// The monitor of the window actions are applied to, shown in the grid picker. If there's no
+    /// such window, the empty state is shown.
+    target: Cell<Option<Screen>>,
+    grid_panel: Cell<GridPanel>,
+    grid_drag: Cell<GridDrag>,
+    /// The action the main window has been told to preview.
+    previewed: Cell<Option<WindowAction>>,
+    /// The client size when the user started resizing the window, `None` when not resizing.
+    size_before_resizing: Cell<Option<(i32, i32)>>,
+    /// Whether [`show`](Self::show) is placing the window, already sized for its display.
+    placing: Cell<bool>,
 }
 
 impl Default for ControlCenter {
     fn default() -> Self {
         ControlCenter {
             window_handle: HWND(0),
+            settings: Default::default(),
+            theme_settings: Default::default(),
+            theme: Default::default(),
+            notify_window: HWND(0),
+            layout: Default::default(),
+            hovered: Cell::new(None),
+            pressed: Cell::new(None),
+            tracking_mouse: Cell::new(false),
+            screen: Default::default(),
+            focus: Default::default(),
+            target: Cell::new(Some(Screen::default())),
+            grid_panel: Default::default(),
+            grid_drag: Default::default(),
+            previewed: Cell::new(None),
+            size_before_resizing: Cell::new(None),
+            placing: Cell::new(false),
         }
     }
 }
@@ -43,25 +161,192 @@ impl AppWindow for ControlCenter {
                 if low_word!(wparam.0) as u32 == WA_INACTIVE {
                     self.hide();
                 }
-            }
+            },
+            // The app mode and high contrast are announced by both, depending on the version.
+            WM_SETTINGCHANGE | WM_SYSCOLORCHANGE => {
+                self.refresh_theme();
+            },
+            WM_SIZE => {
+                let width = low_word!(lparam.0) as i32;
+                let height = high_word!(lparam.0) as i32;
+                self.relayout(width, height);
+            },
+            // The window has been moved to a display with a different scaling, or the scaling has
+            // changed. The suggested rectangle keeps the window the same size to the eye, unless
+            // the window is being shown, and has been sized for the new display already.
+            WM_DPICHANGED => {
+                if !self.placing.get() {
+                    let suggested = unsafe { *(lparam.0 as *const RECT) };
+                    unsafe {
+                        SetWindowPos(self.window_handle, None, suggested.left, suggested.top,
+                                     suggested.right - suggested.left,
+                                     suggested.bottom - suggested.top,
+                                     SWP_NOZORDER | SWP_NOACTIVATE);
+                    }
+                }
+
+                // The size in pixels might stay the same, so WM_SIZE isn't enough.
+                let (width, height) = self.client_size();
+                self.relayout(width, height);
+            },
+            WM_GETMINMAXINFO => {
+                let dpi = self.dpi();
+                let (width, height) = self.minimum_client_size(dpi);
+                let (width, height) = window_size_for(width, height, dpi);
+                let info = lparam.0 as *mut MINMAXINFO;
+                unsafe {
+                    (*info).ptMinTrackSize = POINT { x: width, y: height };
+                }
+            },
+            WM_ENTERSIZEMOVE => {
+                self.size_before_resizing.set(Some(self.client_size()));
+            },
+            WM_EXITSIZEMOVE => {
+                let before = self.size_before_resizing.take();
+                let (width, height) = self.client_size();
+                if before.is_some_and(|before| before != (width, height)) {
+                    let dpi = self.dpi();
+                    let size = unscale(width, dpi) as usize | (unscale(height, dpi) as usize) << 16;
+                    unsafe {
+                        PostMessageA(self.notify_window, WM_CONTROL_CENTER_RESIZED, WPARAM(size),
+                                     LPARAM(0));
+                    }
+                }
+            },
+            WM_MOUSEMOVE => {
+                let action = self.hit_test(lparam);
+                self.track_mouse();
+                let grid_changed = self.update_grid_drag(|drag, panel, x, y| {
+                    drag.move_to(&panel.grid, x, y)
+                }, lparam);
+                if action != self.hovered.replace(action) || grid_changed {
+                    self.invalidate();
+                }
+            },
+            WM_MOUSELEAVE => {
+                self.tracking_mouse.set(false);
+                let grid_changed = self.update_grid_drag(|drag, _, _, _| {
+                    let selection = drag.selection();
+                    drag.leave();
+                    selection != drag.selection()
+                }, lparam);
+                if self.hovered.take().is_some() || grid_changed {
+                    self.invalidate();
+                }
+            },
+            WM_LBUTTONDOWN => {
+                if self.update_grid_drag(|drag, panel, x, y| drag.press(&panel.grid, x, y),
+                                         lparam) {
+                    unsafe { SetCapture(self.window_handle); }
+                    self.invalidate();
+                    return LRESULT(0);
+                }
+
+                let action = self.hit_test(lparam);
+                if action.is_some() {
+                    unsafe { SetCapture(self.window_handle); }
+                }
+                self.pressed.set(action);
+                self.invalidate();
+            },
+            WM_LBUTTONUP => {
+                let mut span = None;
+                self.update_grid_drag(|drag, panel, x, y| {
+                    span = drag.release(&panel.grid, x, y);
+                    span.is_some()
+                }, lparam);
+                if let Some(span) = span {
+                    unsafe { ReleaseCapture(); }
+                    self.invalidate();
+                    self.choose_span(span);
+                    return LRESULT(0);
+                }
+
+                let pressed = self.pressed.take();
+                unsafe { ReleaseCapture(); }
+                self.invalidate();
+
+                // An action is chosen only when the mouse is released over the same button it has
+                // been pressed on, like with regular buttons.
+                let released = self.hit_test(lparam);
+                if let Some(action) = pressed.filter(|action| released == Some(*action)) {
+                    self.choose(action);
+                }
+            },
+            WM_CAPTURECHANGED => {
+                let dragging = self.update_grid_drag(|drag, _, _, _| {
+                    let dragging = drag.is_dragging();
+                    drag.cancel();
+                    dragging
+                }, lparam);
+                if self.pressed.take().is_some() || dragging {
+                    self.invalidate();
+                }
+            },
+            WM_KEYDOWN => {
+                let key = match VIRTUAL_KEY(wparam.0 as u16) {
+                    VK_LEFT => Key::Arrow(Direction::Left),
+                    VK_RIGHT => Key::Arrow(Direction::Right),
+                    VK_UP => Key::Arrow(Direction::Up),
+                    VK_DOWN => Key::Arrow(Direction::Down),
+                    VK_TAB => Key::Tab { backwards: unsafe { GetKeyState(VK_SHIFT.0 as i32) } < 0 },
+                    VK_RETURN => Key::Enter,
+                    VK_SPACE => Key::Space,
+                    VK_ESCAPE => Key::Escape,
+                    _ => return LRESULT(0),
+                };
+                self.handle_key(key);
+            },
+            // Mnemonics come as characters, so they follow the keyboard layout.
+            WM_CHAR => {
+                if let Some(character) = char::from_u32(wparam.0 as u32) {
+                    self.handle_key(Key::Char(character));
+                }
+            },
             _ => unsafe { return DefWindowProcW(self.window_handle, message, wparam, lparam); }
         }
 
         LRESULT(0)
     }
+
+    fn paint(&self, hdc: HDC, area: &RECT) -> bool {
+        let mut client = RECT::default();
+        unsafe { GetClientRect(self.window_handle, &mut client); }
+
+        let theme = self.theme.get();
+        let dpi = self.dpi();
+
+        if self.target.get().is_none() {
+            let list = control_center_view::render_empty(&client.into(), dpi, &theme);
+            gdi_painter::paint(hdc, &(*area).into(), &list, &theme);
+            return true;
+        }
+
+        let states = self.button_states();
+        let mut list = control_center_view::render(&self.layout.borrow(), &states,
+                                                   &self.screen.get(), &client.into(), dpi,
+                                                   &theme);
+        control_center_view::render_grid(&mut list, &self.grid_panel.get(),
+                                         &self.grid_drag.get(), dpi, &theme);
+        gdi_painter::paint(hdc, &(*area).into(), &list, &theme);
+
+        true
+    }
 }
 
 impl ControlCenter {
-    pub fn new(instance: HINSTANCE) -> Rc<RefCell<ControlCenter>> {
+    /// Create the Control Center window, which tells `notify_window` about chosen actions.
+    pub fn new(instance: HINSTANCE, notify_window: HWND) -> Rc<RefCell<ControlCenter>> {
         let (ptr, raw) = make_window_object!(ControlCenter);
+        ptr.borrow_mut().notify_window = notify_window;
 
         unsafe {
             CreateWindowExA(
-                WS_EX_PALETTEWINDOW,
+                EX_STYLE,
                 WINDOW_CLASS_NAME,
                 s!(""),
-                WS_POPUP | WS_THICKFRAME,
-                0, 0, WINDOW_WIDTH, WINDOW_HEIGHT,
+                STYLE,
+                0, 0, 0, 0,
                 HWND::default(),
                 None,
                 instance,
@@ -72,27 +357,91 @@ impl ControlCenter {
         ptr
     }
 
+    /// The handle of the Control Center window.
+    pub fn handle(&self) -> HWND {
+        self.window_handle
+    }
+
+    /// Apply the Control Center part of the settings.
+    pub fn configure(&mut self, settings: ControlCenterSettings) {
+        self.settings = settings;
+    }
+
+    /// Apply the theme part of the settings, and repaint the window with it.
+    pub fn set_theme(&mut self, settings: ThemeSettings) {
+        self.theme_settings = settings;
+        self.refresh_theme();
+    }
+
+    /// The theme the Control Center is drawn with, which the placement preview follows too.
+    pub fn theme(&self) -> Theme {
+        self.theme.get()
+    }
+
     /// Show the Control Center window.
     ///
     /// The Control Center window is created along with the Rectangular's main window, but it's
     /// initially hidden. When called, this method shows the window near the notification area, and
     /// sets it as the foreground window. The proper position can be calculated from the `wparam`
     /// parameter which is usually obtained from a messages sent when the notification icon has been
-    /// clicked.
-    pub fn show(&self, wparam: WPARAM) {
+    /// clicked. If the settings say so, the window is centered on the monitor with the notification
+    /// area instead.
+    ///
+    /// `target` is the monitor of the window actions are applied to, which is shown in the grid
+    /// picker. If there's no such window, the empty state is shown instead.
+    pub fn show(&self, wparam: WPARAM, target: Option<Screen>) {
+        self.focus.borrow_mut().reset();
+        self.grid_drag.set(GridDrag::default());
+        self.target.set(target);
+        self.refresh_theme();
+
         unsafe {
             let x = low_word_signed!(wparam.0) as i32;
             let y = high_word_signed!(wparam.0) as i32;
             let point = POINT { x, y };
-            let size = SIZE { cx: WINDOW_WIDTH, cy: WINDOW_HEIGHT };
+
+            // The monitor with the notification area is also the one shown in the miniatures.
+            let monitor = MonitorFromPoint(point, MONITOR_DEFAULTTONEAREST);
+            let (mut dpi, mut dpi_y) = (BASE_DPI, BASE_DPI);
+            if GetDpiForMonitor(monitor, MDT_EFFECTIVE_DPI, &mut dpi, &mut dpi_y).is_err() {
+                dpi = self.dpi();
+            }
+
+            let (width, height) = self.window_size(dpi);
+            let size = SIZE { cx: width, cy: height };
             let mut result = RECT::default();
 
             let calc_flags = TPM_CENTERALIGN | TPM_VCENTERALIGN | TPM_VERTICAL | TPM_WORKAREA;
-            let show_flags = SWP_NOSIZE | SWP_SHOWWINDOW;
 
-            CalculatePopupWindowPosition(&point, &size, calc_flags.0, None, &mut result);
-            SetWindowPos(self.window_handle, HWND_TOPMOST, result.left, result.top, 0, 0,
-                         show_flags);
+            let mut info = MONITORINFO {
+                cbSize: std::mem::size_of::<MONITORINFO>() as u32,
+                ..Default::default()
+            };
+            if GetMonitorInfoA(monitor, &mut info).as_bool() {
+                self.screen.set(Screen { monitor: info.rcMonitor.into(),
+                                         work_area: info.rcWork.into() });
+            }
+
+            match self.settings.position {
+                ControlCenterPosition::NearTray => {
+                    CalculatePopupWindowPosition(&point, &size, calc_flags.0, None, &mut result);
+                },
+                ControlCenterPosition::ScreenCenter => {
+                    let area = info.rcWork;
+                    result.left = area.left + (area.right - area.left - width) / 2;
+                    result.top = area.top + (area.bottom - area.top - height) / 2;
+                },
+            }
+
+            self.placing.set(true);
+            SetWindowPos(self.window_handle, HWND_TOPMOST, result.left, result.top, width, height,
+                         SWP_SHOWWINDOW);
+            self.placing.set(false);
+
+            // The grid depends on the target, even if the size of the window hasn't changed.
+            let mut client = RECT::default();
+            GetClientRect(self.window_handle, &mut client);
+            self.relayout(client.right - client.left, client.bottom - client.top);
             SetForegroundWindow(self.window_handle);
         }
     }
@@ -105,5 +454,202 @@ impl ControlCenter {
         unsafe {
             ShowWindow(self.window_handle, SW_HIDE);
         }
+
+        self.hovered.set(None);
+        self.pressed.set(None);
+        self.focus.borrow_mut().reset();
+        self.update_preview();
+    }
+
+    /// Show the empty state instead of the buttons, because the window actions were meant for is
+    /// gone.
+    pub fn show_empty_state(&self) {
+        self.target.set(None);
+        self.grid_drag.set(GridDrag::default());
+        self.hovered.set(None);
+        self.pressed.set(None);
+        self.invalidate();
     }
+
+    /// Tell the main window about the chosen action.
+    ///
+    /// The main window applies it, and decides whether the Control Center should be hidden.
+    fn choose(&self, action: WindowAction) {
+        if self.target.get().is_none() {
+            return;
+        }
+
+        let Some(index) = WindowAction::ALL.iter().position(|other| *other == action) else {
+            return;
+        };
+
+        unsafe {
+            PostMessageA(self.notify_window, WM_CONTROL_CENTER_ACTION, WPARAM(index), LPARAM(0));
+        }
+    }
+
+    fn button_states(&self) -> ButtonStates {
+        ButtonStates {
+            hovered: self.hovered.get(),
+            pressed: self.pressed.get(),
+            focused: self.focus.borrow().focused(),
+        }
+    }
+
+    /// Tell the main window about the span selected in the grid picker.
+    fn choose_span(&self, span: CellSpan) {
+        if self.target.get().is_none() {
+            return;
+        }
+
+        let size = self.grid_panel.get().grid.size();
+        let packed_size = (size.columns | size.rows << 16) as isize;
+
+        unsafe {
+            PostMessageA(self.notify_window, WM_CONTROL_CENTER_GRID, WPARAM(span.pack() as usize),
+                         LPARAM(packed_size));
+        }
+    }
+
+    /// Arrange the buttons and the grid picker in a client area of the given size.
+    fn relayout(&self, width: i32, height: i32) {
+        let dpi = self.dpi();
+        let client = Rect::new(0, 0, width, height);
+        let buttons_width = (width - GridPanel::width(dpi)).max(0);
+        *self.layout.borrow_mut() = ControlCenterLayout::new(buttons_width, height, dpi);
+
+        let screen = self.target.get().unwrap_or_default();
+        let size = self.settings.grid.size_for(&screen.monitor);
+        self.grid_panel.set(GridPanel::new(&client, dpi, size, &screen.work_area));
+
+        self.invalidate();
+    }
+
+    /// Update the grid drag with the point given in `lparam` of a mouse message, returning what
+    /// `update` returns.
+    fn update_grid_drag(&self, update: impl FnOnce(&mut GridDrag, &GridPanel, i32, i32) -> bool,
+                        lparam: LPARAM) -> bool {
+        let x = low_word_signed!(lparam.0) as i32;
+        let y = high_word_signed!(lparam.0) as i32;
+        let mut drag = self.grid_drag.get();
+        let result = update(&mut drag, &self.grid_panel.get(), x, y);
+        self.grid_drag.set(drag);
+        result
+    }
+
+    fn handle_key(&self, key: Key) {
+        let outcome = self.focus.borrow_mut().key(key, &self.layout.borrow());
+
+        match outcome {
+            KeyOutcome::Ignored => {},
+            KeyOutcome::FocusMoved => self.invalidate(),
+            KeyOutcome::Apply(action) => {
+                self.invalidate();
+                self.choose(action);
+            },
+            KeyOutcome::Hide => self.hide(),
+        }
+    }
+
+    /// Find the action whose button is at the point given in `lparam` of a mouse message.
+    fn hit_test(&self, lparam: LPARAM) -> Option<WindowAction> {
+        let x = low_word_signed!(lparam.0) as i32;
+        let y = high_word_signed!(lparam.0) as i32;
+        self.layout.borrow().hit_test(x, y)
+    }
+
+    /// Ask for `WM_MOUSELEAVE`, unless it has already been asked for.
+    fn track_mouse(&self) {
+        if self.tracking_mouse.replace(true) {
+            return;
+        }
+
+        let mut event = TRACKMOUSEEVENT {
+            cbSize: std::mem::size_of::<TRACKMOUSEEVENT>() as u32,
+            dwFlags: TME_LEAVE,
+            hwndTrack: self.window_handle,
+            dwHoverTime: 0,
+        };
+
+        unsafe { TrackMouseEvent(&mut event); }
+    }
+
+    /// Repaint the window, and update the preview, which follows the same state.
+    fn invalidate(&self) {
+        unsafe { InvalidateRect(self.window_handle, None, false); }
+        self.update_preview();
+    }
+
+    /// Tell the main window about a change of the action to preview.
+    ///
+    /// The action of the highlighted button is previewed, or the focused one if the keyboard is
+    /// used. Nothing is previewed in the empty state, or while the window is hidden.
+    fn update_preview(&self) {
+        let visible = unsafe { IsWindowVisible(self.window_handle).as_bool() };
+        let states = self.button_states();
+        let action = states.highlighted().or(states.focused)
+            .filter(|_| visible && self.target.get().is_some());
+
+        if action == self.previewed.replace(action) {
+            return;
+        }
+
+        let index = action
+            .and_then(|action| WindowAction::ALL.iter().position(|other| *other == action))
+            .map_or(0, |index| index + 1);
+
+        unsafe {
+            PostMessageA(self.notify_window, WM_CONTROL_CENTER_PREVIEW, WPARAM(index), LPARAM(0));
+        }
+    }
+
+    /// Resolve the theme again, as the system appearance might have changed, and repaint the
+    /// window if the theme is different.
+    fn refresh_theme(&self) {
+        let theme = Theme::resolve(&self.theme_settings, SystemAppearance::current());
+        if theme != self.theme.replace(theme) {
+            unsafe { InvalidateRect(self.window_handle, None, false); }
+        }
+    }
+
+    /// Get the DPI of the display the Control Center is on.
+    fn dpi(&self) -> u32 {
+        gdi_painter::window_dpi(self.window_handle)
+    }
+
+    /// Get the size of the window to show on a display with the given DPI: the one remembered in
+    /// the settings, or the one in which buttons have their preferred size, but never less than
+    /// the minimum.
+    fn window_size(&self, dpi: u32) -> (i32, i32) {
+        let (preferred_width, preferred_height) = ControlCenterLayout::preferred_size(dpi);
+        let (minimum_width, minimum_height) = self.minimum_client_size(dpi);
+
+        let width = self.settings.width
+            .map_or(preferred_width + GridPanel::width(dpi), |width| scale(width as i32, dpi));
+        let height = self.settings.height
+            .map_or(preferred_height, |height| scale(height as i32, dpi));
+
+        window_size_for(width.max(minimum_width), height.max(minimum_height), dpi)
+    }
+
+    /// Get the smallest client size in which buttons and the grid picker are still usable.
+    fn minimum_client_size(&self, dpi: u32) -> (i32, i32) {
+        let (width, height) = ControlCenterLayout::minimum_size(dpi);
+        (width + GridPanel::width(dpi), height)
+    }
+
+    fn client_size(&self) -> (i32, i32) {
+        let mut client = RECT::default();
+        unsafe { GetClientRect(self.window_handle, &mut client); }
+        (client.right - client.left, client.bottom - client.top)
+    }
+}
+
+/// Get the size of the Control Center window with a client area of the given size, on a display
+/// with the given DPI.
+fn window_size_for(width: i32, height: i32, dpi: u32) -> (i32, i32) {
+    let mut rect = RECT { left: 0, top: 0, right: width, bottom: height };
+    unsafe { AdjustWindowRectExForDpi(&mut rect, STYLE, false, EX_STYLE, dpi); }
+
+    (rect.right - rect.left, rect.bottom - rect.top)
 }
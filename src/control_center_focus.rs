@@ -0,0 +1,213 @@
+//! This module contains keyboard navigation of the Control Center.
+//!
+//! The Control Center can be used without the mouse:
+//!
+//! * arrow keys move the focus between buttons, the first one of them focuses the first button,
+//! * `Tab` and `Shift+Tab` move the focus in reading order, wrapping around,
+//! * `Enter` and `Space` apply the focused action,
+//! * `Esc` hides the Control Center,
+//! * mnemonics apply actions directly: `H`, `L`, `T` and `B` the halves (left and right like in
+//!   Vim), `1`, `2` and `3` the thirds, `M` maximizes, and `C` centers.
+//!
+//! Navigation is spatial: moving up or down picks the button of the adjacent line which is the
+//! closest horizontally, so it works the same for rows of different lengths, and for rows wrapped
+//! onto more lines. The column the user has been moving along is remembered, so going down from a
+//! narrow button through a row of wide ones, and back up, returns to where it started.
+
+use crate::action::WindowAction;
+use crate::control_center_layout::{Button, ControlCenterLayout};
+
+/// A direction of an arrow key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    Left,
+    Right,
+    Up,
+    Down,
+}
+
+/// A key handled by the Control Center.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Key {
+    Arrow(Direction),
+    Tab { backwards: bool },
+    Enter,
+    Space,
+    Escape,
+    /// A typed character, which may be a mnemonic.
+    Char(char),
+}
+
+/// What the Control Center should do after a key has been pressed.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyOutcome {
+    /// The key does nothing.
+    Ignored,
+    /// The focus has moved, so the window needs repainting.
+    FocusMoved,
+    /// The action should be applied.
+    Apply(WindowAction),
+    /// The Control Center should be hidden.
+    Hide,
+}
+
+/// Get the action applied directly by a key.
+pub fn mnemonic(key: char) -> Option<WindowAction> {
+    match key.to_ascii_uppercase() {
+        'H' => Some(WindowAction::LeftHalf),
+        'L' => Some(WindowAction::RightHalf),
+        'T' => Some(WindowAction::TopHalf),
+        'B' => Some(WindowAction::BottomHalf),
+        '1' => Some(WindowAction::FirstThird),
+        '2' => Some(WindowAction::CenterThird),
+        '3' => Some(WindowAction::LastThird),
+        'M' => Some(WindowAction::Maximize),
+        'C' => Some(WindowAction::Center),
+        _ => None,
+    }
+}
+
+/// The keyboard focus of the Control Center.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Focus {
+    focused: Option<WindowAction>,
+    /// The horizontal position, as a fraction of the row width, kept while moving up and down.
+    column: Option<f64>,
+}
+
+impl Focus {
+    /// The focused action, `None` until the keyboard is used.
+    pub fn focused(&self) -> Option<WindowAction> {
+        self.focused
+    }
+
+    /// Remove the focus, e.g. when the Control Center is shown again.
+    pub fn reset(&mut self) {
+        *self = Focus::default();
+    }
+
+    /// Handle a key pressed in the Control Center.
+    pub fn key(&mut self, key: Key, layout: &ControlCenterLayout) -> KeyOutcome {
+        match key {
+            Key::Arrow(direction) => {
+                let target = match self.focused {
+                    None => layout.buttons().next().map(|button| button.action),
+                    Some(focused) => self.neighbor(layout, focused, direction),
+                };
+
+                if matches!(direction, Direction::Left | Direction::Right) {
+                    self.column = None;
+                }
+
+                self.move_to(target)
+            },
+            Key::Tab { backwards } => {
+                let actions: Vec<WindowAction> = layout.buttons().map(|button| button.action)
+                    .collect();
+                let count = actions.len();
+                if count == 0 {
+                    return KeyOutcome::Ignored;
+                }
+
+                let position = self.focused
+                    .and_then(|focused| actions.iter().position(|action| *action == focused));
+
+                let next = match (position, backwards) {
+                    (None, false) => 0,
+                    (None, true) => count - 1,
+                    (Some(index), false) => (index + 1) % count,
+                    (Some(index), true) => (index + count - 1) % count,
+                };
+
+                self.column = None;
+                self.move_to(Some(actions[next]))
+            },
+            Key::Enter | Key::Space => match self.focused {
+                Some(action) => KeyOutcome::Apply(action),
+                None => KeyOutcome::Ignored,
+            },
+            Key::Escape => KeyOutcome::Hide,
+            Key::Char(character) => match mnemonic(character) {
+                Some(action) if layout.button(action).is_some() => {
+                    self.focused = Some(action);
+                    self.column = None;
+                    KeyOutcome::Apply(action)
+                },
+                _ => KeyOutcome::Ignored,
+            },
+        }
+    }
+
+    fn move_to(&mut self, target: Option<WindowAction>) -> KeyOutcome {
+        match target {
+            Some(action) if Some(action) != self.focused => {
+                self.focused = Some(action);
+                KeyOutcome::FocusMoved
+            },
+            _ => KeyOutcome::Ignored,
+        }
+    }
+
+    /// Find the button next to the focused one in the direction.
+    fn neighbor(&mut self, layout: &ControlCenterLayout, focused: WindowAction,
+                direction: Direction) -> Option<WindowAction> {
+        // Lines of a wrapped row share the span of its first line, so the last line, which may
+        // be shorter, keeps buttons in the same columns.
+        let lines: Vec<(&[Button], (i32, i32))> = layout.rows().iter()
+            .flat_map(|row| {
+                let span = row.lines().next().map_or((0, 0), row_span);
+                row.lines().map(move |line| (line, span))
+            })
+            .collect();
+        let line = lines.iter()
+            .position(|(buttons, _)| buttons.iter().any(|button| button.action == focused))?;
+        let (buttons, span) = lines[line];
+        let index = buttons.iter().position(|button| button.action == focused)?;
+
+        match direction {
+            Direction::Left => index.checked_sub(1).map(|index| buttons[index].action),
+            Direction::Right => buttons.get(index + 1).map(|button| button.action),
+            Direction::Up | Direction::Down => {
+                let (target_line, target_span) = match direction {
+                    Direction::Up => lines.get(line.checked_sub(1)?)?,
+                    _ => lines.get(line + 1)?,
+                };
+
+                let column = *self.column.get_or_insert_with(|| {
+                    let rect = &buttons[index].rect;
+                    center_fraction(rect.left, rect.right, span)
+                });
+
+                target_line.iter()
+                    .map(|button| {
+                        let center = center_fraction(button.rect.left, button.rect.right,
+                                                     *target_span);
+                        (button, (center - column).abs())
+                    })
+                    // The first of equally close buttons wins, so ties go to the left.
+                    .fold(None, |closest: Option<(WindowAction, f64)>, (button, distance)| {
+                        match closest {
+                            Some((_, best)) if best <= distance => closest,
+                            _ => Some((button.action, distance)),
+                        }
+                    })
+                    .map(|(action, _)| action)
+            },
+        }
+    }
+}
+
+/// Get the horizontal span of a line of buttons.
+fn row_span(buttons: &[Button]) -> (i32, i32) {
+    let left = buttons.first().map_or(0, |button| button.rect.left);
+    let right = buttons.last().map_or(0, |button| button.rect.right);
+    (left, right)
+}
+
+/// Get the center of a button as a fraction of the line span.
+fn center_fraction(left: i32, right: i32, (start, end): (i32, i32)) -> f64 {
+    match end - start {
+        width if width > 0 => ((left + right) as f64 / 2.0 - start as f64) / width as f64,
+        _ => 0.5,
+    }
+}
@@ -0,0 +1,301 @@
+//! This module contains the layout of the Control Center.
+//!
+//! The Control Center shows one button per action, arranged in rows of related actions: halves,
+//! quarters, thirds, and the remaining ones. Every row has a caption above it, and buttons of a row
+//! share its width evenly, so rows with fewer buttons get wider ones.
+//!
+//! The window can be resized, and the layout reflows to the new size: when buttons of a row would
+//! get narrower than [`MIN_BUTTON_WIDTH`], the row wraps onto more lines, with buttons spread
+//! evenly between them. Rows never wrap below [`MIN_COLUMNS`] buttons per line, which together with
+//! the minimum size of buttons gives [`ControlCenterLayout::minimum_size`].
+//!
+//! On the right of the buttons, there's a [`GridPanel`] with the [grid picker](crate::grid_picker),
+//! showing the work area of the monitor of the window being arranged.
+//!
+//! [`ControlCenterLayout`] computes where every caption and button goes for a given client size and
+//! DPI, and answers which action is under a point. It's free of any Windows API, so the window
+//! itself is left with forwarding mouse messages to it and painting what it computed.
+
+use crate::action::WindowAction;
+use crate::geometry::Rect;
+use crate::grid_picker::{Grid, GridSize};
+
+/// The DPI at which the metrics below are given, 100% scaling on Windows.
+pub const BASE_DPI: u32 = 96;
+
+/// A margin around all the content, at [`BASE_DPI`].
+const PADDING: i32 = 8;
+
+/// A space between adjacent buttons, and between rows, at [`BASE_DPI`].
+const SPACING: i32 = 6;
+
+/// A height of a row caption, at [`BASE_DPI`].
+const CAPTION_HEIGHT: i32 = 18;
+
+/// A size of a button in a window of the preferred size, at [`BASE_DPI`].
+const BUTTON_WIDTH: i32 = 60;
+const BUTTON_HEIGHT: i32 = 40;
+
+/// The smallest size of a button before rows wrap, at [`BASE_DPI`].
+pub const MIN_BUTTON_WIDTH: i32 = 40;
+pub const MIN_BUTTON_HEIGHT: i32 = 28;
+
+/// The smallest number of buttons in a line of a wrapped row.
+pub const MIN_COLUMNS: usize = 4;
+
+/// A width of the grid picker panel, at [`BASE_DPI`].
+const GRID_PANEL_WIDTH: i32 = 240;
+
+/// A group of related actions, shown in a single row.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Category {
+    Halves,
+    Quarters,
+    Thirds,
+    Other,
+}
+
+impl Category {
+    /// A caption of the row.
+    pub const fn title(self) -> &'static str {
+        match self {
+            Category::Halves => "Halves",
+            Category::Quarters => "Quarters",
+            Category::Thirds => "Thirds",
+            Category::Other => "Other",
+        }
+    }
+}
+
+/// Rows of the Control Center, from top to bottom, with actions in order of their buttons.
+pub const ROWS: [(Category, &[WindowAction]); 4] = [
+    (Category::Halves, &[
+        WindowAction::LeftHalf,
+        WindowAction::RightHalf,
+        WindowAction::TopHalf,
+        WindowAction::BottomHalf,
+    ]),
+    (Category::Quarters, &[
+        WindowAction::TopLeft,
+        WindowAction::TopRight,
+        WindowAction::BottomLeft,
+        WindowAction::BottomRight,
+    ]),
+    (Category::Thirds, &[
+        WindowAction::FirstThird,
+        WindowAction::CenterThird,
+        WindowAction::LastThird,
+        WindowAction::FirstTwoThirds,
+        WindowAction::LastTwoThirds,
+    ]),
+    (Category::Other, &[
+        WindowAction::Maximize,
+        WindowAction::Center,
+    ]),
+];
+
+/// A button of a single action.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Button {
+    pub action: WindowAction,
+    pub rect: Rect,
+}
+
+/// A row of buttons with its caption.
+///
+/// A wrapped row has its buttons on more than one line, see [`Row::lines`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Row {
+    pub category: Category,
+    pub caption: Rect,
+    pub buttons: Vec<Button>,
+}
+
+impl Row {
+    /// Iterate over lines of buttons, from top to bottom.
+    pub fn lines(&self) -> impl Iterator<Item = &[Button]> {
+        self.buttons.chunk_by(|first, second| first.rect.top == second.rect.top)
+    }
+}
+
+/// Positions of all elements of the Control Center, in client coordinates.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ControlCenterLayout {
+    rows: Vec<Row>,
+}
+
+impl ControlCenterLayout {
+    /// Arrange the elements in a client area of the given size.
+    ///
+    /// Buttons stretch to fill the whole area, so a larger window gets larger buttons. Rows wrap
+    /// when the area is too narrow for their buttons, and lines of all rows share the height
+    /// evenly. In an area too small for the content, buttons shrink down to nothing, but never
+    /// overlap.
+    pub fn new(width: i32, height: i32, dpi: u32) -> ControlCenterLayout {
+        let padding = scale(PADDING, dpi);
+        let spacing = scale(SPACING, dpi);
+        let caption_height = scale(CAPTION_HEIGHT, dpi);
+
+        let content = Rect::new(padding, padding, width - padding, height - padding);
+        let columns = columns(content.width(), dpi);
+        let lines: Vec<usize> = ROWS.iter()
+            .map(|(_, actions)| actions.len().div_ceil(columns))
+            .collect();
+
+        let captions = ROWS.len() as i32 * caption_height;
+        let line_edges = split(content.top, content.height() - captions,
+                               lines.iter().sum::<usize>() as i32, spacing);
+        let mut line_edges = line_edges.into_iter();
+
+        let rows = ROWS.iter().zip(lines)
+            .enumerate()
+            .map(|(index, ((category, actions), lines))| {
+                // Captions of the rows above push this row down.
+                let offset = (index as i32 + 1) * caption_height;
+                let edges: Vec<(i32, i32)> = line_edges.by_ref().take(lines).collect();
+                let top = edges.first().map_or(content.top, |(top, _)| *top);
+                let caption = Rect::new(content.left, top + offset - caption_height,
+                                        content.right.max(content.left), top + offset);
+
+                // Every line has the same number of buttons, but the last one, which may have
+                // fewer. Buttons keep their width there, so they stay in columns.
+                let per_line = actions.len().div_ceil(lines);
+                let columns = split(content.left, content.width(), per_line as i32, spacing);
+                let buttons = actions.iter()
+                    .enumerate()
+                    .map(|(position, action)| {
+                        let (top, bottom) = edges[position / per_line];
+                        let (left, right) = columns[position % per_line];
+                        Button {
+                            action: *action,
+                            rect: Rect::new(left, top + offset, right, bottom + offset),
+                        }
+                    })
+                    .collect();
+
+                Row { category: *category, caption, buttons }
+            })
+            .collect();
+
+        ControlCenterLayout { rows }
+    }
+
+    /// Get the client size in which buttons have their preferred size.
+    pub fn preferred_size(dpi: u32) -> (i32, i32) {
+        let columns = ROWS.iter().map(|(_, actions)| actions.len()).max().unwrap_or_default();
+        let rows = ROWS.len() as i32;
+
+        let width = scale(PADDING * 2 + columns as i32 * (BUTTON_WIDTH + SPACING) - SPACING, dpi);
+        let height = scale(PADDING * 2 + rows * (CAPTION_HEIGHT + BUTTON_HEIGHT + SPACING)
+                           - SPACING, dpi);
+
+        (width, height)
+    }
+
+    /// Get the smallest client size in which buttons are at least of the minimum size.
+    ///
+    /// This is the size of the narrowest layout, with rows wrapped at [`MIN_COLUMNS`] buttons.
+    /// The grid panel isn't included.
+    pub fn minimum_size(dpi: u32) -> (i32, i32) {
+        let lines: i32 = ROWS.iter()
+            .map(|(_, actions)| actions.len().div_ceil(MIN_COLUMNS) as i32)
+            .sum();
+        let rows = ROWS.len() as i32;
+
+        let width = scale(PADDING * 2 + MIN_COLUMNS as i32 * (MIN_BUTTON_WIDTH + SPACING)
+                          - SPACING, dpi);
+        let height = scale(PADDING * 2 + rows * CAPTION_HEIGHT
+                           + lines * (MIN_BUTTON_HEIGHT + SPACING) - SPACING, dpi);
+
+        (width, height)
+    }
+
+    pub fn rows(&self) -> &[Row] {
+        &self.rows
+    }
+
+    /// Iterate over buttons of all rows, from top to bottom, and from left to right.
+    pub fn buttons(&self) -> impl Iterator<Item = &Button> {
+        self.rows.iter().flat_map(|row| row.buttons.iter())
+    }
+
+    /// Find the button of the action.
+    pub fn button(&self, action: WindowAction) -> Option<&Button> {
+        self.buttons().find(|button| button.action == action)
+    }
+
+    /// Find the action whose button is at the point.
+    pub fn hit_test(&self, x: i32, y: i32) -> Option<WindowAction> {
+        self.buttons().find(|button| button.rect.contains(x, y)).map(|button| button.action)
+    }
+}
+
+/// The panel of the grid picker, on the right of the buttons.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GridPanel {
+    pub caption: Rect,
+    /// The grid, which keeps the aspect ratio of the work area it stands for.
+    pub grid: Grid,
+}
+
+impl GridPanel {
+    /// Arrange the panel along the right edge of the client area.
+    ///
+    /// The grid has the given size, and is fitted into the panel below its caption, with the same
+    /// aspect ratio as `work_area`.
+    pub fn new(client: &Rect, dpi: u32, size: GridSize, work_area: &Rect) -> GridPanel {
+        let padding = scale(PADDING, dpi);
+        let caption_height = scale(CAPTION_HEIGHT, dpi);
+
+        // The buttons on the left already leave a padding next to the panel.
+        let left = (client.right - GridPanel::width(dpi)).max(client.left);
+        let right = (client.right - padding).max(left);
+        let caption = Rect::new(left, client.top + padding, right,
+                                client.top + padding + caption_height);
+        let area = Rect::new(left, caption.bottom, right,
+                             (client.bottom - padding).max(caption.bottom));
+
+        GridPanel { caption, grid: Grid::new(size, work_area.fit(&area)) }
+    }
+
+    /// Get the width of the panel, which is taken from the width of the client area.
+    pub fn width(dpi: u32) -> i32 {
+        scale(GRID_PANEL_WIDTH, dpi)
+    }
+}
+
+/// Scale a length given at [`BASE_DPI`] to the given DPI, rounding to the nearest pixel.
+pub fn scale(value: i32, dpi: u32) -> i32 {
+    (value * dpi as i32 + BASE_DPI as i32 / 2) / BASE_DPI as i32
+}
+
+/// Convert a length at the given DPI back to [`BASE_DPI`], rounding to the nearest pixel.
+pub fn unscale(value: i32, dpi: u32) -> i32 {
+    let dpi = dpi.max(1) as i32;
+    (value * BASE_DPI as i32 + dpi / 2) / dpi
+}
+
+/// Get the number of buttons which fit in a line of the given width, without getting narrower than
+/// the minimum, but at least [`MIN_COLUMNS`].
+fn columns(width: i32, dpi: u32) -> usize {
+    let spacing = scale(SPACING, dpi);
+    let fitting = (width + spacing) / (scale(MIN_BUTTON_WIDTH, dpi) + spacing).max(1);
+
+    (fitting.max(0) as usize).max(MIN_COLUMNS)
+}
+
+/// Split a length into `count` parts with `spacing` between them, returning the edges of parts.
+///
+/// Leftover pixels are spread over the parts, so together with spacing they always fill the whole
+/// length. Parts never get a negative size.
+fn split(start: i32, length: i32, count: i32, spacing: i32) -> Vec<(i32, i32)> {
+    let total = (length - spacing * (count - 1)).max(0);
+
+    (0..count)
+        .map(|index| {
+            let left = start + index * spacing + total * index / count;
+            let right = start + index * spacing + total * (index + 1) / count;
+            (left, right)
+        })
+        .collect()
+}
@@ -0,0 +1,221 @@
+//! This module draws the Control Center.
+//!
+//! Every button is drawn as a miniature of the monitor the Control Center is shown on, with the
+//! region the action moves a window to highlighted. The miniature keeps the aspect ratio of the
+//! monitor, and shows its work area, so e.g. the taskbar is visible too. Target regions come from
+//! the action engine, so they always match what the action really does.
+//!
+//! The grid picker is drawn by [`render_grid`], as the work area divided into cells, with the
+//! selected span highlighted.
+//!
+//! When there's no window to arrange, [`render_empty`] explains it instead.
+//!
+//! All colors come from a [`Theme`]. The result is a [`DisplayList`], which can be painted by any
+//! backend, and compared with a snapshot in tests.
+
+use crate::action::WindowAction;
+use crate::control_center_layout::{scale, ControlCenterLayout, GridPanel, Row};
+use crate::display_list::{DisplayList, TextAlign};
+use crate::engine;
+use crate::geometry::Rect;
+use crate::grid_picker::{Cell, CellSpan, GridDrag};
+use crate::settings::Gaps;
+use crate::theme::Theme;
+
+/// A margin between the edge of a button and the miniature monitor, at
+/// [`BASE_DPI`](crate::control_center_layout::BASE_DPI).
+const PREVIEW_MARGIN: i32 = 6;
+
+/// A width of the outline of the focused button, at
+/// [`BASE_DPI`](crate::control_center_layout::BASE_DPI).
+const FOCUS_WIDTH: i32 = 2;
+
+/// A width of the border of the placement preview, at
+/// [`BASE_DPI`](crate::control_center_layout::BASE_DPI).
+const PREVIEW_BORDER_WIDTH: i32 = 4;
+
+/// A height of a line of the empty state, and a margin on its sides, at
+/// [`BASE_DPI`](crate::control_center_layout::BASE_DPI).
+const EMPTY_LINE_HEIGHT: i32 = 20;
+const EMPTY_MARGIN: i32 = 12;
+
+const EMPTY_TITLE: &str = "No window to arrange";
+const EMPTY_HINT: &str = "Click a window, then open the Control Center again";
+
+/// The monitor shown in the miniatures, in screen coordinates.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Screen {
+    pub monitor: Rect,
+    pub work_area: Rect,
+}
+
+/// A Full HD monitor with the taskbar at the bottom, used until the real monitor is known.
+impl Default for Screen {
+    fn default() -> Self {
+        Screen {
+            monitor: Rect::new(0, 0, 1920, 1080),
+            work_area: Rect::new(0, 0, 1920, 1040),
+        }
+    }
+}
+
+/// Buttons which are drawn differently because of the mouse or the keyboard.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ButtonStates {
+    /// The action whose button is under the mouse cursor.
+    pub hovered: Option<WindowAction>,
+    /// The action whose button has been pressed, and not released yet.
+    pub pressed: Option<WindowAction>,
+    /// The action whose button has the keyboard focus.
+    pub focused: Option<WindowAction>,
+}
+
+impl ButtonStates {
+    /// The action whose button is highlighted.
+    ///
+    /// While a button is pressed, hovering over other buttons doesn't highlight them.
+    pub fn highlighted(&self) -> Option<WindowAction> {
+        self.pressed.or(self.hovered)
+    }
+}
+
+/// Draw the whole client area of the Control Center.
+pub fn render(layout: &ControlCenterLayout, states: &ButtonStates, screen: &Screen,
+              client: &Rect, dpi: u32, theme: &Theme) -> DisplayList {
+    let mut list = DisplayList::new();
+    list.fill(*client, theme.background);
+
+    for row in layout.rows() {
+        list.text(row.caption, &caption(row, states), theme.text, TextAlign::Left);
+
+        for button in &row.buttons {
+            let (fill, border) = if states.pressed == Some(button.action) {
+                (theme.button_pressed, theme.accent)
+            } else if states.highlighted() == Some(button.action) {
+                (theme.button_hovered, theme.accent)
+            } else {
+                (theme.button, theme.border)
+            };
+
+            list.fill(button.rect, fill);
+            list.stroke(button.rect, border, scale(1, dpi).max(1));
+            if states.focused == Some(button.action) {
+                list.stroke(button.rect, theme.accent, scale(FOCUS_WIDTH, dpi).max(1));
+            }
+
+            let area = button.rect.inset(scale(PREVIEW_MARGIN, dpi));
+            draw_preview(&mut list, button.action, screen, &area, dpi, theme);
+        }
+    }
+
+    list
+}
+
+/// Draw the grid picker on top of the rest of the Control Center.
+///
+/// While dragging, the selected span is filled with the accent color, and the caption tells its
+/// size in cells. Otherwise, the cell under the mouse is highlighted like a hovered button.
+pub fn render_grid(list: &mut DisplayList, panel: &GridPanel, drag: &GridDrag, dpi: u32,
+                   theme: &Theme) {
+    let grid = &panel.grid;
+    let size = grid.size();
+    let selection = drag.selection();
+
+    let caption = match selection {
+        Some(span) if drag.is_dragging() => {
+            format!("Grid: {} x {} of {} x {}", span.columns(), span.rows(), size.columns,
+                    size.rows)
+        },
+        _ => format!("Grid: {} x {}", size.columns, size.rows),
+    };
+    list.text(panel.caption, &caption, theme.text, TextAlign::Left);
+
+    let area = grid.area();
+    if area.width() <= 0 || area.height() <= 0 {
+        return;
+    }
+
+    list.fill(area, theme.work_area);
+    if let Some(span) = selection {
+        let fill = if drag.is_dragging() { theme.accent } else { theme.button_hovered };
+        list.fill(grid.span_rect(&span), fill);
+    }
+
+    // Lines between cells, centered on their shared edges.
+    let line = scale(1, dpi).max(1);
+    for column in 1..size.columns {
+        let left = grid.span_rect(&CellSpan::single(Cell::new(column, 0))).left - line / 2;
+        list.fill(Rect::new(left, area.top, left + line, area.bottom), theme.border);
+    }
+    for row in 1..size.rows {
+        let top = grid.span_rect(&CellSpan::single(Cell::new(0, row))).top - line / 2;
+        list.fill(Rect::new(area.left, top, area.right, top + line), theme.border);
+    }
+
+    if let Some(span) = selection.filter(|_| !drag.is_dragging()) {
+        list.stroke(grid.span_rect(&span), theme.accent, line);
+    }
+    list.stroke(area, theme.monitor, line);
+}
+
+/// Draw the placement preview, which covers the whole client area of a translucent overlay.
+pub fn render_preview(client: &Rect, dpi: u32, theme: &Theme) -> DisplayList {
+    let mut list = DisplayList::new();
+    list.fill(*client, theme.preview_fill);
+    list.stroke(*client, theme.preview_border, scale(PREVIEW_BORDER_WIDTH, dpi).max(1));
+    list
+}
+
+/// Draw the empty state, shown when there's no window the actions could be applied to.
+pub fn render_empty(client: &Rect, dpi: u32, theme: &Theme) -> DisplayList {
+    let mut list = DisplayList::new();
+    list.fill(*client, theme.background);
+
+    let line_height = scale(EMPTY_LINE_HEIGHT, dpi);
+    let middle = client.top + client.height() / 2;
+    let margin = scale(EMPTY_MARGIN, dpi);
+
+    list.text(Rect::new(client.left + margin, middle - line_height, client.right - margin, middle),
+              EMPTY_TITLE, theme.text, TextAlign::Center);
+    list.text(Rect::new(client.left + margin, middle, client.right - margin, middle + line_height),
+              EMPTY_HINT, theme.hint, TextAlign::Center);
+
+    list
+}
+
+/// Get the caption of the row, which names the highlighted or focused action, if it's in the row.
+///
+/// Buttons show no text, so this is how the user learns the name of the action.
+fn caption(row: &Row, states: &ButtonStates) -> String {
+    let title = row.category.title();
+
+    match states.highlighted().or(states.focused) {
+        Some(action) if row.buttons.iter().any(|button| button.action == action) => {
+            format!("{title}: {}", action.label())
+        },
+        _ => title.to_string(),
+    }
+}
+
+/// Draw a miniature of the screen with the target region of the action, fitted into the area.
+fn draw_preview(list: &mut DisplayList, action: WindowAction, screen: &Screen, area: &Rect,
+                dpi: u32, theme: &Theme) {
+    let monitor = screen.monitor.fit(area);
+    if monitor.width() <= 0 || monitor.height() <= 0 {
+        return;
+    }
+
+    let miniature = |rect: &Rect| monitor.from_fraction(&rect.to_fraction(&screen.monitor));
+
+    // Actions which keep the window size, like centering, are shown with a window of a quarter of
+    // the work area.
+    let work_area = &screen.work_area;
+    let window = Rect::from_size(work_area.left, work_area.top, work_area.width() / 2,
+                                 work_area.height() / 2);
+    let target = engine::target_rect(action, work_area, &window, &Gaps::default());
+
+    list.fill(monitor, theme.monitor);
+    list.fill(miniature(work_area), theme.work_area);
+    list.fill(miniature(&target), theme.accent);
+    list.stroke(monitor, theme.monitor, scale(1, dpi).max(1));
+}
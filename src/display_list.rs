@@ -0,0 +1,148 @@
+//! This module contains a display list, a backend-neutral description of what to draw.
+//!
+//! Windows drawn by Rectangular are described as a list of simple commands: filled rectangles,
+//! rectangle outlines and single lines of text, in the order they should be drawn. Building the
+//! list is free of any Windows API, so what a window looks like can be checked on any platform,
+//! e.g. by comparing [`DisplayList::snapshot`] with a snapshot kept in the repository. Backends,
+//! like [`gdi_painter`](crate::gdi_painter), only carry out the commands.
+
+use std::error::Error;
+use std::fmt;
+use std::str::FromStr;
+
+use serde::{Deserialize, Deserializer};
+
+use crate::geometry::Rect;
+
+/// A color without transparency.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Color {
+    pub red: u8,
+    pub green: u8,
+    pub blue: u8,
+}
+
+impl Color {
+    pub const fn rgb(red: u8, green: u8, blue: u8) -> Color {
+        Color { red, green, blue }
+    }
+}
+
+/// Colors are written like in HTML, e.g. `#0078D7`.
+impl fmt::Display for Color {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{:02X}{:02X}{:02X}", self.red, self.green, self.blue)
+    }
+}
+
+/// An error of parsing a color which isn't written like `#0078D7`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ColorParseError;
+
+impl fmt::Display for ColorParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "expected # followed by six hexadecimal digits, like #0078D7")
+    }
+}
+
+impl Error for ColorParseError {}
+
+impl FromStr for Color {
+    type Err = ColorParseError;
+
+    /// Parse a color written like in HTML, e.g. `#0078D7`. Digits may be of any case.
+    fn from_str(s: &str) -> Result<Color, ColorParseError> {
+        let digits = s.trim().strip_prefix('#').ok_or(ColorParseError)?;
+        if digits.len() != 6 || !digits.chars().all(|digit| digit.is_ascii_hexdigit()) {
+            return Err(ColorParseError);
+        }
+
+        let component = |index: usize| u8::from_str_radix(&digits[index..index + 2], 16);
+        match (component(0), component(2), component(4)) {
+            (Ok(red), Ok(green), Ok(blue)) => Ok(Color::rgb(red, green, blue)),
+            _ => Err(ColorParseError),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for Color {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Color, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(|error| {
+            serde::de::Error::custom(format!("invalid color \"{text}\": {error}"))
+        })
+    }
+}
+
+/// A horizontal alignment of text within its rectangle. Text is always centered vertically.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum TextAlign {
+    Left,
+    Center,
+}
+
+/// A single drawing command.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DrawCommand {
+    /// Fill the rectangle with the color.
+    Fill { rect: Rect, color: Color },
+    /// Draw an outline along the inner edges of the rectangle, `width` pixels thick.
+    Stroke { rect: Rect, color: Color, width: i32 },
+    /// Draw a single line of text, clipped to the rectangle.
+    Text { rect: Rect, text: String, color: Color, align: TextAlign },
+}
+
+/// A list of drawing commands, executed in order, so later commands paint over earlier ones.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct DisplayList {
+    commands: Vec<DrawCommand>,
+}
+
+impl DisplayList {
+    pub fn new() -> DisplayList {
+        DisplayList::default()
+    }
+
+    pub fn fill(&mut self, rect: Rect, color: Color) {
+        self.commands.push(DrawCommand::Fill { rect, color });
+    }
+
+    pub fn stroke(&mut self, rect: Rect, color: Color, width: i32) {
+        self.commands.push(DrawCommand::Stroke { rect, color, width });
+    }
+
+    pub fn text(&mut self, rect: Rect, text: &str, color: Color, align: TextAlign) {
+        self.commands.push(DrawCommand::Text { rect, text: text.to_string(), color, align });
+    }
+
+    pub fn commands(&self) -> &[DrawCommand] {
+        &self.commands
+    }
+
+    /// Describe the list as text, one command per line.
+    ///
+    /// Rectangles are written as `left,top right,bottom`, like
+    /// `stroke 8,26 68,66 1 #ADADAD`. The format is meant for snapshots, which show exactly what
+    /// has changed when they are compared in a diff.
+    pub fn snapshot(&self) -> String {
+        let rect = |rect: &Rect| {
+            format!("{},{} {},{}", rect.left, rect.top, rect.right, rect.bottom)
+        };
+
+        self.commands.iter()
+            .map(|command| match command {
+                DrawCommand::Fill { rect: r, color } => format!("fill {} {color}\n", rect(r)),
+                DrawCommand::Stroke { rect: r, color, width } => {
+                    format!("stroke {} {width} {color}\n", rect(r))
+                },
+                DrawCommand::Text { rect: r, text, color, align } => {
+                    let align = match align {
+                        TextAlign::Left => "left",
+                        TextAlign::Center => "center",
+                    };
+                    format!("text {} {align} {color} {text:?}\n", rect(r))
+                },
+            })
+            .collect()
+    }
+}
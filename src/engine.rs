@@ -0,0 +1,374 @@
+//! This module contains the action engine, the part of Rectangular which actually moves windows.
+//!
+//! The engine is split into two layers. The pure layer computes where a window should land, given
+//! the work area of its monitor, the current window position and the settings. The Windows layer
+//! gathers these inputs from the system and moves the window to the computed position. The inputs
+//! are gathered through a [`WindowSystem`], so a [`placement`] can also be computed for made-up
+//! windows.
+//!
+//! # Frame compensation
+//!
+//! Since Windows 10, most top-level windows have invisible resize borders, so the rectangle
+//! reported by `GetWindowRect` is a few pixels larger than what the user sees. Placing such a
+//! window at exact half of the screen leaves visible gaps around it. The engine computes targets
+//! for the *visible* frame and then grows them by [`FrameInsets`] measured for the window.
+//!
+//! # Repeated actions
+//!
+//! When a half action is applied to a window which is already in place, the result depends on
+//! [`CyclingMode`]: the window either cycles through configured sizes, moves to the adjacent
+//! display, or stays where it is.
+
+use std::ffi::c_void;
+use std::mem;
+
+use windows::Win32::Foundation::{BOOL, HWND, LPARAM, RECT};
+use windows::Win32::Graphics::Dwm::{DWMWA_EXTENDED_FRAME_BOUNDS, DwmGetWindowAttribute};
+use windows::Win32::Graphics::Gdi::{
+    EnumDisplayMonitors, GetMonitorInfoA, HDC, HMONITOR, MONITOR_DEFAULTTONEAREST, MONITORINFO,
+    MonitorFromWindow,
+};
+use windows::Win32::UI::WindowsAndMessaging::*;
+
+use crate::action::WindowAction;
+use crate::geometry::{FracRect, Rect};
+use crate::ignore;
+use crate::settings::{CyclingMode, DisplayOrder, Gaps, Settings};
+use crate::window_system::{Win32WindowSystem, WindowSystem};
+
+/// How far, in pixels, a window edge can be from the target to consider the window in place.
+const IN_PLACE_TOLERANCE: i32 = 2;
+
+/// Widths of the invisible borders around a window.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FrameInsets {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl FrameInsets {
+    /// Measure the insets from the full window rectangle and its visible part.
+    pub fn between(window: &Rect, visible: &Rect) -> FrameInsets {
+        FrameInsets {
+            left: visible.left - window.left,
+            top: visible.top - window.top,
+            right: window.right - visible.right,
+            bottom: window.bottom - visible.bottom,
+        }
+    }
+
+    /// Grow a visible rectangle into the full window rectangle.
+    pub fn expand(&self, visible: &Rect) -> Rect {
+        Rect {
+            left: visible.left - self.left,
+            top: visible.top - self.top,
+            right: visible.right + self.right,
+            bottom: visible.bottom + self.bottom,
+        }
+    }
+}
+
+/// Everything the engine needs to know about a window and its surroundings.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Context {
+    /// The work area of the display the window is on.
+    pub work_area: Rect,
+    /// The visible rectangle of the window.
+    pub current: Rect,
+    /// Work areas of all displays, including the one the window is on.
+    pub displays: Vec<Rect>,
+}
+
+/// Map a fraction of the work area onto pixels, leaving gaps around it.
+///
+/// The outer gap is left between the window and the edges of the work area. Half of the inner gap
+/// is left on every edge touching another fraction, so two adjacent windows are separated by the
+/// full inner gap.
+pub fn fraction_rect(fraction: &FracRect, work_area: &Rect, gaps: &Gaps) -> Rect {
+    const EPSILON: f64 = 0.001;
+
+    let area = work_area.inset(gaps.outer as i32);
+    let mut rect = area.from_fraction(fraction);
+    let half = gaps.inner as i32 / 2;
+    let other_half = gaps.inner as i32 - half;
+
+    if fraction.x > EPSILON {
+        rect.left += half;
+    }
+    if fraction.y > EPSILON {
+        rect.top += half;
+    }
+    if fraction.x + fraction.width < 1.0 - EPSILON {
+        rect.right -= other_half;
+    }
+    if fraction.y + fraction.height < 1.0 - EPSILON {
+        rect.bottom -= other_half;
+    }
+
+    rect
+}
+
+/// Compute the visible rectangle of a window after applying the action.
+///
+/// `work_area` is the work area of the monitor the window is on, and `current` is the current
+/// visible rectangle of the window. Actions which don't resize the window keep its current size,
+/// limited to the size of the work area.
+pub fn target_rect(action: WindowAction, work_area: &Rect, current: &Rect, gaps: &Gaps) -> Rect {
+    match action.fraction() {
+        Some(fraction) => fraction_rect(&fraction, work_area, gaps),
+        None => {
+            let area = work_area.inset(gaps.outer as i32);
+            let width = current.width().min(area.width());
+            let height = current.height().min(area.height());
+            let left = area.left + (area.width() - width) / 2;
+            let top = area.top + (area.height() - height) / 2;
+
+            Rect::from_size(left, top, width, height)
+        }
+    }
+}
+
+/// Compute the visible rectangle of a window after applying the action, including the behaviour
+/// of repeated actions described in the [module documentation](self).
+pub fn plan(action: WindowAction, context: &Context, settings: &Settings) -> Rect {
+    let gaps = &settings.gaps;
+    let target = target_rect(action, &context.work_area, &context.current, gaps);
+
+    match settings.cycling.mode {
+        CyclingMode::None => target,
+        CyclingMode::Sizes => {
+            let variants: Vec<Rect> = settings.cycling.sizes.iter()
+                .filter_map(|size| half_with_size(action, *size))
+                .map(|fraction| fraction_rect(&fraction, &context.work_area, gaps))
+                .collect();
+
+            match variants.iter().position(|variant| in_place(&context.current, variant)) {
+                Some(index) => variants[(index + 1) % variants.len()],
+                None if in_place(&context.current, &target) && !variants.is_empty() => variants[0],
+                None => target,
+            }
+        },
+        CyclingMode::Displays => {
+            if !in_place(&context.current, &target) {
+                return target;
+            }
+
+            let (forward, opposite) = match action {
+                WindowAction::LeftHalf => (false, WindowAction::RightHalf),
+                WindowAction::TopHalf => (false, WindowAction::BottomHalf),
+                WindowAction::RightHalf => (true, WindowAction::LeftHalf),
+                WindowAction::BottomHalf => (true, WindowAction::TopHalf),
+                _ => return target,
+            };
+
+            match adjacent_display(&context.displays, &context.work_area, forward, settings) {
+                Some(display) => target_rect(opposite, &display, &context.current, gaps),
+                None => target,
+            }
+        },
+    }
+}
+
+/// Find the display before or after the current one, in the order given by the settings.
+pub fn adjacent_display(displays: &[Rect], current: &Rect, forward: bool,
+                        settings: &Settings) -> Option<Rect> {
+    let mut sorted = displays.to_vec();
+    match settings.cycling.display_order {
+        DisplayOrder::LeftToRight => sorted.sort_by_key(|display| (display.left, display.top)),
+        DisplayOrder::TopToBottom => sorted.sort_by_key(|display| (display.top, display.left)),
+    }
+
+    let index = sorted.iter().position(|display| display == current)?;
+    let count = sorted.len();
+    let adjacent = match (forward, settings.cycling.wrap_around) {
+        (true, _) if index + 1 < count => index + 1,
+        (false, _) if index > 0 => index - 1,
+        (true, true) => 0,
+        (false, true) => count - 1,
+        _ => return None,
+    };
+
+    (adjacent != index).then_some(sorted[adjacent])
+}
+
+/// Get the fraction of a half action resized to the given size, anchored at the same edge.
+fn half_with_size(action: WindowAction, size: f64) -> Option<FracRect> {
+    match action {
+        WindowAction::LeftHalf => Some(FracRect::new(0.0, 0.0, size, 1.0)),
+        WindowAction::RightHalf => Some(FracRect::new(1.0 - size, 0.0, size, 1.0)),
+        WindowAction::TopHalf => Some(FracRect::new(0.0, 0.0, 1.0, size)),
+        WindowAction::BottomHalf => Some(FracRect::new(0.0, 1.0 - size, 1.0, size)),
+        _ => None,
+    }
+}
+
+fn in_place(current: &Rect, target: &Rect) -> bool {
+    (current.left - target.left).abs() <= IN_PLACE_TOLERANCE
+        && (current.top - target.top).abs() <= IN_PLACE_TOLERANCE
+        && (current.right - target.right).abs() <= IN_PLACE_TOLERANCE
+        && (current.bottom - target.bottom).abs() <= IN_PLACE_TOLERANCE
+}
+
+impl From<RECT> for Rect {
+    fn from(rect: RECT) -> Rect {
+        Rect::new(rect.left, rect.top, rect.right, rect.bottom)
+    }
+}
+
+impl From<Rect> for RECT {
+    fn from(rect: Rect) -> RECT {
+        RECT { left: rect.left, top: rect.top, right: rect.right, bottom: rect.bottom }
+    }
+}
+
+/// Get the work area of the monitor the window is on, or the nearest one.
+pub fn work_area_of(window: HWND) -> Option<Rect> {
+    monitor_of(window).map(|(_, work_area)| work_area)
+}
+
+/// Get the full rectangle and the work area of the monitor the window is on, or the nearest one.
+pub fn monitor_of(window: HWND) -> Option<(Rect, Rect)> {
+    unsafe {
+        let monitor = MonitorFromWindow(window, MONITOR_DEFAULTTONEAREST);
+        let mut info = MONITORINFO {
+            cbSize: mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+
+        GetMonitorInfoA(monitor, &mut info).as_bool()
+            .then(|| (info.rcMonitor.into(), info.rcWork.into()))
+    }
+}
+
+/// Get the full window rectangle and the visible part of it.
+///
+/// When the visible frame can't be obtained from DWM, the full rectangle is used for both.
+pub fn window_rects(window: HWND) -> Option<(Rect, Rect)> {
+    unsafe {
+        let mut window_rect = RECT::default();
+        if !GetWindowRect(window, &mut window_rect).as_bool() {
+            return None;
+        }
+
+        let mut visible_rect = window_rect;
+        let result = DwmGetWindowAttribute(window, DWMWA_EXTENDED_FRAME_BOUNDS,
+                                           &mut visible_rect as *mut RECT as *mut c_void,
+                                           mem::size_of::<RECT>() as u32);
+        if result.is_err() {
+            visible_rect = window_rect;
+        }
+
+        Some((window_rect.into(), visible_rect.into()))
+    }
+}
+
+/// Get work areas of all displays.
+pub fn display_work_areas() -> Vec<Rect> {
+    unsafe extern "system" fn collect(monitor: HMONITOR, _hdc: HDC, _rect: *mut RECT,
+                                      data: LPARAM) -> BOOL {
+        let areas = &mut *(data.0 as *mut Vec<Rect>);
+        let mut info = MONITORINFO {
+            cbSize: mem::size_of::<MONITORINFO>() as u32,
+            ..Default::default()
+        };
+
+        if GetMonitorInfoA(monitor, &mut info).as_bool() {
+            areas.push(info.rcWork.into());
+        }
+
+        BOOL(1)
+    }
+
+    let mut areas: Vec<Rect> = Vec::new();
+    unsafe {
+        EnumDisplayMonitors(HDC::default(), None, Some(collect),
+                            LPARAM(&mut areas as *mut Vec<Rect> as isize));
+    }
+
+    areas
+}
+
+/// Apply the action to the given window.
+///
+/// Maximized windows are restored first, otherwise Windows would ignore the new position. Returns
+/// `false` if the window is gone, belongs to an ignored application, or any of the system calls
+/// failed.
+pub fn apply(window: HWND, action: WindowAction, settings: &Settings) -> bool {
+    move_window(window, settings, |context| plan(action, context, settings))
+}
+
+/// Move the window to a fraction of the work area of its monitor, leaving the gaps around it.
+///
+/// This is how arbitrary regions, like the ones selected in the
+/// [grid picker](crate::grid_picker), are applied. The same rules as in [`apply`] hold.
+pub fn apply_fraction(window: HWND, fraction: &FracRect, settings: &Settings) -> bool {
+    move_window(window, settings,
+                |context| fraction_rect(fraction, &context.work_area, &settings.gaps))
+}
+
+/// Where a window is moved to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Placement {
+    /// The visible rectangle of the window, which is what the user sees.
+    pub visible: Rect,
+    /// The full window rectangle, including the invisible borders, which is what is passed to
+    /// the system.
+    pub window: Rect,
+}
+
+/// Compute where the action would move the window, without moving it.
+///
+/// This is what [`apply`] does, except for restoring maximized windows, so e.g. a preview of the
+/// action shows exactly where the window lands.
+pub fn placement(window: HWND, action: WindowAction, settings: &Settings,
+                 system: &impl WindowSystem) -> Option<Placement> {
+    placement_with(window, system, |context| plan(action, context, settings))
+}
+
+/// Compute the placement of the window, whose visible rectangle is computed by `plan`.
+fn placement_with(window: HWND, system: &impl WindowSystem,
+                  plan: impl FnOnce(&Context) -> Rect) -> Option<Placement> {
+    let work_area = system.work_area(window)?;
+    let (window_rect, visible_rect) = system.frame(window)?;
+
+    let context = Context { work_area, current: visible_rect, displays: system.displays() };
+    let insets = FrameInsets::between(&window_rect, &visible_rect);
+    let visible = plan(&context);
+
+    Some(Placement { visible, window: insets.expand(&visible) })
+}
+
+/// Move the window to the visible rectangle computed by `plan`.
+fn move_window(window: HWND, settings: &Settings, plan: impl FnOnce(&Context) -> Rect) -> bool {
+    if window.0 == 0 {
+        return false;
+    }
+
+    if ignore::identify(window)
+        .is_some_and(|identity| ignore::is_ignored(&settings.ignored_apps, &identity)) {
+        return false;
+    }
+
+    unsafe {
+        if IsZoomed(window).as_bool() {
+            ShowWindow(window, SW_RESTORE);
+        }
+    }
+
+    let Some(Placement { window: target, .. }) =
+        placement_with(window, &Win32WindowSystem, plan) else {
+        return false;
+    };
+
+    unsafe {
+        SetWindowPos(window, HWND::default(), target.left, target.top, target.width(),
+                     target.height(), SWP_NOZORDER | SWP_NOACTIVATE).as_bool()
+    }
+}
+
+/// Apply the action to the current foreground window.
+pub fn apply_to_foreground(action: WindowAction, settings: &Settings) -> bool {
+    unsafe { apply(GetForegroundWindow(), action, settings) }
+}
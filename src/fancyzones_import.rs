@@ -0,0 +1,295 @@
+//! This module imports custom zone layouts from PowerToys FancyZones.
+//!
+//! FancyZones keeps custom layouts in `custom-layouts.json`, in one of two formats:
+//!
+//! - **grid** layouts split the work area into rows and columns, sized in hundredths of a
+//!   percent, and map every cell to a zone. A zone spanning several cells covers all of them.
+//! - **canvas** layouts place zones freely, in pixels of a reference work area.
+//!
+//! Every zone becomes a [`CustomAction`] covering the same fraction of the work area, and zones of
+//! a layout are put in a [`CustomGroup`] named after the layout. Zones are named after their
+//! numbers, like FancyZones shows them. The spacing of grid layouts maps to both the inner and
+//! outer gaps, since FancyZones leaves it around the edges of the work area, too.
+
+use serde::Deserialize;
+use serde_json::Value;
+
+use crate::geometry::FracRect;
+use crate::import::{self, ImportError, ImportReport};
+use crate::settings::{CustomAction, CustomGroup, Gaps, Settings};
+
+/// Precision of imported fractions, enough to express a pixel of a 10000 pixels wide monitor.
+const FRACTION_PRECISION: f64 = 10000.0;
+
+/// The content of `custom-layouts.json`.
+///
+/// Layouts are kept as raw values, so one which can't be read is reported, rather than failing
+/// the whole import.
+#[derive(Deserialize)]
+struct LayoutsFile {
+    #[serde(rename = "custom-layouts")]
+    custom_layouts: Vec<Value>,
+}
+
+#[derive(Deserialize)]
+struct Layout {
+    name: Option<String>,
+    #[serde(rename = "type")]
+    kind: Option<String>,
+    #[serde(default)]
+    info: Value,
+}
+
+/// The description of a grid layout.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct GridInfo {
+    rows_percentage: Vec<f64>,
+    columns_percentage: Vec<f64>,
+    /// Zone numbers of cells, row by row.
+    cell_child_map: Vec<Vec<usize>>,
+    #[serde(default)]
+    show_spacing: bool,
+    #[serde(default)]
+    spacing: u32,
+}
+
+/// The description of a canvas layout.
+#[derive(Deserialize)]
+#[serde(rename_all = "kebab-case")]
+struct CanvasInfo {
+    ref_width: f64,
+    ref_height: f64,
+    zones: Vec<CanvasZone>,
+}
+
+#[derive(Deserialize)]
+struct CanvasZone {
+    #[serde(rename = "X")]
+    x: f64,
+    #[serde(rename = "Y")]
+    y: f64,
+    width: f64,
+    height: f64,
+}
+
+/// Layouts imported from FancyZones.
+#[derive(Clone, Debug, PartialEq)]
+pub struct FancyZonesImport {
+    pub groups: Vec<CustomGroup>,
+    pub gaps: Option<Gaps>,
+    pub report: ImportReport,
+}
+
+impl FancyZonesImport {
+    /// Add the imported groups to the settings. Groups with the same name are replaced.
+    pub fn apply_to(&self, settings: &mut Settings) {
+        settings.custom_groups.retain(|group| {
+            self.groups.iter().all(|imported| imported.name != group.name)
+        });
+        settings.custom_groups.extend(self.groups.iter().cloned());
+
+        if let Some(gaps) = self.gaps {
+            settings.gaps = gaps;
+        }
+    }
+
+    /// Write the imported layouts as a fragment of the settings file.
+    pub fn to_toml(&self) -> String {
+        let mut table = toml::Table::new();
+
+        if let Some(gaps) = self.gaps {
+            let mut section = toml::Table::new();
+            section.insert("inner".to_string(), toml::Value::Integer(gaps.inner as i64));
+            section.insert("outer".to_string(), toml::Value::Integer(gaps.outer as i64));
+            table.insert("gaps".to_string(), toml::Value::Table(section));
+        }
+
+        let groups = self.groups.iter().map(group_to_toml).collect();
+        table.insert("custom-groups".to_string(), toml::Value::Array(groups));
+
+        import::settings_fragment("FancyZones", &self.report, table)
+    }
+}
+
+/// Import the content of `custom-layouts.json`.
+pub fn import(text: &str) -> Result<FancyZonesImport, ImportError> {
+    let file = LayoutsFile::deserialize(import::parse_json(text)?).map_err(|_| {
+        ImportError::Format("the file is not a FancyZones layout file, it has no custom layouts"
+            .to_string())
+    })?;
+
+    let mut report = ImportReport::default();
+    let mut groups: Vec<CustomGroup> = Vec::new();
+    let mut gaps: Option<Gaps> = None;
+
+    for (index, layout) in file.custom_layouts.iter().enumerate() {
+        let layout = Layout::deserialize(layout);
+        let name = layout.as_ref().ok().and_then(|layout| layout.name.clone())
+            .unwrap_or_else(|| format!("Layout {}", index + 1));
+        let item = format!("layout \"{name}\"");
+
+        let layout = match layout {
+            Ok(layout) => layout,
+            Err(error) => {
+                report.add(item, format!("the layout is invalid: {error}"));
+                continue;
+            },
+        };
+
+        if groups.iter().any(|group| group.name == name) {
+            report.add(item, "a layout with the same name has already been imported");
+            continue;
+        }
+
+        let mut spacing = None;
+        let zones = match layout.kind.as_deref() {
+            Some("grid") => GridInfo::deserialize(&layout.info)
+                .map_err(|error| format!("the grid is invalid: {error}"))
+                .and_then(|grid| {
+                    spacing = (grid.show_spacing && grid.spacing > 0).then_some(grid.spacing);
+                    grid_zones(&grid)
+                }),
+            Some("canvas") => CanvasInfo::deserialize(&layout.info)
+                .map_err(|error| format!("the canvas is invalid: {error}"))
+                .and_then(|canvas| canvas_zones(&canvas)),
+            Some(other) => Err(format!("the layout type \"{other}\" is not supported")),
+            None => Err("the layout has no type".to_string()),
+        };
+
+        let zones = match zones {
+            Ok(zones) if zones.is_empty() => {
+                report.add(item, "the layout has no zones");
+                continue;
+            },
+            Ok(zones) => zones,
+            Err(reason) => {
+                report.add(item, reason);
+                continue;
+            },
+        };
+
+        if let Some(spacing) = spacing {
+            let layout_gaps = Gaps { inner: spacing, outer: spacing };
+            match gaps {
+                None => gaps = Some(layout_gaps),
+                Some(gaps) if gaps == layout_gaps => {},
+                Some(gaps) => report.add(format!("spacing of {item}"), format!(
+                    "the spacing of {spacing} px differs from {} px of another layout, gaps are \
+                     global in Rectangular", gaps.inner)),
+            }
+        }
+
+        let actions = zones.into_iter().enumerate()
+            .map(|(index, rect)| CustomAction { name: format!("Zone {}", index + 1), rect })
+            .collect();
+        groups.push(CustomGroup { name, actions });
+    }
+
+    Ok(FancyZonesImport { groups, gaps, report })
+}
+
+/// Get zones of a grid layout.
+fn grid_zones(grid: &GridInfo) -> Result<Vec<FracRect>, String> {
+    let rows = grid_sizes(&grid.rows_percentage, "rows-percentage")?;
+    let columns = grid_sizes(&grid.columns_percentage, "columns-percentage")?;
+
+    // The bounding box of every zone, in cells: first row, first column, last row, last column.
+    let mut bounds: Vec<Option<(usize, usize, usize, usize)>> = Vec::new();
+    for (row, cells) in grid.cell_child_map.iter().enumerate().take(rows.len()) {
+        for (column, zone) in cells.iter().copied().enumerate().take(columns.len()) {
+            if bounds.len() <= zone {
+                bounds.resize(zone + 1, None);
+            }
+
+            bounds[zone] = Some(match bounds[zone] {
+                None => (row, column, row, column),
+                Some((top, left, bottom, right)) => {
+                    (top.min(row), left.min(column), bottom.max(row), right.max(column))
+                },
+            });
+        }
+    }
+
+    let row_edges = edges(&rows);
+    let column_edges = edges(&columns);
+
+    Ok(bounds.into_iter().flatten()
+        .map(|(top, left, bottom, right)| {
+            let x = column_edges[left];
+            let y = row_edges[top];
+            FracRect::new(round(x), round(y), round(column_edges[right + 1] - x),
+                          round(row_edges[bottom + 1] - y))
+        })
+        .collect())
+}
+
+/// Turn sizes of rows or columns of a grid into fractions of the work area.
+fn grid_sizes(sizes: &[f64], key: &str) -> Result<Vec<f64>, String> {
+    if sizes.is_empty() || sizes.iter().any(|size| *size <= 0.0) {
+        return Err(format!("the grid has invalid \"{key}\""));
+    }
+
+    let total: f64 = sizes.iter().sum();
+    // FancyZones keeps the sizes adding up to 10000, but dividing by the actual sum is just as
+    // cheap, and copes with files edited by hand.
+    Ok(sizes.iter().map(|size| size / total).collect())
+}
+
+/// Get positions of the edges between consecutive sizes, starting with 0.
+fn edges(sizes: &[f64]) -> Vec<f64> {
+    let mut edges = vec![0.0];
+    for size in sizes {
+        edges.push(edges[edges.len() - 1] + size);
+    }
+
+    edges
+}
+
+/// Get zones of a canvas layout.
+fn canvas_zones(canvas: &CanvasInfo) -> Result<Vec<FracRect>, String> {
+    let (ref_width, ref_height) = (canvas.ref_width, canvas.ref_height);
+    if ref_width <= 0.0 || ref_height <= 0.0 {
+        return Err("the canvas has an invalid reference size".to_string());
+    }
+
+    Ok(canvas.zones.iter()
+        .map(|zone| {
+            // Zones may stick out of the reference area, clip them to it.
+            let left = (zone.x / ref_width).clamp(0.0, 1.0);
+            let top = (zone.y / ref_height).clamp(0.0, 1.0);
+            let right = ((zone.x + zone.width) / ref_width).clamp(0.0, 1.0);
+            let bottom = ((zone.y + zone.height) / ref_height).clamp(0.0, 1.0);
+
+            FracRect::new(round(left), round(top), round(right - left), round(bottom - top))
+        })
+        .filter(FracRect::is_valid)
+        .collect())
+}
+
+/// Round a fraction, so it's written to the settings file in a readable form.
+fn round(fraction: f64) -> f64 {
+    (fraction * FRACTION_PRECISION).round() / FRACTION_PRECISION
+}
+
+fn group_to_toml(group: &CustomGroup) -> toml::Value {
+    let actions = group.actions.iter()
+        .map(|action| {
+            let mut rect = toml::Table::new();
+            rect.insert("x".to_string(), toml::Value::Float(action.rect.x));
+            rect.insert("y".to_string(), toml::Value::Float(action.rect.y));
+            rect.insert("width".to_string(), toml::Value::Float(action.rect.width));
+            rect.insert("height".to_string(), toml::Value::Float(action.rect.height));
+
+            let mut table = toml::Table::new();
+            table.insert("name".to_string(), toml::Value::String(action.name.clone()));
+            table.insert("rect".to_string(), toml::Value::Table(rect));
+            toml::Value::Table(table)
+        })
+        .collect();
+
+    let mut table = toml::Table::new();
+    table.insert("name".to_string(), toml::Value::String(group.name.clone()));
+    table.insert("actions".to_string(), toml::Value::Array(actions));
+    toml::Value::Table(table)
+}
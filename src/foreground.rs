@@ -0,0 +1,107 @@
+//! This module keeps track of the window the user is working with.
+//!
+//! Clicking the notification icon activates the taskbar, and then the Control Center, so by the
+//! time an action is chosen in it, the foreground window is no longer the one the user meant.
+//! Instead, the main window is told about every change of the foreground window by
+//! [`ForegroundHook`], and [`ForegroundTracker`] remembers the last one which can be arranged.
+//! Windows of Rectangular itself, and parts of the shell, like the taskbar or the desktop, are
+//! skipped.
+
+use std::sync::atomic::{AtomicIsize, Ordering};
+
+use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, WPARAM};
+use windows::Win32::UI::Accessibility::{HWINEVENTHOOK, SetWinEventHook, UnhookWinEvent};
+use windows::Win32::UI::WindowsAndMessaging::{
+    EVENT_SYSTEM_FOREGROUND, OBJID_WINDOW, PostMessageA, WINEVENT_OUTOFCONTEXT, WM_USER,
+};
+
+use crate::window_system::{WindowInfo, WindowSystem};
+
+/// An ID of the message posted to the main window when the foreground window changes.
+///
+/// `wparam` holds the handle of the new foreground window.
+pub const WM_FOREGROUND_CHANGED: u32 = WM_USER + 4;
+
+/// Classes of shell windows which never become the target, even when they are in the foreground.
+pub const SHELL_CLASSES: [&str; 7] = [
+    "Shell_TrayWnd",
+    "Shell_SecondaryTrayWnd",
+    "NotifyIconOverflowWindow",
+    "TopLevelWindowForOverflowXamlIsland",
+    "Progman",
+    "WorkerW",
+    "Windows.UI.Core.CoreWindow",
+];
+
+/// Check whether actions can be applied to the window.
+pub fn is_eligible(info: &WindowInfo) -> bool {
+    info.visible && info.top_level && !info.cloaked && !info.own
+        && !SHELL_CLASSES.contains(&info.class_name.as_str())
+}
+
+/// Remembers the last eligible foreground window.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ForegroundTracker {
+    last: Option<HWND>,
+}
+
+impl ForegroundTracker {
+    /// Record a change of the foreground window. Windows which aren't eligible are ignored.
+    pub fn foreground_changed(&mut self, window: HWND, system: &impl WindowSystem) {
+        if system.info(window).is_some_and(|info| is_eligible(&info)) {
+            self.last = Some(window);
+        }
+    }
+
+    /// Get the window actions should be applied to, `None` if it has been closed or hidden since.
+    pub fn target(&self, system: &impl WindowSystem) -> Option<HWND> {
+        self.last.filter(|window| system.info(*window).is_some_and(|info| is_eligible(&info)))
+    }
+}
+
+/// The window which is told about changes of the foreground window.
+///
+/// Hook callbacks don't take any user data, so the window is kept here. There is only one main
+/// window, so there's only one hook at a time.
+static NOTIFIED_WINDOW: AtomicIsize = AtomicIsize::new(0);
+
+/// Posts [`WM_FOREGROUND_CHANGED`] to a window whenever the foreground window changes.
+///
+/// The hook is removed when this object is dropped.
+pub struct ForegroundHook {
+    hook: HWINEVENTHOOK,
+}
+
+impl ForegroundHook {
+    /// Start posting messages to the given window.
+    ///
+    /// The hook is called from the message loop of the calling thread. Returns `None` if the hook
+    /// can't be set.
+    pub fn start(window: HWND) -> Option<ForegroundHook> {
+        NOTIFIED_WINDOW.store(window.0, Ordering::Relaxed);
+
+        let hook = unsafe {
+            SetWinEventHook(EVENT_SYSTEM_FOREGROUND, EVENT_SYSTEM_FOREGROUND, HINSTANCE::default(),
+                            Some(foreground_changed), 0, 0, WINEVENT_OUTOFCONTEXT)
+        };
+
+        (hook.0 != 0).then_some(ForegroundHook { hook })
+    }
+}
+
+impl Drop for ForegroundHook {
+    fn drop(&mut self) {
+        unsafe {
+            UnhookWinEvent(self.hook);
+        }
+    }
+}
+
+unsafe extern "system" fn foreground_changed(_hook: HWINEVENTHOOK, _event: u32, window: HWND,
+                                             object: i32, _child: i32, _thread: u32, _time: u32) {
+    let notified = HWND(NOTIFIED_WINDOW.load(Ordering::Relaxed));
+
+    if object == OBJID_WINDOW.0 && window.0 != 0 && notified.0 != 0 {
+        PostMessageA(notified, WM_FOREGROUND_CHANGED, WPARAM(window.0 as usize), LPARAM(0));
+    }
+}
@@ -0,0 +1,82 @@
+//! This module paints a [`DisplayList`] with GDI.
+//!
+//! The painter does nothing but carrying out drawing commands, everything else is decided while
+//! building the list. Outlines are painted as four filled bands, so they have the same pixels as
+//! in any other backend, whatever the width.
+//!
+//! Only the background comes from the [`Theme`], so parts of a window the list doesn't cover never
+//! show the default background of the system.
+
+use windows::Win32::Foundation::{COLORREF, HWND, RECT};
+use windows::Win32::Graphics::Gdi::{CreateSolidBrush, DeleteObject, DrawTextA, DT_CENTER,
+                                    DT_END_ELLIPSIS, DT_LEFT, DT_NOPREFIX, DT_SINGLELINE,
+                                    DT_VCENTER, FillRect, HDC, SetBkMode, SetTextColor,
+                                    TRANSPARENT};
+use windows::Win32::UI::HiDpi::GetDpiForWindow;
+
+use crate::control_center_layout::BASE_DPI;
+use crate::display_list::{Color, DisplayList, DrawCommand, TextAlign};
+use crate::geometry::Rect;
+use crate::theme::Theme;
+
+/// Get the DPI of the display the window is on, which display lists are built for.
+pub fn window_dpi(window: HWND) -> u32 {
+    match unsafe { GetDpiForWindow(window) } {
+        0 => BASE_DPI,
+        dpi => dpi,
+    }
+}
+
+/// Fill the area, usually the one being repainted, with the background of the theme, and paint
+/// all commands of the list over it.
+pub fn paint(hdc: HDC, area: &Rect, list: &DisplayList, theme: &Theme) {
+    unsafe {
+        SetBkMode(hdc, TRANSPARENT);
+    }
+
+    fill(hdc, area, theme.background);
+
+    for command in list.commands() {
+        match command {
+            DrawCommand::Fill { rect, color } => fill(hdc, rect, *color),
+            DrawCommand::Stroke { rect, color, width } => {
+                let width = (*width).min(rect.width() / 2).min(rect.height() / 2);
+                if width <= 0 {
+                    continue;
+                }
+
+                let Rect { left, top, right, bottom } = *rect;
+                fill(hdc, &Rect::new(left, top, right, top + width), *color);
+                fill(hdc, &Rect::new(left, bottom - width, right, bottom), *color);
+                fill(hdc, &Rect::new(left, top + width, left + width, bottom - width), *color);
+                fill(hdc, &Rect::new(right - width, top + width, right, bottom - width), *color);
+            },
+            DrawCommand::Text { rect, text, color, align } => {
+                let align = match align {
+                    TextAlign::Left => DT_LEFT,
+                    TextAlign::Center => DT_CENTER,
+                };
+                let mut text = text.as_bytes().to_vec();
+                let mut bounds = RECT::from(*rect);
+
+                unsafe {
+                    SetTextColor(hdc, color_ref(*color));
+                    DrawTextA(hdc, &mut text, &mut bounds,
+                              align | DT_VCENTER | DT_SINGLELINE | DT_END_ELLIPSIS | DT_NOPREFIX);
+                }
+            },
+        }
+    }
+}
+
+fn fill(hdc: HDC, rect: &Rect, color: Color) {
+    unsafe {
+        let brush = CreateSolidBrush(color_ref(color));
+        FillRect(hdc, &RECT::from(*rect), brush);
+        DeleteObject(brush);
+    }
+}
+
+fn color_ref(color: Color) -> COLORREF {
+    COLORREF(color.red as u32 | (color.green as u32) << 8 | (color.blue as u32) << 16)
+}
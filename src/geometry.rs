@@ -0,0 +1,155 @@
+//! This module contains platform-independent geometry primitives.
+//!
+//! Rectangular constantly converts between pixel rectangles reported by Windows and fractional
+//! rectangles describing a window position relatively to a monitor's work area. Keeping these types
+//! free of any Windows API makes the arithmetic easy to test on any platform.
+
+use serde::Deserialize;
+
+/// A rectangle expressed in pixels.
+///
+/// The layout mirrors Windows' `RECT`: the `right` and `bottom` edges are exclusive, so the width
+/// of the rectangle is simply `right - left`.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Rect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl Rect {
+    /// Create a rectangle from its edges.
+    pub const fn new(left: i32, top: i32, right: i32, bottom: i32) -> Rect {
+        Rect { left, top, right, bottom }
+    }
+
+    /// Create a rectangle from its top-left corner and size.
+    pub const fn from_size(left: i32, top: i32, width: i32, height: i32) -> Rect {
+        Rect { left, top, right: left + width, bottom: top + height }
+    }
+
+    pub const fn width(&self) -> i32 {
+        self.right - self.left
+    }
+
+    pub const fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+
+    /// Check whether the point lies inside the rectangle.
+    ///
+    /// Following the `RECT` convention, the left and top edges are inclusive, while the right and
+    /// bottom ones are not.
+    pub const fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.left && x < self.right && y >= self.top && y < self.bottom
+    }
+
+    /// Shrink the rectangle by the given amount of pixels on every side.
+    ///
+    /// A negative amount grows the rectangle instead.
+    pub const fn inset(&self, amount: i32) -> Rect {
+        Rect {
+            left: self.left + amount,
+            top: self.top + amount,
+            right: self.right - amount,
+            bottom: self.bottom - amount,
+        }
+    }
+
+    /// Get the largest rectangle of the same aspect ratio as this one, centered in `area`.
+    ///
+    /// A degenerate rectangle or area yields an empty rectangle at the top-left corner of the area.
+    pub fn fit(&self, area: &Rect) -> Rect {
+        if self.width() <= 0 || self.height() <= 0 || area.width() <= 0 || area.height() <= 0 {
+            return Rect::new(area.left, area.top, area.left, area.top);
+        }
+
+        let (width, height) = match area.width() as i64 * self.height() as i64
+            > area.height() as i64 * self.width() as i64 {
+            // The area is wider than the rectangle, so its height is the limit.
+            true => ((area.height() as i64 * self.width() as i64 / self.height() as i64) as i32,
+                     area.height()),
+            false => (area.width(),
+                      (area.width() as i64 * self.height() as i64 / self.width() as i64) as i32),
+        };
+
+        Rect::from_size(area.left + (area.width() - width) / 2,
+                        area.top + (area.height() - height) / 2, width, height)
+    }
+
+    /// Map a fractional rectangle onto this rectangle.
+    ///
+    /// The edges are rounded independently, so two adjacent fractions (e.g. two halves) always
+    /// share exactly the same edge, without a one pixel gap or overlap between them.
+    pub fn from_fraction(&self, fraction: &FracRect) -> Rect {
+        let width = self.width() as f64;
+        let height = self.height() as f64;
+
+        Rect {
+            left: self.left + (fraction.x * width).round() as i32,
+            top: self.top + (fraction.y * height).round() as i32,
+            right: self.left + ((fraction.x + fraction.width) * width).round() as i32,
+            bottom: self.top + ((fraction.y + fraction.height) * height).round() as i32,
+        }
+    }
+
+    /// Express this rectangle as a fraction of the `outer` rectangle.
+    ///
+    /// This is the inverse of [`Rect::from_fraction`]. A degenerate `outer` rectangle yields an
+    /// empty fraction.
+    pub fn to_fraction(&self, outer: &Rect) -> FracRect {
+        if outer.width() <= 0 || outer.height() <= 0 {
+            return FracRect::default();
+        }
+
+        let width = outer.width() as f64;
+        let height = outer.height() as f64;
+
+        FracRect {
+            x: (self.left - outer.left) as f64 / width,
+            y: (self.top - outer.top) as f64 / height,
+            width: self.width() as f64 / width,
+            height: self.height() as f64 / height,
+        }
+    }
+}
+
+/// A rectangle expressed as fractions of another rectangle, usually a monitor's work area.
+///
+/// All values are expected to be in `0.0..=1.0` range, where `(0, 0)` is the top-left corner of the
+/// outer rectangle, and a width of `1.0` means the full width of it.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Deserialize)]
+pub struct FracRect {
+    pub x: f64,
+    pub y: f64,
+    pub width: f64,
+    pub height: f64,
+}
+
+impl FracRect {
+    pub const fn new(x: f64, y: f64, width: f64, height: f64) -> FracRect {
+        FracRect { x, y, width, height }
+    }
+
+    /// The whole outer rectangle.
+    pub const FULL: FracRect = FracRect::new(0.0, 0.0, 1.0, 1.0);
+
+    /// Check whether the fraction lies within the outer rectangle, and isn't empty.
+    pub fn is_valid(&self) -> bool {
+        const EPSILON: f64 = 0.0001;
+
+        self.x >= 0.0 && self.y >= 0.0 && self.width > 0.0 && self.height > 0.0
+            && self.x + self.width <= 1.0 + EPSILON && self.y + self.height <= 1.0 + EPSILON
+    }
+
+    /// Check whether two fractions are the same, give or take the rounding errors.
+    pub fn approx_eq(&self, other: &FracRect) -> bool {
+        const EPSILON: f64 = 0.01;
+
+        (self.x - other.x).abs() < EPSILON
+            && (self.y - other.y).abs() < EPSILON
+            && (self.width - other.width).abs() < EPSILON
+            && (self.height - other.height).abs() < EPSILON
+    }
+}
@@ -0,0 +1,262 @@
+//! This module contains the model of the grid picker of the Control Center.
+//!
+//! Halves and thirds aren't enough on large monitors, so the Control Center also shows the work
+//! area of the monitor divided into a grid of cells, like Divvy does. Dragging across the cells
+//! selects a span of them, and releasing the mouse button moves the window to the region the span
+//! covers. The size of the grid is configured per monitor, see
+//! [`GridSettings`](crate::settings::GridSettings).
+//!
+//! Everything here is free of any Windows API: [`Grid`] maps points of the picker to cells,
+//! [`GridDrag`] follows the mouse, and [`CellSpan`] converts the selection to a fraction of the
+//! work area, and then to pixels, with gaps left the same way as for other actions.
+
+use crate::engine;
+use crate::geometry::{FracRect, Rect};
+use crate::settings::Gaps;
+
+/// The largest number of columns or rows of a grid.
+///
+/// Spans are passed between windows with every edge packed into a byte, and cells of larger grids
+/// would be too small to hit anyway.
+pub const MAX_CELLS: u32 = 32;
+
+/// A number of columns and rows of a grid.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct GridSize {
+    pub columns: u32,
+    pub rows: u32,
+}
+
+impl GridSize {
+    /// Create a size, limiting both values to `1..=MAX_CELLS`.
+    pub fn new(columns: u32, rows: u32) -> GridSize {
+        GridSize { columns: columns.clamp(1, MAX_CELLS), rows: rows.clamp(1, MAX_CELLS) }
+    }
+}
+
+/// Six columns and four rows, which give cells of roughly the same aspect ratio as a 16:9
+/// monitor.
+impl Default for GridSize {
+    fn default() -> Self {
+        GridSize { columns: 6, rows: 4 }
+    }
+}
+
+/// A single cell of a grid, counted from the top-left one.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Cell {
+    pub column: u32,
+    pub row: u32,
+}
+
+impl Cell {
+    pub const fn new(column: u32, row: u32) -> Cell {
+        Cell { column, row }
+    }
+}
+
+/// A rectangular span of cells. Both corners are inclusive, so a span always has at least one
+/// cell.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct CellSpan {
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+}
+
+impl CellSpan {
+    /// Create the span of cells between two opposite corners, given in any order.
+    ///
+    /// This is how a drag is normalized: it doesn't matter in which direction it went.
+    pub fn between(first: Cell, second: Cell) -> CellSpan {
+        CellSpan {
+            left: first.column.min(second.column),
+            top: first.row.min(second.row),
+            right: first.column.max(second.column),
+            bottom: first.row.max(second.row),
+        }
+    }
+
+    /// Create the span of a single cell.
+    pub fn single(cell: Cell) -> CellSpan {
+        CellSpan::between(cell, cell)
+    }
+
+    pub const fn columns(&self) -> u32 {
+        self.right - self.left + 1
+    }
+
+    pub const fn rows(&self) -> u32 {
+        self.bottom - self.top + 1
+    }
+
+    /// Check whether the cell is a part of the span.
+    pub const fn contains(&self, cell: Cell) -> bool {
+        cell.column >= self.left && cell.column <= self.right
+            && cell.row >= self.top && cell.row <= self.bottom
+    }
+
+    /// Express the span as a fraction of an area divided into a grid of the given size.
+    pub fn to_fraction(&self, size: GridSize) -> FracRect {
+        let columns = size.columns.max(1) as f64;
+        let rows = size.rows.max(1) as f64;
+
+        FracRect::new(self.left as f64 / columns, self.top as f64 / rows,
+                      self.columns() as f64 / columns, self.rows() as f64 / rows)
+    }
+
+    /// Pack the span into a single number, one byte per edge, to pass it in a window message.
+    pub const fn pack(&self) -> u32 {
+        (self.left & 0xFF) | (self.top & 0xFF) << 8 | (self.right & 0xFF) << 16
+            | (self.bottom & 0xFF) << 24
+    }
+
+    /// Unpack a span packed with [`CellSpan::pack`].
+    pub fn unpack(packed: u32) -> CellSpan {
+        let byte = |shift: u32| (packed >> shift) & 0xFF;
+        CellSpan::between(Cell::new(byte(0), byte(8)), Cell::new(byte(16), byte(24)))
+    }
+
+    /// Compute the visible rectangle of a window moved to the span of the work area, leaving the
+    /// gaps around it.
+    pub fn target_rect(&self, size: GridSize, work_area: &Rect, gaps: &Gaps) -> Rect {
+        engine::fraction_rect(&self.to_fraction(size), work_area, gaps)
+    }
+}
+
+/// A grid shown in the picker, in client coordinates.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Grid {
+    size: GridSize,
+    area: Rect,
+}
+
+impl Grid {
+    pub fn new(size: GridSize, area: Rect) -> Grid {
+        Grid { size: GridSize::new(size.columns, size.rows), area }
+    }
+
+    pub fn size(&self) -> GridSize {
+        self.size
+    }
+
+    pub fn area(&self) -> Rect {
+        self.area
+    }
+
+    /// Get the rectangle covered by a span of cells.
+    ///
+    /// Edges are rounded the same way as in [`Rect::from_fraction`], so adjacent cells share an
+    /// edge, without a gap or overlap between them.
+    pub fn span_rect(&self, span: &CellSpan) -> Rect {
+        self.area.from_fraction(&span.to_fraction(self.size))
+    }
+
+    /// Iterate over all cells, from top to bottom, and from left to right.
+    pub fn cells(&self) -> impl Iterator<Item = Cell> {
+        let GridSize { columns, rows } = self.size;
+        (0..rows).flat_map(move |row| (0..columns).map(move |column| Cell::new(column, row)))
+    }
+
+    /// Find the cell at the point, `None` outside of the grid.
+    pub fn hit_test(&self, x: i32, y: i32) -> Option<Cell> {
+        if !self.area.contains(x, y) {
+            return None;
+        }
+
+        self.nearest_cell(x, y)
+    }
+
+    /// Find the cell closest to the point, which may lie outside of the grid.
+    ///
+    /// This keeps a drag going when the mouse overshoots the edge of the grid. Returns `None` only
+    /// when the grid has no area at all.
+    pub fn nearest_cell(&self, x: i32, y: i32) -> Option<Cell> {
+        if self.area.width() <= 0 || self.area.height() <= 0 {
+            return None;
+        }
+
+        let column = (0..self.size.columns)
+            .find(|column| x < self.span_rect(&CellSpan::single(Cell::new(*column, 0))).right)
+            .unwrap_or(self.size.columns - 1);
+        let row = (0..self.size.rows)
+            .find(|row| y < self.span_rect(&CellSpan::single(Cell::new(0, *row))).bottom)
+            .unwrap_or(self.size.rows - 1);
+
+        Some(Cell::new(column, row))
+    }
+}
+
+/// Mouse interaction with the grid picker.
+///
+/// Before the button is pressed, the cell under the mouse is highlighted. Pressing it over a cell
+/// starts a drag, which selects the span between that cell and the one under the mouse, or the
+/// nearest one when the mouse is outside of the grid. Releasing the button finishes the drag and
+/// gives the selected span.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct GridDrag {
+    /// The cell the drag started at, `None` when no drag is in progress.
+    anchor: Option<Cell>,
+    /// The cell under the mouse, or the nearest one while dragging.
+    current: Option<Cell>,
+}
+
+impl GridDrag {
+    /// Start a drag at the point. Returns `false`, and doesn't start it, outside of the grid.
+    pub fn press(&mut self, grid: &Grid, x: i32, y: i32) -> bool {
+        match grid.hit_test(x, y) {
+            Some(cell) => {
+                self.anchor = Some(cell);
+                self.current = Some(cell);
+                true
+            },
+            None => false,
+        }
+    }
+
+    /// Follow the mouse. Returns whether the selection has changed, so it needs repainting.
+    pub fn move_to(&mut self, grid: &Grid, x: i32, y: i32) -> bool {
+        let cell = match self.anchor {
+            Some(_) => grid.nearest_cell(x, y),
+            None => grid.hit_test(x, y),
+        };
+
+        cell != std::mem::replace(&mut self.current, cell)
+    }
+
+    /// Finish the drag at the point, returning the selected span. Returns `None` if no drag has
+    /// been in progress.
+    pub fn release(&mut self, grid: &Grid, x: i32, y: i32) -> Option<CellSpan> {
+        let anchor = self.anchor.take()?;
+        let end = grid.nearest_cell(x, y).unwrap_or(anchor);
+        self.current = grid.hit_test(x, y);
+
+        Some(CellSpan::between(anchor, end))
+    }
+
+    /// Stop the drag without selecting anything, e.g. when the mouse capture is lost.
+    pub fn cancel(&mut self) {
+        self.anchor = None;
+    }
+
+    /// Forget the cell under the mouse, when it leaves the window. A drag in progress continues.
+    pub fn leave(&mut self) {
+        if self.anchor.is_none() {
+            self.current = None;
+        }
+    }
+
+    pub fn is_dragging(&self) -> bool {
+        self.anchor.is_some()
+    }
+
+    /// The span to highlight: the selection while dragging, or the cell under the mouse.
+    pub fn selection(&self) -> Option<CellSpan> {
+        match (self.anchor, self.current) {
+            (Some(anchor), Some(current)) => Some(CellSpan::between(anchor, current)),
+            (Some(anchor), None) => Some(CellSpan::single(anchor)),
+            (None, current) => current.map(CellSpan::single),
+        }
+    }
+}
@@ -0,0 +1,360 @@
+//! This module contains the representation of keyboard shortcuts.
+//!
+//! Shortcuts are written by humans as strings like `Ctrl+Alt+Left`, and consumed by Windows as a
+//! pair of modifier flags and a virtual-key code. [`Hotkey`] converts between both worlds. The
+//! parsing and formatting are platform-independent, only [`register`] and [`unregister`] talk to
+//! Windows.
+//!
+//! The key of a shortcut can be given either by its virtual-key name (`Ctrl+Alt+[`), or by the
+//! scan code of the physical key (`Ctrl+Alt+sc:0x1A`). The former follows the character printed
+//! on the key, which differs between keyboard layouts, while the latter always refers to the same
+//! key on the keyboard. See [`crate::keyboard_layout`] for the mapping between the two.
+
+use std::error::Error;
+use std::fmt;
+use std::ops::BitOr;
+use std::str::FromStr;
+
+use serde::{de, Deserialize, Deserializer};
+use windows::Win32::Foundation::HWND;
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    HOT_KEY_MODIFIERS, MOD_NOREPEAT, RegisterHotKey, UnregisterHotKey,
+};
+
+use crate::keyboard_layout::{self, KeyMap};
+
+/// Virtual-key code of the Escape key.
+pub const VK_ESCAPE: u16 = 0x1B;
+
+/// Virtual-key code of the Space key.
+pub const VK_SPACE: u16 = 0x20;
+
+/// Names of keys which are not a single letter or digit, with their virtual-key codes.
+///
+/// The first name of every key is the canonical one, used when a shortcut is formatted. Lookup is
+/// case-insensitive.
+const KEY_NAMES: [(&str, u16); 38] = [
+    ("Backspace", 0x08),
+    ("Tab", 0x09),
+    ("Enter", 0x0D),
+    ("Return", 0x0D),
+    ("Esc", VK_ESCAPE),
+    ("Escape", VK_ESCAPE),
+    ("Space", VK_SPACE),
+    ("PageUp", 0x21),
+    ("PageDown", 0x22),
+    ("End", 0x23),
+    ("Home", 0x24),
+    ("Left", 0x25),
+    ("Up", 0x26),
+    ("Right", 0x27),
+    ("Down", 0x28),
+    ("Insert", 0x2D),
+    ("Delete", 0x2E),
+    ("Del", 0x2E),
+    ("Num0", 0x60),
+    ("Num1", 0x61),
+    ("Num2", 0x62),
+    ("Num3", 0x63),
+    ("Num4", 0x64),
+    ("Num5", 0x65),
+    ("Num6", 0x66),
+    ("Num7", 0x67),
+    ("Num8", 0x68),
+    ("Num9", 0x69),
+    (";", 0xBA),
+    ("=", 0xBB),
+    (",", 0xBC),
+    ("-", 0xBD),
+    (".", 0xBE),
+    ("/", 0xBF),
+    ("`", 0xC0),
+    ("[", 0xDB),
+    ("\\", 0xDC),
+    ("]", 0xDD),
+];
+
+/// Virtual-key code of `F1`, the remaining function keys follow it.
+const VK_F1: u16 = 0x70;
+
+/// A prefix of keys given by a scan code.
+const SCAN_CODE_PREFIX: &str = "sc:";
+
+/// A set of modifier keys.
+///
+/// The values of the flags are the same as the ones expected by `RegisterHotKey`, so no conversion
+/// is needed when a shortcut is registered.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct Modifiers(u32);
+
+impl Modifiers {
+    pub const NONE: Modifiers = Modifiers(0);
+    pub const ALT: Modifiers = Modifiers(0x1);
+    pub const CTRL: Modifiers = Modifiers(0x2);
+    pub const SHIFT: Modifiers = Modifiers(0x4);
+    pub const WIN: Modifiers = Modifiers(0x8);
+
+    /// Modifiers in the order they appear in a formatted shortcut, along with their names.
+    const NAMES: [(Modifiers, &'static str); 4] = [
+        (Modifiers::CTRL, "Ctrl"),
+        (Modifiers::ALT, "Alt"),
+        (Modifiers::SHIFT, "Shift"),
+        (Modifiers::WIN, "Win"),
+    ];
+
+    /// All names accepted when a shortcut is parsed.
+    const ALIASES: [(&'static str, Modifiers); 7] = [
+        ("Ctrl", Modifiers::CTRL),
+        ("Control", Modifiers::CTRL),
+        ("Alt", Modifiers::ALT),
+        ("Shift", Modifiers::SHIFT),
+        ("Win", Modifiers::WIN),
+        ("Super", Modifiers::WIN),
+        ("Meta", Modifiers::WIN),
+    ];
+
+    pub const fn bits(self) -> u32 {
+        self.0
+    }
+
+    pub const fn contains(self, other: Modifiers) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Names of the modifiers in the set, in the order they appear in a formatted shortcut.
+    pub fn names(self) -> impl Iterator<Item = &'static str> {
+        Modifiers::NAMES.into_iter()
+            .filter(move |(modifier, _)| self.contains(*modifier))
+            .map(|(_, name)| name)
+    }
+
+    /// Find a modifier by one of its names, case-insensitively.
+    fn from_name(name: &str) -> Option<Modifiers> {
+        Modifiers::ALIASES.iter()
+            .find(|(alias, _)| alias.eq_ignore_ascii_case(name))
+            .map(|(_, modifier)| *modifier)
+    }
+}
+
+impl BitOr for Modifiers {
+    type Output = Modifiers;
+
+    fn bitor(self, rhs: Modifiers) -> Modifiers {
+        Modifiers(self.0 | rhs.0)
+    }
+}
+
+/// A key of a shortcut.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum KeySpec {
+    /// A virtual-key code, its physical position depends on the keyboard layout.
+    Virtual(u16),
+    /// A scan code of a physical key, independent of the keyboard layout.
+    Scan(u16),
+}
+
+impl KeySpec {
+    /// Parse a key name.
+    ///
+    /// Besides names accepted by [`key_from_name`], a scan code can be given as `sc:` followed by
+    /// a hexadecimal (`sc:0x1A`) or decimal (`sc:26`) number.
+    pub fn from_name(name: &str) -> Option<KeySpec> {
+        let Some(code) = name.strip_prefix(SCAN_CODE_PREFIX) else {
+            return key_from_name(name).map(KeySpec::Virtual);
+        };
+
+        let parsed = match code.strip_prefix("0x").or_else(|| code.strip_prefix("0X")) {
+            Some(hex) => u16::from_str_radix(hex, 16),
+            None => code.parse(),
+        };
+
+        parsed.ok().filter(|code| *code != 0).map(KeySpec::Scan)
+    }
+}
+
+impl fmt::Display for KeySpec {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            KeySpec::Virtual(key) => match key_name(*key) {
+                Some(name) => f.write_str(&name),
+                None => write!(f, "0x{key:02X}"),
+            },
+            KeySpec::Scan(code) => write!(f, "{SCAN_CODE_PREFIX}0x{code:02X}"),
+        }
+    }
+}
+
+/// A keyboard shortcut: a set of modifiers and a key.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Hotkey {
+    pub modifiers: Modifiers,
+    pub key: KeySpec,
+}
+
+impl Hotkey {
+    pub const fn new(modifiers: Modifiers, key: KeySpec) -> Hotkey {
+        Hotkey { modifiers, key }
+    }
+}
+
+/// An error returned when a string can't be parsed as a [`Hotkey`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum HotkeyParseError {
+    /// The string is empty, or contains an empty part (like `Ctrl++A`).
+    Empty,
+    /// One of the parts before the key isn't a known modifier.
+    UnknownModifier(String),
+    /// The last part isn't a known key.
+    UnknownKey(String),
+}
+
+impl fmt::Display for HotkeyParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            HotkeyParseError::Empty => write!(f, "empty shortcut or shortcut part"),
+            HotkeyParseError::UnknownModifier(name) => write!(f, "unknown modifier \"{name}\""),
+            HotkeyParseError::UnknownKey(name) => write!(f, "unknown key \"{name}\""),
+        }
+    }
+}
+
+impl Error for HotkeyParseError {}
+
+impl FromStr for Hotkey {
+    type Err = HotkeyParseError;
+
+    /// Parse a shortcut written as modifiers and a key joined with `+`, e.g. `Ctrl+Alt+Left`.
+    fn from_str(s: &str) -> Result<Hotkey, HotkeyParseError> {
+        let mut parts: Vec<&str> = s.split('+').map(str::trim).collect();
+        let key_name = parts.pop().unwrap_or_default();
+
+        if key_name.is_empty() || parts.iter().any(|part| part.is_empty()) {
+            return Err(HotkeyParseError::Empty);
+        }
+
+        let mut modifiers = Modifiers::NONE;
+        for part in parts {
+            modifiers = modifiers | Modifiers::from_name(part)
+                .ok_or_else(|| HotkeyParseError::UnknownModifier(part.to_string()))?;
+        }
+
+        let key = KeySpec::from_name(key_name)
+            .ok_or_else(|| HotkeyParseError::UnknownKey(key_name.to_string()))?;
+
+        Ok(Hotkey { modifiers, key })
+    }
+}
+
+impl<'de> Deserialize<'de> for Hotkey {
+    /// Deserialize a shortcut from a string accepted by [`Hotkey::from_str`].
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Hotkey, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(|error| de::Error::custom(format!("invalid shortcut: {error}")))
+    }
+}
+
+impl fmt::Display for Hotkey {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for name in self.modifiers.names() {
+            write!(f, "{name}+")?;
+        }
+
+        write!(f, "{}", self.key)
+    }
+}
+
+/// Find a virtual-key code of the key with the given name.
+///
+/// Letters and digits are their own names, function keys are named `F1` to `F24`, and the names of
+/// remaining keys are listed in [`KEY_NAMES`]. The lookup is case-insensitive.
+pub fn key_from_name(name: &str) -> Option<u16> {
+    let mut chars = name.chars();
+    if let (Some(ch), None) = (chars.next(), chars.next()) {
+        if ch.is_ascii_alphanumeric() {
+            return Some(ch.to_ascii_uppercase() as u16);
+        }
+    }
+
+    if let Some(number) = name.strip_prefix(['F', 'f']).and_then(|n| n.parse::<u16>().ok()) {
+        if (1..=24).contains(&number) {
+            return Some(VK_F1 + number - 1);
+        }
+    }
+
+    KEY_NAMES.iter()
+        .find(|(key_name, _)| key_name.eq_ignore_ascii_case(name))
+        .map(|(_, code)| *code)
+}
+
+/// Get the canonical name of the key with the given virtual-key code.
+pub fn key_name(key: u16) -> Option<String> {
+    match key {
+        0x30..=0x39 | 0x41..=0x5A => Some(char::from(key as u8).to_string()),
+        _ if (VK_F1..VK_F1 + 24).contains(&key) => Some(format!("F{}", key - VK_F1 + 1)),
+        _ => KEY_NAMES.iter()
+            .find(|(_, code)| *code == key)
+            .map(|(name, _)| name.to_string()),
+    }
+}
+
+/// Get a regular expression matching the shortcuts accepted by [`Hotkey::from_str`].
+///
+/// The expression is meant for the schema of the settings file, which has no way of making it
+/// case-insensitive, so every letter is written as a class of both of its cases.
+pub fn hotkey_pattern() -> String {
+    let modifiers: Vec<String> = Modifiers::ALIASES.iter()
+        .map(|(alias, _)| pattern_literal(alias))
+        .collect();
+
+    let mut keys = vec![
+        "[A-Za-z0-9]".to_string(),
+        "[Ff]([1-9]|1[0-9]|2[0-4])".to_string(),
+        format!("{SCAN_CODE_PREFIX}(0[xX][0-9A-Fa-f]+|[0-9]+)"),
+    ];
+    keys.extend(KEY_NAMES.iter().map(|(name, _)| pattern_literal(name)));
+
+    format!(r"^\s*(({})\s*\+\s*)*({})\s*$", modifiers.join("|"), keys.join("|"))
+}
+
+/// Write a text as a regular expression matching it case-insensitively.
+fn pattern_literal(text: &str) -> String {
+    text.chars()
+        .map(|ch| match ch {
+            _ if ch.is_ascii_alphabetic() => {
+                format!("[{}{}]", ch.to_ascii_uppercase(), ch.to_ascii_lowercase())
+            },
+            '\\' | '^' | '$' | '.' | '|' | '?' | '*' | '+' | '(' | ')' | '[' | ']' | '{' | '}' => {
+                format!("\\{ch}")
+            },
+            _ => ch.to_string(),
+        })
+        .collect()
+}
+
+/// Register a system-wide hotkey which will be delivered to the window as `WM_HOTKEY` message.
+///
+/// Keys given by a scan code are translated using the given keyboard layout, so they have to be
+/// registered again when it changes. Auto-repeat is disabled, so holding the shortcut triggers it
+/// only once. Returns `false` when the shortcut couldn't be registered, usually because another
+/// application already owns it.
+pub fn register(window: HWND, id: i32, hotkey: &Hotkey, keys: &dyn KeyMap) -> bool {
+    let Some(key) = keyboard_layout::resolve_vk(hotkey.key, keys) else {
+        return false;
+    };
+
+    unsafe {
+        let modifiers = HOT_KEY_MODIFIERS(hotkey.modifiers.bits()) | MOD_NOREPEAT;
+        RegisterHotKey(window, id, modifiers, key as u32).as_bool()
+    }
+}
+
+/// Unregister a hotkey previously registered with [`register`].
+pub fn unregister(window: HWND, id: i32) {
+    unsafe {
+        UnregisterHotKey(window, id);
+    }
+}
@@ -0,0 +1,151 @@
+//! This module decides which windows Rectangular leaves alone.
+//!
+//! Some applications, like remote desktop clients, games or viewers of virtual machines, must
+//! never be moved, and often need shortcuts of their own. They are listed in the ignore list, see
+//! [`IgnoredApp`]. An entry names the executable, either by its name, compared case-insensitively
+//! like Windows does, or by its full path. It may also give patterns of the window class and title,
+//! so only some windows of the application are ignored.
+//!
+//! Actions are never applied to an ignored window. Entries with `suspend-hotkeys` also release
+//! shortcuts of Rectangular while a matching window is in the foreground, the same way pausing
+//! does, see [`pause`](crate::pause).
+//!
+//! Matching works on a [`WindowIdentity`], so it can be tested without Windows. [`identify`]
+//! describes a real window.
+
+use std::path::PathBuf;
+
+use windows::core::PWSTR;
+use windows::Win32::Foundation::{CloseHandle, HWND};
+use windows::Win32::System::Threading::{
+    OpenProcess, PROCESS_NAME_WIN32, PROCESS_QUERY_LIMITED_INFORMATION,
+    QueryFullProcessImageNameW,
+};
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetClassNameA, GetWindowTextLengthW, GetWindowTextW, GetWindowThreadProcessId,
+};
+
+use crate::settings::IgnoredApp;
+
+/// What an entry of the ignore list is matched against.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WindowIdentity {
+    /// The full path of the executable which created the window.
+    pub executable: String,
+    pub class_name: String,
+    pub title: String,
+}
+
+/// Describe the window, `None` if its executable can't be found, e.g. because the window is gone
+/// or belongs to an elevated process.
+pub fn identify(window: HWND) -> Option<WindowIdentity> {
+    let executable = executable_path(window)?;
+
+    unsafe {
+        let mut class_name = [0u8; 256];
+        let class_length = GetClassNameA(window, &mut class_name).max(0) as usize;
+
+        let mut title = vec![0u16; GetWindowTextLengthW(window).max(0) as usize + 1];
+        let title_length = GetWindowTextW(window, &mut title).max(0) as usize;
+
+        Some(WindowIdentity {
+            executable: executable.to_string_lossy().into_owned(),
+            class_name: String::from_utf8_lossy(&class_name[..class_length]).into_owned(),
+            title: String::from_utf16_lossy(&title[..title_length]),
+        })
+    }
+}
+
+/// Get the full path of the executable which owns the window.
+fn executable_path(window: HWND) -> Option<PathBuf> {
+    unsafe {
+        let mut process_id = 0u32;
+        GetWindowThreadProcessId(window, Some(&mut process_id));
+
+        let process = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, false, process_id).ok()?;
+        let mut buffer = [0u16; 1024];
+        let mut length = buffer.len() as u32;
+        let result = QueryFullProcessImageNameW(process, PROCESS_NAME_WIN32,
+                                                PWSTR(buffer.as_mut_ptr()), &mut length);
+        CloseHandle(process);
+
+        result.as_bool()
+            .then(|| PathBuf::from(String::from_utf16_lossy(&buffer[..length as usize])))
+    }
+}
+
+/// Get the name of the executable from its path, e.g. `mstsc.exe`.
+pub fn app_name(executable: &str) -> &str {
+    executable.rsplit(['\\', '/']).next().unwrap_or(executable)
+}
+
+/// Check whether the application of an entry, given either as a name or as a full path, is the
+/// executable.
+pub fn names_app(app: &str, executable: &str) -> bool {
+    if app.contains(['\\', '/']) {
+        app.replace('/', "\\").eq_ignore_ascii_case(&executable.replace('/', "\\"))
+    } else {
+        app.eq_ignore_ascii_case(app_name(executable))
+    }
+}
+
+/// Check whether the entry of the ignore list matches the window.
+pub fn matches(ignored: &IgnoredApp, window: &WindowIdentity) -> bool {
+    names_app(&ignored.app, &window.executable)
+        && ignored.class.as_ref().is_none_or(|class| wildcard_match(class, &window.class_name))
+        && ignored.title.as_ref().is_none_or(|title| wildcard_match(title, &window.title))
+}
+
+/// Find the first entry of the ignore list matching the window.
+pub fn find<'a>(ignored_apps: &'a [IgnoredApp], window: &WindowIdentity)
+                -> Option<&'a IgnoredApp> {
+    ignored_apps.iter().find(|ignored| matches(ignored, window))
+}
+
+/// Check whether the window is left alone.
+///
+/// This is the only check there is: actions skip such windows, and the context menu shows their
+/// application as ignored.
+pub fn is_ignored(ignored_apps: &[IgnoredApp], window: &WindowIdentity) -> bool {
+    find(ignored_apps, window).is_some()
+}
+
+/// Check whether shortcuts should be released while the window is in the foreground.
+pub fn suspends_hotkeys(ignored_apps: &[IgnoredApp], window: &WindowIdentity) -> bool {
+    ignored_apps.iter().any(|ignored| ignored.suspend_hotkeys && matches(ignored, window))
+}
+
+/// Match the text against a pattern, where `*` matches any text, including an empty one, and `?`
+/// matches any single character. Letters are compared case-insensitively.
+pub fn wildcard_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.to_lowercase().chars().collect();
+    let text: Vec<char> = text.to_lowercase().chars().collect();
+
+    let (mut p, mut t) = (0, 0);
+    // The position in the pattern after the last star, and where in the text it stops matching.
+    let mut star: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                star = Some((p + 1, t));
+                p += 1;
+            },
+            Some(c) if *c == '?' || *c == text[t] => {
+                p += 1;
+                t += 1;
+            },
+            _ => match star {
+                // Let the star match one more character, and try again after it.
+                Some((after, end)) => {
+                    star = Some((after, end + 1));
+                    p = after;
+                    t = end + 1;
+                },
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
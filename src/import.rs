@@ -0,0 +1,128 @@
+//! This module contains what's common to importers of settings from other applications.
+//!
+//! Importers never touch the settings file on their own. Each of them reads an export of another
+//! application, and produces a fragment of Rectangular's settings file, which the user can review
+//! and merge into their settings. Whatever couldn't be carried over is listed in an
+//! [`ImportReport`], and the report is also put at the top of the fragment as comments, so nothing
+//! gets lost without a word.
+
+use std::error::Error;
+use std::fmt;
+
+use serde_json::Value;
+
+use crate::migration;
+use crate::settings::Location;
+
+/// A single thing which couldn't be imported, along with the reason.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct ImportNote {
+    /// What couldn't be imported, in terms of the source application, e.g. `shortcut "restore"`.
+    pub item: String,
+    pub reason: String,
+}
+
+impl fmt::Display for ImportNote {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.item, self.reason)
+    }
+}
+
+/// A list of things which couldn't be imported.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ImportReport {
+    pub notes: Vec<ImportNote>,
+}
+
+impl ImportReport {
+    pub fn add(&mut self, item: impl Into<String>, reason: impl Into<String>) {
+        self.notes.push(ImportNote { item: item.into(), reason: reason.into() });
+    }
+
+    /// Whether everything has been imported.
+    pub fn is_empty(&self) -> bool {
+        self.notes.is_empty()
+    }
+}
+
+/// A syntax error in an exported file, with a line and column, like problems in the settings file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct JsonError {
+    pub location: Location,
+    pub message: String,
+}
+
+impl fmt::Display for JsonError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.location.line, self.location.column, self.message)
+    }
+}
+
+impl Error for JsonError {}
+
+impl From<serde_json::Error> for JsonError {
+    fn from(error: serde_json::Error) -> Self {
+        // The message of serde_json ends with the location, which is kept separately.
+        let message = error.to_string();
+        let message = message.rsplit_once(" at line ").map_or(&message[..], |(message, _)| message);
+
+        JsonError {
+            location: Location { line: error.line(), column: error.column() },
+            message: message.to_string(),
+        }
+    }
+}
+
+/// Parse an exported file. Only the syntax is checked here, importers read the values they know
+/// with their own types, and report the rest.
+pub fn parse_json(text: &str) -> Result<Value, ImportError> {
+    serde_json::from_str(text).map_err(|error| ImportError::Json(error.into()))
+}
+
+/// An error returned when the file can't be imported at all.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ImportError {
+    /// The file is not valid JSON.
+    Json(JsonError),
+    /// The file is valid JSON, but it's not an export of the expected application.
+    Format(String),
+}
+
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Json(error) => write!(f, "{error}"),
+            ImportError::Format(message) => f.write_str(message),
+        }
+    }
+}
+
+impl Error for ImportError {}
+
+impl From<JsonError> for ImportError {
+    fn from(error: JsonError) -> Self {
+        ImportError::Json(error)
+    }
+}
+
+/// Write a fragment of the settings file.
+///
+/// The fragment starts with a comment naming the source application, followed by the report,
+/// and the version of the settings format.
+pub fn settings_fragment(source: &str, report: &ImportReport, table: toml::Table) -> String {
+    let mut text = format!("# Imported from {source}.\n");
+    if !report.is_empty() {
+        text.push_str("#\n# Not imported:\n");
+        for note in &report.notes {
+            text.push_str(&format!("# - {note}\n"));
+        }
+    }
+
+    let mut root = toml::Table::new();
+    root.insert(migration::VERSION_KEY.to_string(),
+                toml::Value::Integer(migration::CURRENT_VERSION as i64));
+    root.extend(table);
+
+    text.push_str(&toml::to_string(&root).unwrap_or_default());
+    text
+}
@@ -0,0 +1,263 @@
+//! This module maps keys between scan codes, virtual-key codes and characters.
+//!
+//! The same physical key produces different characters, and different virtual-key codes, depending
+//! on the active keyboard layout. For example, the key right of `P` is `[` on the US layout, but
+//! `Ü` on the German one. To show shortcuts the way the user sees them on their keyboard, and to
+//! register shortcuts given by a scan code, Rectangular needs a mapping between these three.
+//!
+//! The mapping is abstracted by the [`KeyMap`] trait. [`SystemKeyMap`] asks Windows about the
+//! active layout, while [`LayoutTable`] is a built-in table for a few common layouts. The tables
+//! make the label and fallback rules testable without a live keyboard layout, and they are the
+//! fallback for keys the active layout doesn't know: the table of its language if there's one,
+//! the US table otherwise.
+
+use windows::Win32::UI::Input::KeyboardAndMouse::{
+    GetKeyboardLayout, MapVirtualKeyExW, ToUnicodeEx,
+};
+use windows::Win32::UI::TextServices::HKL;
+use windows::Win32::UI::WindowsAndMessaging::{
+    GetForegroundWindow, GetWindowThreadProcessId, MAPVK_VK_TO_VSC, MAPVK_VSC_TO_VK_EX,
+};
+
+use crate::hotkey::{self, Hotkey, KeySpec};
+
+/// A mapping between scan codes, virtual-key codes and characters of a keyboard layout.
+pub trait KeyMap {
+    /// Get the virtual-key code produced by the physical key with the given scan code.
+    fn vk_from_scan(&self, scan: u16) -> Option<u16>;
+
+    /// Get the scan code of the physical key producing the given virtual-key code.
+    fn scan_from_vk(&self, vk: u16) -> Option<u16>;
+
+    /// Get the character produced by the given virtual-key code, without any modifiers.
+    fn char_from_vk(&self, vk: u16) -> Option<char>;
+
+    /// Get the language identifier of the layout, see [`fallback_table`].
+    fn language_id(&self) -> Option<u16> {
+        None
+    }
+}
+
+/// A single key of a [`LayoutTable`]: a scan code, a virtual-key code and an unshifted character.
+type LayoutKey = (u16, u16, char);
+
+/// Keys of the US layout, the base for all other built-in layouts.
+///
+/// Only keys producing characters are listed, since the remaining keys (arrows, function keys,
+/// etc.) have the same virtual-key codes and names regardless of the layout.
+const US_KEYS: [LayoutKey; 48] = [
+    (0x02, 0x31, '1'), (0x03, 0x32, '2'), (0x04, 0x33, '3'), (0x05, 0x34, '4'),
+    (0x06, 0x35, '5'), (0x07, 0x36, '6'), (0x08, 0x37, '7'), (0x09, 0x38, '8'),
+    (0x0A, 0x39, '9'), (0x0B, 0x30, '0'), (0x0C, 0xBD, '-'), (0x0D, 0xBB, '='),
+    (0x10, 0x51, 'q'), (0x11, 0x57, 'w'), (0x12, 0x45, 'e'), (0x13, 0x52, 'r'),
+    (0x14, 0x54, 't'), (0x15, 0x59, 'y'), (0x16, 0x55, 'u'), (0x17, 0x49, 'i'),
+    (0x18, 0x4F, 'o'), (0x19, 0x50, 'p'), (0x1A, 0xDB, '['), (0x1B, 0xDD, ']'),
+    (0x1E, 0x41, 'a'), (0x1F, 0x53, 's'), (0x20, 0x44, 'd'), (0x21, 0x46, 'f'),
+    (0x22, 0x47, 'g'), (0x23, 0x48, 'h'), (0x24, 0x4A, 'j'), (0x25, 0x4B, 'k'),
+    (0x26, 0x4C, 'l'), (0x27, 0xBA, ';'), (0x28, 0xDE, '\''), (0x29, 0xC0, '`'),
+    (0x2B, 0xDC, '\\'), (0x2C, 0x5A, 'z'), (0x2D, 0x58, 'x'), (0x2E, 0x43, 'c'),
+    (0x2F, 0x56, 'v'), (0x30, 0x42, 'b'), (0x31, 0x4E, 'n'), (0x32, 0x4D, 'm'),
+    (0x33, 0xBC, ','), (0x34, 0xBE, '.'), (0x35, 0xBF, '/'), (0x56, 0xE2, '\\'),
+];
+
+/// Keys of the German (QWERTZ) layout which differ from the US one.
+///
+/// Dead keys (`´` and `^`) are listed with the accent they produce.
+const GERMAN_KEYS: [LayoutKey; 12] = [
+    (0x0C, 0xDB, 'ß'), (0x0D, 0xDD, '´'), (0x15, 0x5A, 'z'), (0x1A, 0xBA, 'ü'),
+    (0x1B, 0xBB, '+'), (0x27, 0xC0, 'ö'), (0x28, 0xDE, 'ä'), (0x29, 0xDC, '^'),
+    (0x2B, 0xBF, '#'), (0x2C, 0x59, 'y'), (0x35, 0xBD, '-'), (0x56, 0xE2, '<'),
+];
+
+/// A built-in description of a keyboard layout.
+///
+/// A table consists of the US keys, and a list of keys overriding them. A base key is ignored if
+/// its scan code is overridden, so the overrides must list every key that moved.
+#[derive(Debug)]
+pub struct LayoutTable {
+    /// The language identifier of the layout, the lower word of Windows' `HKL`.
+    pub language_id: u16,
+    pub name: &'static str,
+    overrides: &'static [LayoutKey],
+}
+
+/// The US layout, the fallback for layouts without a built-in table.
+pub const US_LAYOUT: LayoutTable = LayoutTable {
+    language_id: 0x0409,
+    name: "US",
+    overrides: &[],
+};
+
+/// All built-in layouts.
+///
+/// The default Polish layout on Windows, "Polish (Programmers)", is the US layout with Polish
+/// letters available under `AltGr`, so it shares the US table.
+pub const LAYOUTS: [LayoutTable; 3] = [
+    US_LAYOUT,
+    LayoutTable { language_id: 0x0407, name: "German", overrides: &GERMAN_KEYS },
+    LayoutTable { language_id: 0x0415, name: "Polish (Programmers)", overrides: &[] },
+];
+
+impl LayoutTable {
+    /// Find a built-in layout by its language identifier.
+    pub fn find(language_id: u16) -> Option<&'static LayoutTable> {
+        LAYOUTS.iter().find(|layout| layout.language_id == language_id)
+    }
+
+    fn keys(&self) -> impl Iterator<Item = &LayoutKey> {
+        let base = US_KEYS.iter()
+            .filter(|(scan, _, _)| self.overrides.iter().all(|(other, _, _)| other != scan));
+
+        self.overrides.iter().chain(base)
+    }
+}
+
+impl KeyMap for LayoutTable {
+    fn vk_from_scan(&self, scan: u16) -> Option<u16> {
+        self.keys().find(|key| key.0 == scan).map(|key| key.1)
+    }
+
+    fn scan_from_vk(&self, vk: u16) -> Option<u16> {
+        self.keys().find(|key| key.1 == vk).map(|key| key.0)
+    }
+
+    fn char_from_vk(&self, vk: u16) -> Option<char> {
+        self.keys().find(|key| key.1 == vk).map(|key| key.2)
+    }
+
+    fn language_id(&self) -> Option<u16> {
+        Some(self.language_id)
+    }
+}
+
+/// A key map of the keyboard layout currently active in Windows.
+///
+/// The layout is asked directly, since its language says little about the physical layout: a
+/// Polish user may type on a QWERTZ keyboard, and a German one on a US keyboard.
+///
+/// Two key maps are equal when they describe the same layout, so a change of the layout can be
+/// noticed by comparing them.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SystemKeyMap {
+    layout: HKL,
+}
+
+/// Tells `ToUnicodeEx` to leave the keyboard state, e.g. a pending dead key, as it is.
+const TO_UNICODE_KEEP_STATE: u32 = 0x4;
+
+impl SystemKeyMap {
+    /// Get the key map of the layout the user is typing in.
+    ///
+    /// Windows keeps a layout per thread, so that's the layout of the foreground window's thread,
+    /// not Rectangular's own. Without a foreground window, the layout of the calling thread is
+    /// used.
+    pub fn foreground() -> SystemKeyMap {
+        unsafe {
+            let thread = GetWindowThreadProcessId(GetForegroundWindow(), None);
+            SystemKeyMap { layout: GetKeyboardLayout(thread) }
+        }
+    }
+
+    fn map(&self, code: u16, map_type: u32) -> Option<u32> {
+        let result = unsafe { MapVirtualKeyExW(code as u32, map_type, self.layout) };
+        (result != 0).then_some(result)
+    }
+}
+
+impl KeyMap for SystemKeyMap {
+    fn vk_from_scan(&self, scan: u16) -> Option<u16> {
+        self.map(scan, MAPVK_VSC_TO_VK_EX).map(|vk| vk as u16)
+    }
+
+    fn scan_from_vk(&self, vk: u16) -> Option<u16> {
+        self.map(vk, MAPVK_VK_TO_VSC).map(|scan| scan as u16)
+    }
+
+    fn char_from_vk(&self, vk: u16) -> Option<char> {
+        let scan = self.scan_from_vk(vk).unwrap_or_default();
+        let mut buffer = [0u16; 4];
+
+        // No keys are held down, so the character is the unshifted one. A dead key returns a
+        // negative length, but still writes its accent to the buffer.
+        let length = unsafe {
+            ToUnicodeEx(vk as u32, scan as u32, &[0; 256], &mut buffer, TO_UNICODE_KEEP_STATE,
+                        self.layout)
+        };
+
+        if length == 0 {
+            return None;
+        }
+
+        char::decode_utf16(buffer.iter().copied().take_while(|unit| *unit != 0))
+            .next()
+            .and_then(Result::ok)
+            .filter(|ch| !ch.is_control())
+    }
+
+    fn language_id(&self) -> Option<u16> {
+        // The lower word of a layout handle is its language.
+        Some(self.layout.0 as u16)
+    }
+}
+
+/// Get the built-in table used for keys the key map doesn't know.
+///
+/// That's the table of the map's language, or the US table for languages without one.
+pub fn fallback_table(map: &dyn KeyMap) -> &'static LayoutTable {
+    map.language_id().and_then(LayoutTable::find).unwrap_or(&US_LAYOUT)
+}
+
+/// Get the virtual-key code to register for the given key.
+///
+/// Scan codes unknown to the layout fall back to a built-in table, see [`fallback_table`], so a
+/// shortcut never silently stops working because of an incomplete layout.
+pub fn resolve_vk(key: KeySpec, map: &dyn KeyMap) -> Option<u16> {
+    match key {
+        KeySpec::Virtual(vk) => Some(vk),
+        KeySpec::Scan(scan) => {
+            map.vk_from_scan(scan).or_else(|| fallback_table(map).vk_from_scan(scan))
+        },
+    }
+}
+
+/// Check whether two shortcuts are the same once registered, e.g. `Ctrl+sc:0x1E` and `Ctrl+A` on
+/// the US layout.
+pub fn same_shortcut(first: &Hotkey, second: &Hotkey, map: &dyn KeyMap) -> bool {
+    first.modifiers == second.modifiers
+        && (first.key == second.key || resolve_vk(first.key, map)
+            .is_some_and(|vk| resolve_vk(second.key, map) == Some(vk)))
+}
+
+/// Get a human-readable name of the key, as printed on the user's keyboard.
+///
+/// The rules are applied in order:
+/// 1. Keys producing a character are named after the character, uppercase when possible.
+/// 2. Other keys use their canonical names, like `Left` or `F5`.
+/// 3. Keys which can't be named are shown as in the settings file, e.g. `sc:0x59`.
+pub fn key_label(key: KeySpec, map: &dyn KeyMap) -> String {
+    let Some(vk) = resolve_vk(key, map) else {
+        return key.to_string();
+    };
+
+    if let Some(ch) = map.char_from_vk(vk).or_else(|| fallback_table(map).char_from_vk(vk)) {
+        // Some characters, like `ß`, have no single-character uppercase form, keep them as is.
+        let mut upper = ch.to_uppercase();
+        return match (upper.next(), upper.next()) {
+            (Some(upper), None) => upper.to_string(),
+            _ => ch.to_string(),
+        };
+    }
+
+    hotkey::key_name(vk).unwrap_or_else(|| key.to_string())
+}
+
+/// Get a human-readable label of the shortcut, e.g. `Ctrl+Alt+Ü`.
+pub fn hotkey_label(hotkey: &Hotkey, map: &dyn KeyMap) -> String {
+    let mut label = String::new();
+    for name in hotkey.modifiers.names() {
+        label.push_str(name);
+        label.push('+');
+    }
+
+    label + &key_label(hotkey.key, map)
+}
@@ -10,10 +10,41 @@ use windows::Win32::UI::WindowsAndMessaging::{WINDOW_LONG_PTR_INDEX, WM_USER};
 pub mod notification;
 pub mod utils;
 pub mod context_menu;
+pub mod command;
 pub mod rectangular_window;
 pub mod control_center;
+pub mod control_center_layout;
+pub mod control_center_focus;
+pub mod control_center_view;
+pub mod grid_picker;
+pub mod display_list;
+pub mod theme;
+pub mod gdi_painter;
 pub mod app_window;
 pub mod wndproc;
+pub mod geometry;
+pub mod action;
+pub mod hotkey;
+pub mod keyboard_layout;
+pub mod engine;
+pub mod ignore;
+pub mod chord;
+pub mod pause;
+pub mod settings;
+pub mod settings_edit;
+pub mod migration;
+pub mod import;
+pub mod rectangle_import;
+pub mod fancyzones_import;
+pub mod reload;
+pub mod cli;
+pub mod paths;
+pub mod autostart;
+pub mod schema;
+pub mod window_system;
+pub mod foreground;
+pub mod preview;
+pub mod preview_overlay;
 
 /// A window class used by all Rectangular windows.
 pub const WINDOW_CLASS_NAME: PCSTR = s!("Rectangular_Common_Class");
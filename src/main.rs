@@ -1,20 +1,108 @@
 //! This module is fully dedicated to the `main` method.
 #![windows_subsystem = "windows"]
 
-use windows::core::Result;
+use std::env;
+use std::path::PathBuf;
+use std::process;
+
 use windows::Win32::Foundation::HWND;
+use windows::Win32::System::Console::{AttachConsole, ATTACH_PARENT_PROCESS};
 use windows::Win32::System::LibraryLoader::GetModuleHandleA;
+use windows::Win32::UI::HiDpi::{SetProcessDpiAwarenessContext,
+                                DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2};
 use windows::Win32::UI::WindowsAndMessaging::*;
 
+use rectangular::cli::{self, RunOptions, Startup};
+use rectangular::command::{self, Command, ForegroundTarget};
+use rectangular::{paths, schema};
 use rectangular::rectangular_window::RectangularWindow;
+use rectangular::settings::{self, Settings};
 use rectangular::WINDOW_CLASS_NAME;
 use rectangular::wndproc::wndproc;
 
 /// The entry point of Rectangular.
 ///
-/// The main method contains the basic setup of the application, which includes creation of the
-/// window class, and entering the message loop.
-fn main() -> Result<()> {
+/// The command line decides what happens. Without arguments, the application runs as usual, other
+/// commands do their job and exit. Since Rectangular is a GUI application, it has no console of
+/// its own, so commands printing anything attach to the console of the process which started them.
+///
+/// Rectangular is aware of the DPI of every monitor, so windows are moved in physical pixels, and
+/// its own windows are drawn sharp at any scaling rather than stretched by the system.
+fn main() {
+    unsafe {
+        SetProcessDpiAwarenessContext(DPI_AWARENESS_CONTEXT_PER_MONITOR_AWARE_V2);
+    }
+
+    let startup = match cli::parse(env::args_os().skip(1)) {
+        Ok(startup) => startup,
+        Err(error) => {
+            attach_console();
+            eprintln!("rectangular: {error}\n\n{}", cli::USAGE);
+            process::exit(cli::EXIT_USAGE);
+        },
+    };
+
+    match startup {
+        Startup::Run(options) => run(&options),
+        Startup::PrintHelp => {
+            attach_console();
+            println!("{}", cli::USAGE);
+        },
+        Startup::PrintDefaultConfig => {
+            attach_console();
+            print!("{}", settings::DEFAULT_SETTINGS);
+        },
+        Startup::PrintSchema => {
+            attach_console();
+            print!("{}", schema::settings_schema_text());
+        },
+        Startup::CheckConfig(path) => {
+            attach_console();
+            exit_with(cli::check_config(&path));
+        },
+        Startup::UpgradeConfig(path) => {
+            attach_console();
+            exit_with(cli::upgrade_config(&path));
+        },
+        Startup::ApplyAction { action, config } => {
+            apply_to_foreground(&Command::Apply(action), config);
+        },
+        Startup::ApplyLayout { group, action, config } => {
+            apply_to_foreground(&Command::ApplyLayout { group, action }, config);
+        },
+        Startup::Import { source, path } => {
+            attach_console();
+            exit_with(cli::import(source, &path));
+        },
+    }
+}
+
+/// Carry out the command on the foreground window, using settings from the given file, or the
+/// default one, and exit with a failure if it can't be done.
+fn apply_to_foreground(command: &Command, config: Option<PathBuf>) {
+    let settings = match paths::current(config).map(|paths| paths.settings) {
+        Some(path) => match settings::load(&path) {
+            Ok(loaded) => loaded.settings,
+            Err(error) => {
+                attach_console();
+                for diagnostic in &error.0 {
+                    eprintln!("{}", cli::diagnostic_line(&path, diagnostic));
+                }
+                process::exit(cli::EXIT_FAILURE);
+            },
+        },
+        None => Settings::default(),
+    };
+
+    if let Err(error) = command::dispatch(command, &settings, &ForegroundTarget) {
+        attach_console();
+        eprintln!("rectangular: {error}");
+        process::exit(cli::EXIT_FAILURE);
+    }
+}
+
+/// Run the application: create the window class and the main window, and enter the message loop.
+fn run(options: &RunOptions) {
     unsafe {
         let app_instance = GetModuleHandleA(None)
             .expect("A valid application handle should be returned from GetModuleHandleA");
@@ -32,7 +120,7 @@ fn main() -> Result<()> {
 
         RegisterClassA(&wc);
 
-        let _ = RectangularWindow::new(app_instance);
+        let _ = RectangularWindow::new(app_instance, options);
 
         let mut message = MSG::default();
         while GetMessageA(&mut message, HWND(0), 0, 0).into() {
@@ -42,6 +130,24 @@ fn main() -> Result<()> {
 
         UnregisterClassA(WINDOW_CLASS_NAME, app_instance);
     }
+}
+
+/// Attach to the console of the parent process, so printed text shows up there.
+///
+/// Output redirected to a file or a pipe works without a console, so failing to attach is fine.
+fn attach_console() {
+    unsafe {
+        AttachConsole(ATTACH_PARENT_PROCESS);
+    }
+}
 
-    Ok(())
-}
\ No newline at end of file
+/// Print the outcome of a command, and exit with the matching code.
+fn exit_with(outcome: Result<String, String>) {
+    match outcome {
+        Ok(text) => println!("{text}"),
+        Err(text) => {
+            eprintln!("{text}");
+            process::exit(cli::EXIT_FAILURE);
+        },
+    }
+}
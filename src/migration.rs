@@ -0,0 +1,197 @@
+//! This module upgrades settings files written by older versions of Rectangular.
+//!
+//! Every settings file carries a `version` key. Files written before the key was introduced don't
+//! have it, and are treated as version 0. Whenever the format changes in an incompatible way, the
+//! [`CURRENT_VERSION`] is bumped, and a step converting the previous version to the new one is
+//! appended to [`MIGRATIONS`]. An old file is upgraded by running all steps from its version up to
+//! the current one, so every step only has to know about its own change.
+//!
+//! A step which only bumps the version needs nothing else, an old file keeps working as it is, and
+//! loading leaves it alone. When a step actually changes the settings, [`upgrade_changed_file`]
+//! writes the upgraded file back whenever it's loaded, after the original has been copied to
+//! `settings.toml.bak`. Such file keeps the order of keys, but not the comments, which is why the
+//! backup is made. A file whose upgraded settings are invalid is left alone, and is only upgraded
+//! in memory. [`upgrade_file`], run with `--upgrade-config`, upgrades any old file, and one whose
+//! settings don't change only gets its version set, with everything else kept as it is.
+//!
+//! A file with a version newer than the current one was written by a newer Rectangular. Such file
+//! is rejected, since its meaning is unknown, and reading it anyway would silently drop whatever
+//! the newer version added.
+
+use std::ffi::OsString;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+use toml::de::{DeTable, DeValue};
+use toml::{Table, Value};
+
+use crate::settings;
+
+/// The version of the settings format written by this version of Rectangular.
+pub const CURRENT_VERSION: u32 = 2;
+
+/// A name of the key holding the version of the settings file.
+pub const VERSION_KEY: &str = "version";
+
+/// A step upgrading the settings from the version equal to its index to the next one.
+type Migration = fn(&mut Table);
+
+/// All migration steps, the step at index `n` upgrades version `n` to `n + 1`.
+const MIGRATIONS: [Migration; CURRENT_VERSION as usize] = [
+    // Version 0 is the format from before the `version` key was introduced, its content is the
+    // same as of version 1.
+    |_| {},
+    move_displays_to_cycling,
+];
+
+/// Version 2 moved the settings of moving windows between displays, which only the `displays`
+/// cycling mode uses, from the `[displays]` table to `[cycling]`, with `order` renamed to
+/// `display-order`.
+fn move_displays_to_cycling(table: &mut Table) {
+    // Values of other types are invalid either way, and are left for the settings to report.
+    let movable = matches!(table.get("displays"), Some(Value::Table(_)))
+        && matches!(table.get("cycling"), None | Some(Value::Table(_)));
+    if !movable {
+        return;
+    }
+
+    let displays = table.remove("displays");
+    let cycling = table.entry("cycling").or_insert_with(|| Value::Table(Table::new()));
+    if let (Some(Value::Table(displays)), Value::Table(cycling)) = (displays, cycling) {
+        for (key, value) in displays {
+            let key = match key.as_str() {
+                "order" => "display-order".to_string(),
+                _ => key,
+            };
+            cycling.entry(key).or_insert(value);
+        }
+    }
+}
+
+/// Check the version read from a settings file.
+///
+/// A missing version means the file predates versioning, and is treated as version 0.
+pub fn check_version(version: Option<i64>) -> Result<u32, String> {
+    match version {
+        None => Ok(0),
+        Some(version) if version < 0 => Err("the version must not be negative".to_string()),
+        Some(version) if version > CURRENT_VERSION as i64 => Err(format!(
+            "the settings file has version {version}, but this version of Rectangular supports \
+             versions up to {CURRENT_VERSION}, please update Rectangular")),
+        Some(version) => Ok(version as u32),
+    }
+}
+
+/// Content of a settings file upgraded to the current version.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Migrated {
+    /// The version the content has been upgraded from.
+    pub version: u32,
+    pub text: String,
+    /// Whether any step changed the settings, rather than only the version.
+    ///
+    /// If not, the original content can be parsed instead of the upgraded one, which keeps
+    /// locations of diagnostics in line with the file the user sees.
+    pub settings_changed: bool,
+}
+
+/// Upgrade the content of a settings file to the current version.
+///
+/// Returns `None` if the content is already up to date. Errors are returned for content which
+/// isn't valid TOML, or has an unsupported version.
+pub fn migrate(text: &str) -> Result<Option<Migrated>, String> {
+    let mut table: Table = text.parse().map_err(|error: toml::de::Error| {
+        error.message().trim_end().to_string()
+    })?;
+
+    let version = match table.remove(VERSION_KEY) {
+        None => None,
+        Some(Value::Integer(version)) => Some(version),
+        Some(_) => return Err("the version must be a number".to_string()),
+    };
+
+    let version = check_version(version)?;
+    if version == CURRENT_VERSION {
+        return Ok(None);
+    }
+
+    let original = table.clone();
+    for migration in &MIGRATIONS[version as usize..] {
+        migration(&mut table);
+    }
+
+    if table == original {
+        let text = with_current_version(text)
+            .ok_or_else(|| "the version can't be updated".to_string())?;
+        return Ok(Some(Migrated { version, text, settings_changed: false }));
+    }
+
+    // Keep the version at the top of the file.
+    let mut upgraded = Table::new();
+    upgraded.insert(VERSION_KEY.to_string(), Value::Integer(CURRENT_VERSION as i64));
+    upgraded.extend(table);
+
+    let text = toml::to_string(&upgraded).map_err(|error| error.to_string())?;
+    Ok(Some(Migrated { version, text, settings_changed: true }))
+}
+
+/// Set the version in the content of a settings file to the current one, keeping everything else,
+/// including comments. A missing version is added at the top.
+fn with_current_version(text: &str) -> Option<String> {
+    let root = DeTable::parse(text).ok()?;
+    let version = root.get_ref().iter()
+        .find(|(key, _)| key.get_ref().as_ref() == VERSION_KEY)
+        .map(|(_, value)| value);
+
+    match version {
+        Some(version) if matches!(version.get_ref(), DeValue::Integer(_)) => {
+            let span = version.span();
+            Some(format!("{}{CURRENT_VERSION}{}", &text[..span.start], &text[span.end..]))
+        },
+        Some(_) => None,
+        None => Some(format!("{VERSION_KEY} = {CURRENT_VERSION}\n\n{text}")),
+    }
+}
+
+/// Get the path of the backup made before the settings file is upgraded.
+pub fn backup_path(path: &Path) -> PathBuf {
+    let mut backup = OsString::from(path.as_os_str());
+    backup.push(".bak");
+    PathBuf::from(backup)
+}
+
+/// Upgrade the settings file in place, keeping a backup of the original.
+///
+/// Returns `true` if the file has been upgraded, and `false` if it's already up to date. The
+/// upgraded content is checked before anything is written, so a file with errors is left as it is,
+/// and the errors are returned.
+pub fn upgrade_file(path: &Path) -> io::Result<bool> {
+    upgrade(path, false).map(|migrated| migrated.is_some())
+}
+
+/// Upgrade the settings file in place when it's loaded, like [`upgrade_file`], but only if the
+/// migration changes its settings.
+///
+/// Returns the version the file has been upgraded from, or `None` if it has been left alone.
+pub fn upgrade_changed_file(path: &Path) -> io::Result<Option<u32>> {
+    upgrade(path, true).map(|migrated| migrated.map(|migrated| migrated.version))
+}
+
+fn upgrade(path: &Path, only_changed: bool) -> io::Result<Option<Migrated>> {
+    let text = fs::read_to_string(path)?;
+    let invalid = |message: String| io::Error::new(io::ErrorKind::InvalidData, message);
+
+    let Some(migrated) = migrate(&text).map_err(invalid)? else {
+        return Ok(None);
+    };
+    if only_changed && !migrated.settings_changed {
+        return Ok(None);
+    }
+    settings::parse(&migrated.text).map_err(|error| invalid(error.to_string()))?;
+
+    fs::write(backup_path(path), &text)?;
+    fs::write(path, &migrated.text)?;
+
+    Ok(Some(migrated))
+}
@@ -15,16 +15,25 @@
 //! When user clicks on the notification icon with the secondary mouse button, a context menu
 //! appears. The context menu includes entries related strictly to Rectangular itself, like:
 //! settings, help or possibility to exit the application.
+//!
+//! # Pausing
+//!
+//! While Rectangular is paused, the icon is grayed out, and the tooltip says so. The gray icon is
+//! made from the regular one when the application starts, see [`gray_out`].
 
+use std::ffi::c_void;
 use std::mem;
 
 use windows::core::{
     GUID, PCSTR,
 };
-use windows::s;
 use windows::Win32::{
     Foundation::*, UI::Shell::*, UI::WindowsAndMessaging::*,
 };
+use windows::Win32::Graphics::Gdi::{
+    BI_RGB, BITMAP, BITMAPINFO, BITMAPINFOHEADER, DeleteObject, DIB_RGB_COLORS, GetDC, GetDIBits,
+    GetObjectA, ReleaseDC, SetDIBits,
+};
 
 use crate::WM_NIACTION;
 
@@ -32,7 +41,10 @@ use crate::WM_NIACTION;
 const ICON_UID: u32 = 1;
 
 /// A text that will appear in the notification icon's tooltip.
-const ICON_TITLE: PCSTR = s!("Rectangular");
+const ICON_TITLE: &str = "Rectangular";
+
+/// A text of the tooltip while Rectangular is paused.
+const PAUSED_TITLE: &str = "Rectangular (paused)";
 
 /// An ID of a resource with the icon.
 const ICON_RESOURCE_ID: PCSTR = PCSTR(1 as *const u8);
@@ -44,6 +56,9 @@ const ICON_RESOURCE_ID: PCSTR = PCSTR(1 as *const u8);
 pub struct NotificationIcon {
     window_handle: HWND,
     icon_handle: HICON,
+    /// The grayed out icon, shown while Rectangular is paused. It's the regular icon if it
+    /// couldn't be made.
+    paused_icon_handle: HICON,
 }
 
 impl Default for NotificationIcon {
@@ -51,6 +66,7 @@ impl Default for NotificationIcon {
         NotificationIcon {
             window_handle: Default::default(),
             icon_handle: Default::default(),
+            paused_icon_handle: Default::default(),
         }
     }
 }
@@ -60,53 +76,191 @@ impl NotificationIcon {
         unsafe {
             let image_handle = LoadImageA(module_instance, ICON_RESOURCE_ID, IMAGE_ICON,
                                           16, 16, LR_DEFAULTCOLOR).expect("Could not load icon.");
-            NotificationIcon { window_handle, icon_handle: HICON(image_handle.0) }
+            let icon_handle = HICON(image_handle.0);
+            let paused_icon_handle = grayed_icon(icon_handle).unwrap_or(icon_handle);
+
+            NotificationIcon { window_handle, icon_handle, paused_icon_handle }
         }
     }
 
     /// Initialize and add an icon to the notification area.
     pub fn add_to_window(&self) {
-        let mut icon_data = NOTIFYICONDATAA {
-            cbSize: mem::size_of::<NOTIFYICONDATAA>() as u32,
+        let mut icon_data = NOTIFYICONDATAW {
+            cbSize: mem::size_of::<NOTIFYICONDATAW>() as u32,
             hWnd: self.window_handle,
             uID: ICON_UID,
             uFlags: NIF_MESSAGE | NIF_INFO | NIF_ICON | NIF_TIP | NIF_STATE | NIF_SHOWTIP,
             uCallbackMessage: WM_NIACTION,
             hIcon: self.icon_handle,
-            szTip: [CHAR(0); 128],
+            szTip: [0; 128],
             dwState: NOTIFY_ICON_STATE(0),
             dwStateMask: 0,
-            szInfo: [CHAR(0); 256],
-            Anonymous: NOTIFYICONDATAA_0 { uVersion: NOTIFYICON_VERSION_4 },
-            szInfoTitle: [CHAR(0); 64],
+            szInfo: [0; 256],
+            Anonymous: NOTIFYICONDATAW_0 { uVersion: NOTIFYICON_VERSION_4 },
+            szInfoTitle: [0; 64],
             dwInfoFlags: NIIF_NONE,
             guidItem: GUID::zeroed(),
             hBalloonIcon: Default::default(),
         };
 
-        unsafe {
-            for (i, ch) in ICON_TITLE.as_bytes().iter().enumerate() {
-                icon_data.szTip[i] = CHAR(*ch);
-            }
+        copy_to_wide(&mut icon_data.szTip, ICON_TITLE);
 
-            Shell_NotifyIconA(NIM_ADD, &icon_data)
+        unsafe {
+            Shell_NotifyIconW(NIM_ADD, &icon_data)
                 .expect("Could not add icon to notification area.");
-            Shell_NotifyIconA(NIM_SETVERSION, &icon_data)
+            Shell_NotifyIconW(NIM_SETVERSION, &icon_data)
                 .expect("Could not set version of notification icon.");
         }
     }
+
+    /// Swap the icon and the tooltip for the ones telling whether Rectangular is paused.
+    pub fn set_paused(&self, paused: bool) {
+        let mut icon_data = NOTIFYICONDATAW {
+            cbSize: mem::size_of::<NOTIFYICONDATAW>() as u32,
+            hWnd: self.window_handle,
+            uID: ICON_UID,
+            uFlags: NIF_ICON | NIF_TIP | NIF_SHOWTIP,
+            ..Default::default()
+        };
+
+        let (icon, title) = match paused {
+            true => (self.paused_icon_handle, PAUSED_TITLE),
+            false => (self.icon_handle, ICON_TITLE),
+        };
+        icon_data.hIcon = icon;
+        copy_to_wide(&mut icon_data.szTip, title);
+
+        unsafe {
+            Shell_NotifyIconW(NIM_MODIFY, &icon_data);
+        }
+    }
+
+    /// Show a balloon notification next to the icon.
+    ///
+    /// Both the title and the text are truncated if they don't fit into the notification
+    /// structure. Showing a new balloon replaces the previous one.
+    pub fn show_info(&self, title: &str, text: &str) {
+        let mut icon_data = NOTIFYICONDATAW {
+            cbSize: mem::size_of::<NOTIFYICONDATAW>() as u32,
+            hWnd: self.window_handle,
+            uID: ICON_UID,
+            uFlags: NIF_INFO,
+            dwInfoFlags: NOTIFY_ICON_INFOTIP_FLAGS(NIIF_INFO.0 | NIIF_NOSOUND.0),
+            ..Default::default()
+        };
+
+        copy_to_wide(&mut icon_data.szInfoTitle, title);
+        copy_to_wide(&mut icon_data.szInfo, text);
+
+        unsafe {
+            Shell_NotifyIconW(NIM_MODIFY, &icon_data);
+        }
+    }
+
+    /// Hide the balloon notification shown with [`NotificationIcon::show_info`].
+    pub fn hide_info(&self) {
+        let icon_data = NOTIFYICONDATAW {
+            cbSize: mem::size_of::<NOTIFYICONDATAW>() as u32,
+            hWnd: self.window_handle,
+            uID: ICON_UID,
+            uFlags: NIF_INFO,
+            ..Default::default()
+        };
+
+        unsafe {
+            Shell_NotifyIconW(NIM_MODIFY, &icon_data);
+        }
+    }
+}
+
+/// Copy a string into a fixed-size, null-terminated array of UTF-16 code units.
+///
+/// The string is truncated if it's too long, the last element of the array is always left for the
+/// null terminator. A character which doesn't fit as a whole, e.g. an emoji taking two units, is
+/// left out with the rest of the string rather than cut in half.
+pub fn copy_to_wide<const N: usize>(destination: &mut [u16; N], source: &str) {
+    let mut length = 0;
+    for ch in source.chars() {
+        if length + ch.len_utf16() > N - 1 {
+            break;
+        }
+        length += ch.encode_utf16(&mut destination[length..]).len();
+    }
+    destination[length..].fill(0);
+}
+
+/// Turn pixels of a 32-bit bitmap, in the blue, green, red and unused byte order, into shades
+/// of gray, a bit lighter than the original, like disabled icons look.
+///
+/// The fourth byte of each pixel is kept as it is, as it may be the alpha channel.
+pub fn gray_out(pixels: &mut [u8]) {
+    for pixel in pixels.chunks_exact_mut(4) {
+        let luminance = (pixel[0] as u32 * 29 + pixel[1] as u32 * 150 + pixel[2] as u32 * 77) >> 8;
+        let gray = (luminance + 255) / 2;
+        pixel[..3].fill(gray as u8);
+    }
+}
+
+/// Make a grayed out copy of the icon, see [`gray_out`].
+fn grayed_icon(icon: HICON) -> Option<HICON> {
+    unsafe {
+        let mut info = ICONINFO::default();
+        if !GetIconInfo(icon, &mut info).as_bool() {
+            return None;
+        }
+
+        let mut bitmap = BITMAP::default();
+        GetObjectA(info.hbmColor, mem::size_of::<BITMAP>() as i32,
+                   Some(&mut bitmap as *mut BITMAP as *mut c_void));
+
+        let mut header = BITMAPINFO {
+            bmiHeader: BITMAPINFOHEADER {
+                biSize: mem::size_of::<BITMAPINFOHEADER>() as u32,
+                biWidth: bitmap.bmWidth,
+                // A negative height puts the top row first.
+                biHeight: -bitmap.bmHeight,
+                biPlanes: 1,
+                biBitCount: 32,
+                biCompression: BI_RGB,
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        let rows = bitmap.bmHeight.max(0) as u32;
+        let mut pixels = vec![0u8; (bitmap.bmWidth.max(0) as usize) * rows as usize * 4];
+
+        let hdc = GetDC(None);
+        let read = GetDIBits(hdc, info.hbmColor, 0, rows,
+                             Some(pixels.as_mut_ptr() as *mut c_void), &mut header,
+                             DIB_RGB_COLORS);
+        if read > 0 {
+            gray_out(&mut pixels);
+            SetDIBits(hdc, info.hbmColor, 0, rows, pixels.as_ptr() as *const c_void, &header,
+                      DIB_RGB_COLORS);
+        }
+        ReleaseDC(None, hdc);
+
+        let grayed = (read > 0).then(|| CreateIconIndirect(&info).ok()).flatten();
+        DeleteObject(info.hbmColor);
+        DeleteObject(info.hbmMask);
+
+        grayed
+    }
 }
 
 impl Drop for NotificationIcon {
     fn drop(&mut self) {
-        let icon_data = NOTIFYICONDATAA {
+        let icon_data = NOTIFYICONDATAW {
             hWnd: self.window_handle,
             uID: ICON_UID,
             ..Default::default()
         };
 
         unsafe {
-            Shell_NotifyIconA(NIM_DELETE, &icon_data);
+            Shell_NotifyIconW(NIM_DELETE, &icon_data);
+            if self.paused_icon_handle != self.icon_handle {
+                DestroyIcon(self.paused_icon_handle);
+            }
             DestroyIcon(self.icon_handle);
         }
     }
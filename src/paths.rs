@@ -0,0 +1,121 @@
+//! This module decides where Rectangular keeps its files.
+//!
+//! Everything Rectangular writes lives in a single data directory: the settings file, the backups
+//! made when it's upgraded, and any other file it may keep in the future, like logs or history.
+//! The directory is chosen by [`resolve`], which checks the following places, and takes the first
+//! one that applies:
+//!
+//! 1. The settings file given on the command line with `--config`, which is used as it is. The
+//!    data directory is the one containing the file.
+//! 2. The directory of the executable, if there's a `rectangular.portable` marker or a
+//!    `settings.toml` next to it. This is the portable mode, for running Rectangular from a shared
+//!    or removable folder without leaving anything in the user profile.
+//! 3. The directory given in the `RECTANGULAR_HOME` environment variable.
+//! 4. `%APPDATA%\Rectangular`.
+//!
+//! The resolution only looks at an [`Environment`] and asks whether files exist, so it can be
+//! tested without touching the real environment.
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+use crate::settings::SETTINGS_FILE_NAME;
+
+/// A name of the file which turns on the portable mode, when put next to the executable.
+pub const PORTABLE_MARKER: &str = "rectangular.portable";
+
+/// A name of the environment variable overriding the data directory.
+pub const HOME_VARIABLE: &str = "RECTANGULAR_HOME";
+
+/// A name of the data directory inside `%APPDATA%`.
+pub const APP_DATA_DIRECTORY: &str = "Rectangular";
+
+/// Where the data directory comes from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PathSource {
+    CommandLine,
+    Portable,
+    Environment,
+    AppData,
+}
+
+/// Locations of Rectangular's files.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct DataPaths {
+    pub source: PathSource,
+    /// The directory where all files are kept.
+    pub directory: PathBuf,
+    pub settings: PathBuf,
+}
+
+impl DataPaths {
+    fn in_directory(source: PathSource, directory: PathBuf) -> DataPaths {
+        let settings = directory.join(SETTINGS_FILE_NAME);
+        DataPaths { source, directory, settings }
+    }
+}
+
+/// Everything the data directory depends on.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Environment {
+    /// The settings file given on the command line.
+    pub config: Option<PathBuf>,
+    /// The directory containing the executable.
+    pub executable_directory: Option<PathBuf>,
+    /// The value of [`HOME_VARIABLE`].
+    pub home: Option<PathBuf>,
+    /// The value of `%APPDATA%`.
+    pub app_data: Option<PathBuf>,
+}
+
+impl Environment {
+    /// Read the environment of the running process.
+    pub fn current(config: Option<PathBuf>) -> Environment {
+        // An empty variable is as good as no variable.
+        let variable = |name: &str| env::var_os(name).filter(|value| !value.is_empty())
+            .map(PathBuf::from);
+
+        Environment {
+            config,
+            executable_directory: env::current_exe().ok()
+                .and_then(|path| path.parent().map(Path::to_path_buf)),
+            home: variable(HOME_VARIABLE),
+            app_data: variable("APPDATA"),
+        }
+    }
+}
+
+/// Choose the data directory, see the [module documentation](self) for the order of precedence.
+///
+/// `exists` tells whether a file exists. Returns `None` if none of the places applies, which only
+/// happens when even `%APPDATA%` is missing.
+pub fn resolve(environment: &Environment, exists: impl Fn(&Path) -> bool) -> Option<DataPaths> {
+    if let Some(config) = &environment.config {
+        let directory = match config.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+
+        return Some(DataPaths { source: PathSource::CommandLine, directory,
+                                settings: config.clone() });
+    }
+
+    if let Some(directory) = &environment.executable_directory {
+        if exists(&directory.join(PORTABLE_MARKER)) || exists(&directory.join(SETTINGS_FILE_NAME)) {
+            return Some(DataPaths::in_directory(PathSource::Portable, directory.clone()));
+        }
+    }
+
+    if let Some(home) = &environment.home {
+        return Some(DataPaths::in_directory(PathSource::Environment, home.clone()));
+    }
+
+    environment.app_data.as_ref().map(|app_data| {
+        DataPaths::in_directory(PathSource::AppData, app_data.join(APP_DATA_DIRECTORY))
+    })
+}
+
+/// Choose the data directory of the running process.
+pub fn current(config: Option<PathBuf>) -> Option<DataPaths> {
+    resolve(&Environment::current(config), Path::exists)
+}
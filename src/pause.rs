@@ -0,0 +1,92 @@
+//! This module decides when Rectangular is paused, and what still works then.
+//!
+//! Pausing is meant for presentations and games, where shortcuts of Rectangular would get in the
+//! way. While paused, every shortcut is released, so it reaches the foreground application, except
+//! the one of pausing itself, which resumes Rectangular. The settings can make Rectangular resume
+//! by itself after a while, see [`PauseSettings`].
+//!
+//! [`PauseState`] is a small state machine, which is given the current time instead of reading
+//! the clock, and [`active_shortcuts`] lists shortcuts to register in either state. The main window
+//! carries out the result: it registers the shortcuts, runs the timer, and swaps the notification
+//! icon.
+
+use std::time::{Duration, Instant};
+
+use crate::action::WindowAction;
+use crate::hotkey::Hotkey;
+use crate::settings::{PauseSettings, Settings};
+
+/// Whether Rectangular is paused, and until when.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PauseState {
+    paused: bool,
+    /// When Rectangular resumes by itself, `None` if it stays paused until it's resumed.
+    resume_at: Option<Instant>,
+}
+
+impl PauseState {
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    /// Get the time when Rectangular resumes by itself, if it's paused and should.
+    pub fn resume_at(&self) -> Option<Instant> {
+        self.resume_at
+    }
+
+    /// Pause if running, resume if paused. Returns whether Rectangular is paused now.
+    pub fn toggle(&mut self, now: Instant, settings: &PauseSettings) -> bool {
+        if self.paused {
+            *self = PauseState::default();
+        } else {
+            self.paused = true;
+            self.resume_at = settings.resume_after.map(|duration| now + duration);
+        }
+
+        self.paused
+    }
+
+    /// Resume if the time is up. Returns `true` if Rectangular has just resumed.
+    pub fn tick(&mut self, now: Instant) -> bool {
+        match self.resume_at {
+            Some(resume_at) if now >= resume_at => {
+                *self = PauseState::default();
+                true
+            },
+            _ => false,
+        }
+    }
+
+    /// Get the time left until Rectangular resumes by itself.
+    pub fn remaining(&self, now: Instant) -> Option<Duration> {
+        self.resume_at.map(|resume_at| resume_at.saturating_duration_since(now))
+    }
+}
+
+/// A shortcut registered by the main window.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Shortcut {
+    /// The leader key, see [`chord`](crate::chord).
+    Leader,
+    /// The shortcut pausing and resuming Rectangular.
+    Pause,
+    Action(WindowAction),
+}
+
+/// Get shortcuts which should be registered, with the keys bound to them.
+///
+/// While paused, only the shortcut of pausing is left.
+pub fn active_shortcuts(settings: &Settings, paused: bool) -> Vec<(Shortcut, Hotkey)> {
+    let mut shortcuts: Vec<(Shortcut, Hotkey)> = settings.pause.hotkey.iter()
+        .map(|hotkey| (Shortcut::Pause, *hotkey))
+        .collect();
+
+    if !paused {
+        shortcuts.push((Shortcut::Leader, settings.leader.hotkey));
+        shortcuts.extend(WindowAction::ALL.iter().filter_map(|action| {
+            settings.hotkeys.get(*action).map(|hotkey| (Shortcut::Action(*action), *hotkey))
+        }));
+    }
+
+    shortcuts
+}
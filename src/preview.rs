@@ -0,0 +1,79 @@
+//! This module decides where the placement preview is shown.
+//!
+//! While an action is hovered in the Control Center, a translucent overlay shows where the window
+//! would land, on the monitor it would land on. The region comes from
+//! [`engine::placement`], so it includes gaps, and follows repeated actions just like applying
+//! the action does.
+//!
+//! [`PreviewState`] turns changes of the hovered action into [`OverlayUpdate`]s. The overlay is
+//! only touched when the region really changes, and moving between buttons moves the overlay
+//! instead of hiding and showing it again, so it doesn't flicker.
+
+use windows::Win32::Foundation::HWND;
+
+use crate::action::WindowAction;
+use crate::engine;
+use crate::geometry::Rect;
+use crate::settings::Settings;
+use crate::window_system::WindowSystem;
+
+/// What should be done with the overlay.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverlayUpdate {
+    /// The overlay stays as it is.
+    Unchanged,
+    /// The overlay should be shown over the rectangle, or moved there if it's already shown.
+    Show(Rect),
+    Hide,
+}
+
+/// The action being previewed, and the region the overlay covers.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PreviewState {
+    hovered: Option<WindowAction>,
+    shown: Option<Rect>,
+}
+
+impl PreviewState {
+    /// React to a change of the hovered action, `None` when nothing is hovered anymore.
+    ///
+    /// `target` is the window the action would be applied to.
+    pub fn hover(&mut self, hovered: Option<WindowAction>, target: Option<HWND>,
+                 system: &impl WindowSystem, settings: &Settings) -> OverlayUpdate {
+        self.hovered = hovered;
+        self.refresh(target, system, settings)
+    }
+
+    /// Compute the region again, e.g. after the target window has been moved.
+    pub fn refresh(&mut self, target: Option<HWND>, system: &impl WindowSystem,
+                   settings: &Settings) -> OverlayUpdate {
+        let region = self.hovered.zip(target)
+            .and_then(|(action, window)| engine::placement(window, action, settings, system))
+            .map(|placement| placement.visible);
+
+        self.show(region)
+    }
+
+    /// Hide the overlay, e.g. when the Control Center is hidden.
+    pub fn hide(&mut self) -> OverlayUpdate {
+        self.hovered = None;
+        self.show(None)
+    }
+
+    /// The region the overlay covers, `None` if it's hidden.
+    pub fn shown(&self) -> Option<Rect> {
+        self.shown
+    }
+
+    fn show(&mut self, region: Option<Rect>) -> OverlayUpdate {
+        if region == self.shown {
+            return OverlayUpdate::Unchanged;
+        }
+
+        self.shown = region;
+        match region {
+            Some(rect) => OverlayUpdate::Show(rect),
+            None => OverlayUpdate::Hide,
+        }
+    }
+}
@@ -0,0 +1,127 @@
+//! This module contains the window of the placement preview.
+//!
+//! The overlay is a translucent, topmost window covering the region a window would be moved to,
+//! see [`preview`](crate::preview) for when and where it's shown. It never takes the focus and lets
+//! mouse clicks through, so it doesn't get in the way of the Control Center, or anything else.
+
+use std::cell::{Cell, RefCell};
+use std::ffi::c_void;
+use std::rc::Rc;
+
+use windows::s;
+use windows::Win32::Foundation::{COLORREF, HINSTANCE, HWND, LPARAM, LRESULT, RECT, WPARAM};
+use windows::Win32::Graphics::Gdi::{HDC, RDW_INVALIDATE, RDW_UPDATENOW, RedrawWindow};
+use windows::Win32::UI::WindowsAndMessaging::*;
+
+use crate::app_window::AppWindow;
+use crate::control_center_view;
+use crate::gdi_painter;
+use crate::geometry::Rect;
+use crate::preview::OverlayUpdate;
+use crate::theme::Theme;
+use crate::{make_window_object, WINDOW_CLASS_NAME};
+
+/// The opacity of the overlay, from 0 to 255.
+const OPACITY: u8 = 112;
+
+/// Styles of the overlay: a layered window which is transparent for the mouse, and never shows up
+/// on the taskbar or takes the focus.
+const STYLE: WINDOW_STYLE = WS_POPUP;
+const EX_STYLE: WINDOW_EX_STYLE = WINDOW_EX_STYLE(WS_EX_LAYERED.0 | WS_EX_TRANSPARENT.0
+    | WS_EX_TOOLWINDOW.0 | WS_EX_NOACTIVATE.0 | WS_EX_TOPMOST.0);
+
+#[derive(Default)]
+pub struct PreviewOverlay {
+    window_handle: HWND,
+    /// The theme of the Control Center, given with every update.
+    theme: Cell<Theme>,
+}
+
+impl AppWindow for PreviewOverlay {
+    fn handle_create(&mut self, _app_instance: HINSTANCE, window: HWND, _message: u32,
+                     _wparam: WPARAM, _lparam: LPARAM) -> LRESULT {
+        self.window_handle = window;
+
+        unsafe {
+            SetLayeredWindowAttributes(window, COLORREF(0), OPACITY, LWA_ALPHA);
+        }
+
+        LRESULT(0)
+    }
+
+    fn process_message(&self, _window: HWND, message: u32, wparam: WPARAM,
+                       lparam: LPARAM) -> LRESULT {
+        match message {
+            // The whole window is painted in `WM_PAINT`, so erasing it first would only flicker.
+            WM_ERASEBKGND => LRESULT(1),
+            WM_MOUSEACTIVATE => LRESULT(MA_NOACTIVATE as isize),
+            _ => unsafe { DefWindowProcA(self.window_handle, message, wparam, lparam) },
+        }
+    }
+
+    fn paint(&self, hdc: HDC, _area: &RECT) -> bool {
+        let mut client = RECT::default();
+        unsafe { GetClientRect(self.window_handle, &mut client); }
+
+        let dpi = gdi_painter::window_dpi(self.window_handle);
+        let theme = self.theme.get();
+        let list = control_center_view::render_preview(&client.into(), dpi, &theme);
+        gdi_painter::paint(hdc, &client.into(), &list, &theme);
+
+        true
+    }
+}
+
+impl PreviewOverlay {
+    /// Create the overlay window, which is initially hidden.
+    pub fn new(instance: HINSTANCE) -> Rc<RefCell<PreviewOverlay>> {
+        let (ptr, raw) = make_window_object!(PreviewOverlay);
+
+        unsafe {
+            CreateWindowExA(
+                EX_STYLE,
+                WINDOW_CLASS_NAME,
+                s!(""),
+                STYLE,
+                0, 0, 0, 0,
+                HWND::default(),
+                None,
+                instance,
+                Some(raw),
+            );
+        }
+
+        ptr
+    }
+
+    /// Carry out an update decided by [`PreviewState`](crate::preview::PreviewState).
+    ///
+    /// The overlay is placed right below `above`, which is the Control Center, so the overlay
+    /// never covers it, and it's drawn with the same theme.
+    pub fn update(&self, update: OverlayUpdate, above: HWND, theme: Theme) {
+        self.theme.set(theme);
+
+        match update {
+            OverlayUpdate::Unchanged => {},
+            OverlayUpdate::Show(rect) => self.show(&rect, above),
+            OverlayUpdate::Hide => self.hide(),
+        }
+    }
+
+    /// Show the overlay over the rectangle, given in screen coordinates.
+    ///
+    /// An overlay which is already shown is just moved, without hiding it first.
+    fn show(&self, rect: &Rect, above: HWND) {
+        unsafe {
+            SetWindowPos(self.window_handle, above, rect.left, rect.top, rect.width(),
+                         rect.height(), SWP_NOACTIVATE | SWP_SHOWWINDOW);
+            RedrawWindow(self.window_handle, None, None, RDW_INVALIDATE | RDW_UPDATENOW);
+        }
+    }
+
+    fn hide(&self) {
+        unsafe {
+            ShowWindow(self.window_handle, SW_HIDE);
+        }
+    }
+}
@@ -0,0 +1,270 @@
+//! This module imports settings exported from [Rectangle](https://rectangleapp.com), the macOS
+//! application Rectangular is modeled after.
+//!
+//! Rectangle exports its settings as `RectangleConfig.json`, which looks like this:
+//!
+//! ```json
+//! {
+//!   "bundleId": "com.knollsoft.Rectangle",
+//!   "shortcuts": {
+//!     "leftHalf": { "keyCode": 123, "modifierFlags": 786432 }
+//!   },
+//!   "defaults": {
+//!     "gapSize": { "float": 10 },
+//!     "subsequentExecutionMode": { "int": 0 }
+//!   }
+//! }
+//! ```
+//!
+//! Shortcuts consist of a macOS key code, which identifies a physical key, and `NSEvent` modifier
+//! flags. Keys are mapped to the Windows keys with the same label on the US layout. There is no
+//! Command key on Windows, so it's replaced with a modifier of the user's choice, the Windows key
+//! by default. Actions Rectangle has, but Rectangular doesn't, are listed in the report.
+//!
+//! Rectangle keeps only the shortcuts which are bound, so actions missing from the export are
+//! unbound in the imported settings, too.
+
+use std::collections::BTreeMap;
+
+use serde::Deserialize;
+use serde_json::{Map, Value};
+
+use crate::action::WindowAction;
+use crate::hotkey::{Hotkey, KeySpec, Modifiers};
+use crate::import::{self, ImportError, ImportReport};
+use crate::settings::{CyclingMode, Gaps, Hotkeys, Settings};
+
+/// `NSEvent` modifier flags used by Rectangle.
+const FLAG_SHIFT: i64 = 1 << 17;
+const FLAG_CONTROL: i64 = 1 << 18;
+const FLAG_OPTION: i64 = 1 << 19;
+const FLAG_COMMAND: i64 = 1 << 20;
+
+/// Rectangle's names of actions supported by Rectangular.
+const ACTION_NAMES: [(&str, WindowAction); 15] = [
+    ("leftHalf", WindowAction::LeftHalf),
+    ("rightHalf", WindowAction::RightHalf),
+    ("topHalf", WindowAction::TopHalf),
+    ("bottomHalf", WindowAction::BottomHalf),
+    ("topLeft", WindowAction::TopLeft),
+    ("topRight", WindowAction::TopRight),
+    ("bottomLeft", WindowAction::BottomLeft),
+    ("bottomRight", WindowAction::BottomRight),
+    ("firstThird", WindowAction::FirstThird),
+    ("centerThird", WindowAction::CenterThird),
+    ("lastThird", WindowAction::LastThird),
+    ("firstTwoThirds", WindowAction::FirstTwoThirds),
+    ("lastTwoThirds", WindowAction::LastTwoThirds),
+    ("maximize", WindowAction::Maximize),
+    ("center", WindowAction::Center),
+];
+
+/// macOS key codes (`kVK_*`) with virtual-key codes of the keys in the same place.
+const KEY_CODES: [(i64, u16); 73] = [
+    (0, 0x41), (1, 0x53), (2, 0x44), (3, 0x46), (4, 0x48), (5, 0x47), (6, 0x5A), (7, 0x58),
+    (8, 0x43), (9, 0x56), (11, 0x42), (12, 0x51), (13, 0x57), (14, 0x45), (15, 0x52), (16, 0x59),
+    (17, 0x54), (18, 0x31), (19, 0x32), (20, 0x33), (21, 0x34), (22, 0x36), (23, 0x35),
+    (24, 0xBB), (25, 0x39), (26, 0x37), (27, 0xBD), (28, 0x38), (29, 0x30), (30, 0xDD),
+    (31, 0x4F), (32, 0x55), (33, 0xDB), (34, 0x49), (35, 0x50), (36, 0x0D), (37, 0x4C),
+    (38, 0x4A), (39, 0xDE), (40, 0x4B), (41, 0xBA), (42, 0xDC), (43, 0xBC), (44, 0xBF),
+    (45, 0x4E), (46, 0x4D), (47, 0xBE), (48, 0x09), (49, 0x20), (50, 0xC0), (51, 0x08),
+    (53, 0x1B), (96, 0x74), (97, 0x75), (98, 0x76), (99, 0x72), (100, 0x77), (101, 0x78),
+    (103, 0x7A), (109, 0x79), (111, 0x7B), (115, 0x24), (116, 0x21), (117, 0x2E), (118, 0x73),
+    (119, 0x23), (120, 0x71), (121, 0x22), (122, 0x70), (123, 0x25), (124, 0x27),
+    (125, 0x28), (126, 0x26),
+];
+
+/// The part of `RectangleConfig.json` which is imported.
+///
+/// Shortcuts and preferences are kept as raw values, so one which can't be read is reported,
+/// rather than failing the whole import.
+#[derive(Deserialize)]
+struct RectangleConfig {
+    shortcuts: Map<String, Value>,
+    #[serde(default)]
+    defaults: Map<String, Value>,
+}
+
+/// A shortcut of Rectangle.
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Shortcut {
+    key_code: i64,
+    #[serde(default)]
+    modifier_flags: i64,
+}
+
+/// A number preference, which Rectangle wraps in an object naming its type, like
+/// `{ "float": 10 }`.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum Preference {
+    Float { float: f64 },
+    Int { int: f64 },
+    Plain(f64),
+}
+
+impl Preference {
+    fn number(value: &Value) -> Option<f64> {
+        match Preference::deserialize(value).ok()? {
+            Preference::Float { float: number } | Preference::Int { int: number }
+            | Preference::Plain(number) => Some(number),
+        }
+    }
+}
+
+/// Options of the import.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct RectangleImportOptions {
+    /// The modifier replacing the Command key.
+    pub command: Modifiers,
+}
+
+impl Default for RectangleImportOptions {
+    fn default() -> Self {
+        RectangleImportOptions { command: Modifiers::WIN }
+    }
+}
+
+/// Settings imported from Rectangle.
+#[derive(Clone, Debug, PartialEq)]
+pub struct RectangleImport {
+    /// All imported shortcuts. Actions without one should be unbound.
+    pub hotkeys: BTreeMap<WindowAction, Hotkey>,
+    pub gaps: Option<Gaps>,
+    pub cycling: Option<CyclingMode>,
+    pub report: ImportReport,
+}
+
+impl RectangleImport {
+    /// Replace the corresponding parts of the settings with the imported ones.
+    pub fn apply_to(&self, settings: &mut Settings) {
+        settings.hotkeys = Hotkeys(self.hotkeys.clone());
+
+        if let Some(gaps) = self.gaps {
+            settings.gaps = gaps;
+        }
+        if let Some(mode) = self.cycling {
+            settings.cycling.mode = mode;
+        }
+    }
+
+    /// Write the imported settings as a fragment of the settings file.
+    pub fn to_toml(&self) -> String {
+        let mut table = toml::Table::new();
+
+        let mut hotkeys = toml::Table::new();
+        for action in WindowAction::ALL {
+            let hotkey = self.hotkeys.get(&action).map(Hotkey::to_string).unwrap_or_default();
+            hotkeys.insert(action.name().to_string(), toml::Value::String(hotkey));
+        }
+        table.insert("hotkeys".to_string(), toml::Value::Table(hotkeys));
+
+        if let Some(gaps) = self.gaps {
+            let mut section = toml::Table::new();
+            section.insert("inner".to_string(), toml::Value::Integer(gaps.inner as i64));
+            section.insert("outer".to_string(), toml::Value::Integer(gaps.outer as i64));
+            table.insert("gaps".to_string(), toml::Value::Table(section));
+        }
+
+        if let Some(mode) = self.cycling {
+            let name = match mode {
+                CyclingMode::None => "none",
+                CyclingMode::Sizes => "sizes",
+                CyclingMode::Displays => "displays",
+            };
+
+            let mut section = toml::Table::new();
+            section.insert("mode".to_string(), toml::Value::String(name.to_string()));
+            table.insert("cycling".to_string(), toml::Value::Table(section));
+        }
+
+        import::settings_fragment("Rectangle", &self.report, table)
+    }
+}
+
+/// Import the content of `RectangleConfig.json`.
+pub fn import(text: &str, options: &RectangleImportOptions)
+              -> Result<RectangleImport, ImportError> {
+    let config = RectangleConfig::deserialize(import::parse_json(text)?).map_err(|_| {
+        ImportError::Format("the file is not a Rectangle export, it has no shortcuts".to_string())
+    })?;
+
+    let mut report = ImportReport::default();
+    let mut hotkeys = BTreeMap::new();
+    let leader = Settings::default().leader.hotkey;
+
+    for (name, shortcut) in &config.shortcuts {
+        let item = format!("shortcut \"{name}\"");
+        let Some(action) = action_from_name(name) else {
+            report.add(item, "Rectangular has no such action");
+            continue;
+        };
+
+        let hotkey = match shortcut_to_hotkey(shortcut, options) {
+            Ok(hotkey) => hotkey,
+            Err(reason) => {
+                report.add(item, reason);
+                continue;
+            },
+        };
+
+        if let Some((other, _)) = hotkeys.iter().find(|(_, other)| **other == hotkey) {
+            report.add(item, format!("{hotkey} is already used by \"{other}\""));
+        } else if hotkey == leader {
+            report.add(item, format!("{hotkey} is used by the leader key"));
+        } else {
+            hotkeys.insert(action, hotkey);
+        }
+    }
+
+    let mut gaps = None;
+    let mut cycling = None;
+    for (name, value) in &config.defaults {
+        let item = format!("preference \"{name}\"");
+        match name.as_str() {
+            "gapSize" => match Preference::number(value) {
+                Some(size) => {
+                    let size = size.max(0.0).round() as u32;
+                    gaps = Some(Gaps { inner: size, outer: size });
+                },
+                None => report.add(item, "the value is not a number"),
+            },
+            "subsequentExecutionMode" => match Preference::number(value).map(|mode| mode as i64) {
+                Some(0) => cycling = Some(CyclingMode::Sizes),
+                Some(1) => cycling = Some(CyclingMode::Displays),
+                Some(2) => cycling = Some(CyclingMode::None),
+                _ => report.add(item, "the mode is not supported by Rectangular"),
+            },
+            _ => report.add(item, "the preference is not supported by Rectangular"),
+        }
+    }
+
+    Ok(RectangleImport { hotkeys, gaps, cycling, report })
+}
+
+fn action_from_name(name: &str) -> Option<WindowAction> {
+    ACTION_NAMES.iter().find(|(other, _)| *other == name).map(|(_, action)| *action)
+}
+
+/// Convert a Rectangle shortcut to a hotkey, or describe why it can't be done.
+fn shortcut_to_hotkey(shortcut: &Value, options: &RectangleImportOptions)
+                      -> Result<Hotkey, String> {
+    let Shortcut { key_code, modifier_flags: flags } = Shortcut::deserialize(shortcut)
+        .map_err(|error| format!("the shortcut is invalid: {error}"))?;
+
+    let vk = KEY_CODES.iter()
+        .find(|(code, _)| *code == key_code)
+        .map(|(_, vk)| *vk)
+        .ok_or_else(|| format!("the key code {key_code} has no counterpart on Windows"))?;
+
+    let mut modifiers = Modifiers::NONE;
+    for (flag, modifier) in [(FLAG_SHIFT, Modifiers::SHIFT), (FLAG_CONTROL, Modifiers::CTRL),
+                             (FLAG_OPTION, Modifiers::ALT), (FLAG_COMMAND, options.command)] {
+        if flags & flag != 0 {
+            modifiers = modifiers | modifier;
+        }
+    }
+
+    Ok(Hotkey::new(modifiers, KeySpec::Virtual(vk)))
+}
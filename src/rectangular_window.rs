@@ -4,24 +4,77 @@
 //! "message-only window", that means it isn't visible to the user, but it can receive messages, and
 //! create other windows.
 
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
+use std::env;
 use std::ffi::c_void;
+use std::fs;
+use std::path::Path;
 use std::rc::Rc;
+use std::time::Instant;
 
 use windows::core::PCSTR;
 use windows::s;
 use windows::Win32::Foundation::{HINSTANCE, HWND, LPARAM, LRESULT, WPARAM};
+use windows::Win32::UI::Shell::ShellExecuteA;
 use windows::Win32::UI::WindowsAndMessaging::*;
 
+use crate::action::WindowAction;
 use crate::app_window::AppWindow;
-use crate::context_menu::ContextMenu;
-use crate::control_center::ControlCenter;
-use crate::{low_word, make_window_object, WINDOW_CLASS_NAME, WM_NIACTION};
+use crate::autostart::{self, AutostartState, RegistryStore};
+use crate::chord::{ChordEvent, ChordKey, ChordMachine, LeaderConfig};
+use crate::cli::RunOptions;
+use crate::command::{self, Command, CommandTarget};
+use crate::context_menu::{self, ContextMenu, MenuState};
+use crate::control_center::{
+    ControlCenter, WM_CONTROL_CENTER_ACTION, WM_CONTROL_CENTER_GRID, WM_CONTROL_CENTER_PREVIEW,
+    WM_CONTROL_CENTER_RESIZED,
+};
+use crate::control_center_view::Screen;
+use crate::foreground::{ForegroundHook, ForegroundTracker, WM_FOREGROUND_CHANGED};
+use crate::geometry::FracRect;
+use crate::grid_picker::{CellSpan, GridSize};
+use crate::pause::{self, PauseState, Shortcut};
+use crate::preview::{OverlayUpdate, PreviewState};
+use crate::preview_overlay::PreviewOverlay;
+use crate::{engine, high_word, hotkey, ignore, low_word, make_window_object, paths, reload,
+            settings, settings_edit};
+use crate::{WINDOW_CLASS_NAME, WM_NIACTION};
+use crate::hotkey::{Hotkey, KeySpec, Modifiers};
+use crate::keyboard_layout::{self, SystemKeyMap};
 use crate::notification::NotificationIcon;
+use crate::paths::DataPaths;
+use crate::reload::{Debouncer, Reload, SettingsTarget, SettingsWatcher, WM_SETTINGS_CHANGED};
+use crate::settings::{ControlCenterSettings, Settings, ThemeSettings, TrayClick};
+use crate::window_system::{Win32WindowSystem, WindowSystem};
 
 /// A name of the main application window.
 const WINDOW_NAME: PCSTR = s!("Rectangular");
 
+/// An ID of the hotkey starting a leader-key sequence.
+const HOTKEY_LEADER: i32 = 1;
+
+/// An ID of the hotkey pausing and resuming Rectangular.
+const HOTKEY_PAUSE: i32 = 2;
+
+/// IDs of hotkeys bound to actions start from this value.
+///
+/// The ID of such hotkey is this value plus the index of the action in [`WindowAction::ALL`].
+const HOTKEY_ACTION_BASE: i32 = 0x10;
+
+/// IDs of hotkeys grabbed while a leader-key sequence is typed start from this value.
+///
+/// The ID of such hotkey is this value plus the virtual-key code of the grabbed key.
+const HOTKEY_CHORD_KEY_BASE: i32 = 0x100;
+
+/// An ID of the timer cancelling a leader-key sequence.
+const TIMER_CHORD: usize = 1;
+
+/// An ID of the timer reloading settings after the settings file has changed.
+const TIMER_RELOAD: usize = 2;
+
+/// An ID of the timer resuming Rectangular after it has been paused for a while.
+const TIMER_RESUME: usize = 3;
+
 /// This structure represents the main application window.
 ///
 /// The purpose of this class is to be a container for things that are strictly related to the main
@@ -34,6 +87,34 @@ pub struct RectangularWindow {
     pub context_menu: ContextMenu,
     pub notification_icon: NotificationIcon,
     pub control_center: Rc<RefCell<ControlCenter>>,
+    pub preview_overlay: Rc<RefCell<PreviewOverlay>>,
+
+    /// Whether the notification icon is shown, see [`RunOptions::tray`].
+    tray: bool,
+
+    settings: RefCell<Settings>,
+    /// Where the settings file and everything else Rectangular writes are kept.
+    paths: Option<DataPaths>,
+    settings_watcher: Option<SettingsWatcher>,
+    reload_debouncer: RefCell<Debouncer>,
+
+    chord: RefCell<ChordMachine>,
+    /// Virtual-key codes of keys grabbed for the sequence being typed.
+    chord_keys: RefCell<Vec<u16>>,
+
+    /// The window Control Center actions are applied to.
+    foreground: RefCell<ForegroundTracker>,
+    foreground_hook: Option<ForegroundHook>,
+    /// Where the action previewed in the Control Center is shown.
+    preview: Cell<PreviewState>,
+    pause: Cell<PauseState>,
+    /// Whether shortcuts are released, because an ignored application asking for it is in the
+    /// foreground, see [`ignore`].
+    suspended: Cell<bool>,
+    /// Whether Rectangular starts at login, as the menu shows it.
+    start_at_login: RefCell<AutostartState>,
+    /// The keyboard layout of the foreground window, which shortcuts are labeled for.
+    key_map: Cell<Option<SystemKeyMap>>,
 }
 
 impl Default for RectangularWindow {
@@ -43,6 +124,21 @@ impl Default for RectangularWindow {
             context_menu: Default::default(),
             notification_icon: Default::default(),
             control_center: Default::default(),
+            preview_overlay: Default::default(),
+            tray: true,
+            settings: Default::default(),
+            paths: Default::default(),
+            settings_watcher: Default::default(),
+            reload_debouncer: Default::default(),
+            chord: Default::default(),
+            chord_keys: Default::default(),
+            foreground: Default::default(),
+            foreground_hook: Default::default(),
+            preview: Default::default(),
+            pause: Default::default(),
+            suspended: Default::default(),
+            start_at_login: Default::default(),
+            key_map: Default::default(),
         }
     }
 }
@@ -53,10 +149,38 @@ impl AppWindow for RectangularWindow {
         self.window_handle = window;
 
         self.notification_icon = NotificationIcon::new(window, app_instance);
-        self.notification_icon.add_to_window();
+        if self.tray {
+            self.notification_icon.add_to_window();
+        }
 
+        self.settings_watcher = self.paths.as_ref()
+            .and_then(|paths| SettingsWatcher::start(paths, window));
+
+        self.foreground_hook = ForegroundHook::start(window);
+        if let Some(foreground) = Win32WindowSystem.foreground() {
+            self.foreground.borrow_mut().foreground_changed(foreground, &Win32WindowSystem);
+        }
+
+        // The entry of an executable which has been moved since is only shown as such, it's
+        // pointed to this one when the user turns starting at login on again.
+        if let Ok(executable) = env::current_exe() {
+            *self.start_at_login.borrow_mut() = autostart::state(&RegistryStore, &executable)
+                .unwrap_or_default();
+        }
+
+        let settings = self.load_settings();
+        self.chord = RefCell::new(chord_machine(&settings.leader));
+        self.key_map.set(Some(SystemKeyMap::foreground()));
         self.context_menu = ContextMenu::new(window);
-        self.control_center = ControlCenter::new(app_instance);
+        self.rebuild_menu(&settings);
+        self.control_center = ControlCenter::new(app_instance, window);
+        self.control_center.borrow_mut().configure(settings.control_center.clone());
+        self.control_center.borrow_mut().set_theme(settings.theme.clone());
+        self.preview_overlay = PreviewOverlay::new(app_instance);
+        self.settings = RefCell::new(settings);
+
+        self.register_hotkeys();
+        self.foreground_changed();
 
         LRESULT(0)
     }
@@ -67,15 +191,80 @@ impl AppWindow for RectangularWindow {
             WM_DESTROY => {
                 unsafe { PostQuitMessage(0); }
             },
+            WM_HOTKEY => {
+                self.handle_hotkey(wparam.0 as i32);
+            },
+            WM_TIMER if wparam.0 == TIMER_CHORD => {
+                let event = self.chord.borrow_mut().tick(Instant::now());
+                self.handle_chord_event(event);
+            },
+            WM_TIMER if wparam.0 == TIMER_RELOAD => {
+                if self.reload_debouncer.borrow_mut().tick(Instant::now()) {
+                    unsafe { KillTimer(self.window_handle, TIMER_RELOAD); }
+                    self.reload_settings();
+                }
+            },
+            WM_TIMER if wparam.0 == TIMER_RESUME => {
+                let mut pause = self.pause.get();
+                if pause.tick(Instant::now()) {
+                    self.pause.set(pause);
+                    self.pause_changed();
+                }
+            },
+            WM_SETTINGS_CHANGED => {
+                self.reload_debouncer.borrow_mut().event(Instant::now());
+                unsafe {
+                    SetTimer(self.window_handle, TIMER_RELOAD,
+                             reload::DEBOUNCE_DELAY.as_millis() as u32, None);
+                }
+            },
+            WM_FOREGROUND_CHANGED => {
+                let window = HWND(wparam.0 as isize);
+                self.foreground.borrow_mut().foreground_changed(window, &Win32WindowSystem);
+                self.foreground_changed();
+            },
+            WM_CONTROL_CENTER_ACTION => {
+                self.handle_control_center_action(wparam.0);
+            },
+            WM_CONTROL_CENTER_PREVIEW => {
+                let action = wparam.0.checked_sub(1)
+                    .and_then(|index| WindowAction::ALL.get(index))
+                    .copied();
+                self.update_preview(|preview, target, settings| {
+                    preview.hover(action, target, &Win32WindowSystem, settings)
+                });
+            },
+            WM_CONTROL_CENTER_GRID => {
+                let span = CellSpan::unpack(wparam.0 as u32);
+                let size = GridSize::new(low_word!(lparam.0) as u32, high_word!(lparam.0) as u32);
+                self.apply_from_control_center(|window, settings| {
+                    engine::apply_fraction(window, &span.to_fraction(size), settings);
+                });
+            },
+            WM_CONTROL_CENTER_RESIZED => {
+                self.remember_control_center_size(low_word!(wparam.0) as u32,
+                                                  high_word!(wparam.0) as u32);
+            },
             WM_COMMAND => {
-                unsafe { PostMessageA(self.window_handle, WM_CLOSE, WPARAM(0), LPARAM(0)); }
+                let id = low_word!(wparam.0) as usize;
+                let command = Command::from_menu_id(id, &self.settings.borrow());
+                if let Some(command) = command {
+                    self.run_command(&command);
+                }
             },
             WM_NIACTION => match low_word!(lparam.0) as u32 {
                 WM_CONTEXTMENU => {
-                    self.context_menu.show(wparam);
+                    self.show_context_menu(wparam);
                 },
-                WM_LBUTTONUP => {
-                    self.control_center.borrow().show(wparam);
+                WM_LBUTTONUP => match self.settings.borrow().tray.left_click {
+                    TrayClick::ControlCenter => {
+                        let target = self.foreground.borrow().target(&Win32WindowSystem)
+                            .and_then(engine::monitor_of)
+                            .map(|(monitor, work_area)| Screen { monitor, work_area });
+                        self.control_center.borrow().show(wparam, target);
+                    },
+                    TrayClick::ContextMenu => self.show_context_menu(wparam),
+                    TrayClick::Nothing => {},
                 },
                 _ => unsafe { return DefWindowProcA(self.window_handle, message, wparam, lparam); }
             },
@@ -87,9 +276,21 @@ impl AppWindow for RectangularWindow {
 }
 
 impl RectangularWindow {
-    pub fn new(instance: HINSTANCE) -> Rc<RefCell<RectangularWindow>> {
+    /// Create the main window, configured by the command line.
+    ///
+    /// Without a settings file given on the command line, the one in the data directory is used,
+    /// see [`paths`].
+    pub fn new(instance: HINSTANCE, options: &RunOptions) -> Rc<RefCell<RectangularWindow>> {
         let (ptr, raw) = make_window_object!(RectangularWindow);
 
+        // The options have to be in place before the window is created, as they are needed by
+        // `handle_create`.
+        {
+            let mut window = ptr.borrow_mut();
+            window.tray = options.tray;
+            window.paths = paths::current(options.config.clone());
+        }
+
         unsafe {
             CreateWindowExA(
                 WINDOW_EX_STYLE::default(),
@@ -106,4 +307,612 @@ impl RectangularWindow {
 
         ptr
     }
-}
\ No newline at end of file
+
+    /// Get the settings file, see [`paths`].
+    fn settings_path(&self) -> Option<&Path> {
+        self.paths.as_ref().map(|paths| paths.settings.as_path())
+    }
+
+    /// Load settings from the settings file.
+    ///
+    /// Problems found in the file are shown as a notification. When the file can't be loaded at
+    /// all, the default settings are used.
+    fn load_settings(&self) -> Settings {
+        let Some(path) = self.settings_path() else {
+            return Settings::default();
+        };
+
+        match settings::load(path) {
+            Ok(loaded) => {
+                if let Some(warning) = loaded.warnings.first() {
+                    self.notify_settings_problem(&loaded.settings, &warning.to_string());
+                }
+
+                loaded.settings
+            },
+            Err(error) => {
+                let defaults = Settings::default();
+                if let Some(diagnostic) = error.0.first() {
+                    self.notify_settings_problem(&defaults, &diagnostic.to_string());
+                }
+
+                defaults
+            },
+        }
+    }
+
+    /// Reload settings after the settings file has changed.
+    ///
+    /// Only the changed parts of the settings are applied. When the file is invalid, the active
+    /// settings are kept, and the user is told what's wrong.
+    fn reload_settings(&self) {
+        let Some(path) = self.settings_path() else {
+            return;
+        };
+
+        let outcome = reload::reload(&self.settings.borrow(), settings::load(path));
+        match outcome {
+            Reload::Unchanged => {},
+            Reload::Changed { settings, diff, warnings } => {
+                let taken = reload::apply(&diff, &settings, self);
+                *self.settings.borrow_mut() = *settings;
+                // The ignore list may have changed, and with it the application in the foreground
+                // may ask for shortcuts to be released, or the menu may show it differently.
+                self.foreground_changed();
+
+                for hotkey in &taken {
+                    self.notify_hotkey_taken(hotkey);
+                }
+                if let Some(warning) = warnings.first() {
+                    self.notify_settings_problem(&self.settings.borrow(), &warning.to_string());
+                }
+            },
+            Reload::Rejected(error) => {
+                if let Some(diagnostic) = error.0.first() {
+                    let text = format!("The settings weren't reloaded.\n{diagnostic}");
+                    self.notify_settings_problem(&self.settings.borrow(), &text);
+                }
+            },
+        }
+    }
+
+    /// Register the leader key, the shortcut of pausing, and shortcuts of all actions.
+    fn register_hotkeys(&self) {
+        let settings = self.settings.borrow();
+        let shortcuts = wanted_shortcuts(&settings, self.pause.get().is_paused(),
+                                         self.suspended.get());
+        self.switch_shortcuts(&[], &shortcuts);
+    }
+
+    /// Unregister shortcuts which are no longer wanted, and register ones which are newly wanted.
+    fn switch_shortcuts(&self, before: &[(Shortcut, Hotkey)], after: &[(Shortcut, Hotkey)]) {
+        for (shortcut, _) in before.iter().filter(|shortcut| !after.contains(shortcut)) {
+            hotkey::unregister(self.window_handle, shortcut_hotkey_id(*shortcut));
+        }
+
+        for (shortcut, hotkey) in after.iter().filter(|shortcut| !before.contains(shortcut)) {
+            if !self.register_shortcut(shortcut_hotkey_id(*shortcut), hotkey) {
+                self.notify_hotkey_taken(hotkey);
+            }
+        }
+    }
+
+    /// Register a hotkey of the main window, with keys given by a scan code translated using the
+    /// keyboard layout of the foreground window.
+    fn register_shortcut(&self, id: i32, hotkey: &Hotkey) -> bool {
+        hotkey::register(self.window_handle, id, hotkey, &self.key_map())
+    }
+
+    /// Dispatch a `WM_HOTKEY` message with the given hotkey ID.
+    fn handle_hotkey(&self, id: i32) {
+        let now = Instant::now();
+        let actions = HOTKEY_ACTION_BASE..HOTKEY_ACTION_BASE + WindowAction::ALL.len() as i32;
+
+        let event = if id == HOTKEY_LEADER {
+            self.chord.borrow_mut().leader(now)
+        } else if id == HOTKEY_PAUSE {
+            self.run_command(&Command::Pause);
+            ChordEvent::Ignored
+        } else if actions.contains(&id) {
+            let action = WindowAction::ALL[(id - HOTKEY_ACTION_BASE) as usize];
+            self.run_command(&Command::Apply(action));
+            ChordEvent::Ignored
+        } else {
+            match ChordKey::from_vk((id - HOTKEY_CHORD_KEY_BASE) as u16) {
+                Some(key) => self.chord.borrow_mut().key(key, now),
+                None => ChordEvent::Ignored,
+            }
+        };
+
+        self.handle_chord_event(event);
+    }
+
+    /// Carry out the command chosen from the menu, or with a shortcut.
+    ///
+    /// Settings are copied, because some commands, like reloading them, replace the active ones.
+    /// A command which can't be carried out, e.g. a custom action removed from the settings while
+    /// the menu was open, is reported like other problems with the settings.
+    fn run_command(&self, command: &Command) {
+        let settings = self.settings.borrow().clone();
+
+        if let Err(error) = command::dispatch(command, &settings, self) {
+            self.notify_settings_problem(&settings, &error.to_string());
+        }
+    }
+
+    /// Get the window commands are meant for.
+    ///
+    /// That's the foreground window, unless it belongs to Rectangular, like while a command is
+    /// chosen from the context menu. Then it's the window the user has been working with before,
+    /// see [`foreground`](crate::foreground).
+    fn command_window(&self) -> Option<HWND> {
+        let foreground = Win32WindowSystem.foreground()?;
+        match Win32WindowSystem.info(foreground) {
+            Some(info) if info.own => self.foreground.borrow().target(&Win32WindowSystem),
+            _ => Some(foreground),
+        }
+    }
+
+    /// Apply the action chosen in the Control Center to the window the user has been working with
+    /// before opening it, see [`foreground`](crate::foreground).
+    fn handle_control_center_action(&self, index: usize) {
+        let Some(action) = WindowAction::ALL.get(index) else {
+            return;
+        };
+
+        self.apply_from_control_center(|window, settings| {
+            engine::apply(window, *action, settings);
+        });
+    }
+
+    /// Arrange the target window of the Control Center with `apply`.
+    ///
+    /// If the Control Center should close after an action, the window is activated again.
+    /// Otherwise, the Control Center stays in the foreground, so more actions can be tried. When
+    /// the window is gone, the Control Center says so instead.
+    fn apply_from_control_center(&self, apply: impl FnOnce(HWND, &Settings)) {
+        let control_center = self.control_center.borrow();
+        let Some(window) = self.foreground.borrow().target(&Win32WindowSystem) else {
+            control_center.show_empty_state();
+            return;
+        };
+
+        let settings = self.settings.borrow();
+        apply(window, &settings);
+
+        if settings.control_center.close_after_action {
+            control_center.hide();
+            Win32WindowSystem.activate(window);
+        } else {
+            // The window has moved, so e.g. a repeated action would move it somewhere else now.
+            self.update_preview(|preview, target, settings| {
+                preview.refresh(target, &Win32WindowSystem, settings)
+            });
+        }
+    }
+
+    /// Carry out a change of the paused state.
+    ///
+    /// Shortcuts which don't work while paused are released or registered again, the timer
+    /// resuming Rectangular is started or stopped, and the icon and the menu show the new state.
+    fn pause_changed(&self) {
+        let pause = self.pause.get();
+        let paused = pause.is_paused();
+        self.finish_chord();
+
+        {
+            let settings = self.settings.borrow();
+            let suspended = self.suspended.get();
+            self.switch_shortcuts(&wanted_shortcuts(&settings, !paused, suspended),
+                                  &wanted_shortcuts(&settings, paused, suspended));
+        }
+
+        unsafe {
+            match pause.remaining(Instant::now()) {
+                Some(remaining) => {
+                    let milliseconds = remaining.as_millis().min(u32::MAX as u128) as u32;
+                    SetTimer(self.window_handle, TIMER_RESUME, milliseconds, None);
+                },
+                None => {
+                    KillTimer(self.window_handle, TIMER_RESUME);
+                },
+            }
+        }
+
+        if self.tray {
+            self.notification_icon.set_paused(paused);
+        }
+        self.rebuild_menu(&self.settings.borrow());
+    }
+
+    /// Follow a change of the foreground window, or of the ignore list.
+    ///
+    /// Shortcuts are released while a window of an ignored application asking for it is in the
+    /// foreground, and registered again once it's not. The menu offers to ignore the application
+    /// of the window commands are meant for.
+    fn foreground_changed(&self) {
+        let settings = self.settings.borrow();
+        let suspended = Win32WindowSystem.foreground()
+            .and_then(ignore::identify)
+            .is_some_and(|identity| ignore::suspends_hotkeys(&settings.ignored_apps, &identity));
+
+        if suspended != self.suspended.get() {
+            let paused = self.pause.get().is_paused();
+            self.finish_chord();
+            self.switch_shortcuts(&wanted_shortcuts(&settings, paused, !suspended),
+                                  &wanted_shortcuts(&settings, paused, suspended));
+            self.suspended.set(suspended);
+        }
+
+        self.follow_keyboard_layout();
+        self.rebuild_menu(&settings);
+    }
+
+    /// Get the keyboard layout shortcuts are registered and labeled for.
+    fn key_map(&self) -> SystemKeyMap {
+        self.key_map.get().unwrap_or_else(SystemKeyMap::foreground)
+    }
+
+    /// Follow a change of the keyboard layout the user is typing in.
+    ///
+    /// Shortcuts given by a scan code are registered again, as their keys produce other
+    /// virtual-key codes in the new layout, and the menu labels shortcuts with its keys. Windows
+    /// doesn't tell a message-only window about a change of the layout, so it's checked whenever
+    /// the foreground window changes, and right before the menu is shown.
+    fn follow_keyboard_layout(&self) {
+        let key_map = SystemKeyMap::foreground();
+        if self.key_map.replace(Some(key_map)) == Some(key_map) {
+            return;
+        }
+
+        let settings = self.settings.borrow();
+        let shortcuts = wanted_shortcuts(&settings, self.pause.get().is_paused(),
+                                         self.suspended.get());
+        for (shortcut, hotkey) in &shortcuts {
+            if let KeySpec::Scan(_) = hotkey.key {
+                hotkey::unregister(self.window_handle, shortcut_hotkey_id(*shortcut));
+                if !self.register_shortcut(shortcut_hotkey_id(*shortcut), hotkey) {
+                    self.notify_hotkey_taken(hotkey);
+                }
+            }
+        }
+
+        self.rebuild_menu(&settings);
+    }
+
+    /// Show the context menu, with shortcuts labeled for the layout the user is typing in.
+    fn show_context_menu(&self, wparam: WPARAM) {
+        self.follow_keyboard_layout();
+        self.context_menu.show(wparam);
+    }
+
+    /// Get the state of the application shown in the context menu.
+    fn menu_state(&self, settings: &Settings) -> MenuState {
+        let target = self.foreground.borrow().target(&Win32WindowSystem).and_then(ignore::identify);
+
+        MenuState {
+            paused: self.pause.get().is_paused(),
+            app: target.as_ref().map(|identity| ignore::app_name(&identity.executable).to_string()),
+            app_ignored: target.is_some_and(|identity| {
+                ignore::is_ignored(&settings.ignored_apps, &identity)
+            }),
+            start_at_login: self.start_at_login.borrow().clone(),
+        }
+    }
+
+    /// Remember the size the user has resized the Control Center to, in the settings file too.
+    ///
+    /// The file is edited in place, so comments and everything else in it are kept. The change is
+    /// noticed by the settings watcher, and finds the same settings that are already active.
+    fn remember_control_center_size(&self, width: u32, height: u32) {
+        let control_center = {
+            let mut settings = self.settings.borrow_mut();
+            settings.control_center.width = Some(width);
+            settings.control_center.height = Some(height);
+            settings.control_center.clone()
+        };
+        self.configure_control_center(control_center);
+
+        let Some(path) = self.settings_path() else {
+            return;
+        };
+
+        if let Err(error) = settings_edit::save_control_center_size(path, width, height) {
+            let text = format!("The size of the Control Center wasn't saved.\n{error}");
+            self.notify_settings_problem(&self.settings.borrow(), &text);
+        }
+    }
+
+    /// Update the placement preview with `update`, and carry out the result on the overlay.
+    fn update_preview(&self,
+                      update: impl FnOnce(&mut PreviewState, Option<HWND>, &Settings)
+                          -> OverlayUpdate) {
+        let target = self.foreground.borrow().target(&Win32WindowSystem);
+        let mut preview = self.preview.get();
+        let result = update(&mut preview, target, &self.settings.borrow());
+        self.preview.set(preview);
+
+        let control_center = self.control_center.borrow();
+        self.preview_overlay.borrow().update(result, control_center.handle(),
+                                             control_center.theme());
+    }
+
+    /// React to a change of the leader-key state machine.
+    ///
+    /// While a sequence is being typed, all plain letters and digits are grabbed as global
+    /// hotkeys, so they don't reach the foreground window, and a key which doesn't continue any
+    /// sequence cancels it instead of being typed into the window. The keys are released as soon
+    /// as the sequence is completed or cancelled.
+    fn handle_chord_event(&self, event: ChordEvent) {
+        match event {
+            ChordEvent::Ignored => {},
+            ChordEvent::Started | ChordEvent::Advanced => {
+                if event == ChordEvent::Started {
+                    let keys: Vec<u16> = ChordKey::all().iter().map(|key| key.vk()).collect();
+                    self.grab_chord_keys(&keys);
+                }
+
+                let chord = self.chord.borrow();
+                let settings = self.settings.borrow();
+                unsafe {
+                    SetTimer(self.window_handle, TIMER_CHORD,
+                             settings.leader.timeout.as_millis() as u32, None);
+                }
+
+                if settings.leader.show_hint {
+                    self.notification_icon.show_info("Rectangular", &chord.hint_text());
+                }
+            },
+            ChordEvent::Completed(action) => {
+                self.finish_chord();
+                self.run_command(&Command::Apply(action));
+            },
+            ChordEvent::Cancelled(_) => {
+                self.finish_chord();
+            },
+        }
+    }
+
+    /// Replace currently grabbed keys with the given ones.
+    fn grab_chord_keys(&self, keys: &[u16]) {
+        self.release_chord_keys();
+
+        let mut grabbed = self.chord_keys.borrow_mut();
+        for key in keys {
+            if self.register_shortcut(HOTKEY_CHORD_KEY_BASE + *key as i32,
+                                      &Hotkey::new(Modifiers::NONE, KeySpec::Virtual(*key))) {
+                grabbed.push(*key);
+            }
+        }
+    }
+
+    fn release_chord_keys(&self) {
+        for key in self.chord_keys.borrow_mut().drain(..) {
+            hotkey::unregister(self.window_handle, HOTKEY_CHORD_KEY_BASE + key as i32);
+        }
+    }
+
+    /// Clean up after a sequence has been completed or cancelled.
+    fn finish_chord(&self) {
+        self.release_chord_keys();
+
+        unsafe {
+            KillTimer(self.window_handle, TIMER_CHORD);
+        }
+
+        if self.settings.borrow().leader.show_hint {
+            self.notification_icon.hide_info();
+        }
+    }
+
+    /// Let the user know that a shortcut couldn't be registered.
+    fn notify_hotkey_taken(&self, hotkey: &Hotkey) {
+        if !self.settings.borrow().tray.show_notifications {
+            return;
+        }
+
+        let label = keyboard_layout::hotkey_label(hotkey, &self.key_map());
+        let text = format!("The shortcut {label} is already used by another application.");
+        self.notification_icon.show_info("Rectangular", &text);
+    }
+
+    /// Let the user know about a problem in the settings file.
+    ///
+    /// The settings are passed explicitly, because the problem may be reported before they are
+    /// stored.
+    fn notify_settings_problem(&self, settings: &Settings, text: &str) {
+        if settings.tray.show_notifications {
+            self.notification_icon.show_info("Rectangular settings", text);
+        }
+    }
+}
+
+impl SettingsTarget for RectangularWindow {
+    /// Register the shortcut of an action, unless Rectangular is paused. In that case, it's
+    /// registered when Rectangular resumes.
+    fn register_hotkey(&self, action: WindowAction, hotkey: &Hotkey) -> bool {
+        if self.pause.get().is_paused() || self.suspended.get() {
+            return true;
+        }
+
+        self.register_shortcut(action_hotkey_id(action), hotkey)
+    }
+
+    fn unregister_hotkey(&self, action: WindowAction) {
+        hotkey::unregister(self.window_handle, action_hotkey_id(action));
+    }
+
+    fn replace_leader(&self, leader: &LeaderConfig) -> bool {
+        // A sequence typed right now could refer to bindings which no longer exist.
+        self.finish_chord();
+        *self.chord.borrow_mut() = chord_machine(leader);
+
+        hotkey::unregister(self.window_handle, HOTKEY_LEADER);
+        self.pause.get().is_paused() || self.suspended.get()
+            || self.register_shortcut(HOTKEY_LEADER, &leader.hotkey)
+    }
+
+    fn replace_pause_hotkey(&self, hotkey: Option<&Hotkey>) -> bool {
+        hotkey::unregister(self.window_handle, HOTKEY_PAUSE);
+        let register = |hotkey| self.register_shortcut(HOTKEY_PAUSE, hotkey);
+        self.suspended.get() || hotkey.is_none_or(register)
+    }
+
+    fn rebuild_menu(&self, settings: &Settings) {
+        let menu = context_menu::model(settings, &self.menu_state(settings), &self.key_map());
+        if let Err(error) = self.context_menu.rebuild(&menu) {
+            let text = format!("The menu couldn't be updated: {error}.");
+            self.notify_settings_problem(settings, &text);
+        }
+    }
+
+    fn configure_control_center(&self, settings: ControlCenterSettings) {
+        self.control_center.borrow_mut().configure(settings);
+    }
+
+    fn apply_theme(&self, settings: &ThemeSettings) {
+        self.control_center.borrow_mut().set_theme(settings.clone());
+    }
+}
+
+impl CommandTarget for RectangularWindow {
+    fn apply(&self, action: WindowAction, settings: &Settings) {
+        if let Some(window) = self.command_window() {
+            engine::apply(window, action, settings);
+        }
+    }
+
+    fn apply_fraction(&self, fraction: &FracRect, settings: &Settings) {
+        if let Some(window) = self.command_window() {
+            engine::apply_fraction(window, fraction, settings);
+        }
+    }
+
+    fn exit(&self) {
+        unsafe { PostMessageA(self.window_handle, WM_CLOSE, WPARAM(0), LPARAM(0)); }
+    }
+
+    fn toggle_pause(&self) {
+        let mut pause = self.pause.get();
+        pause.toggle(Instant::now(), &self.settings.borrow().pause);
+        self.pause.set(pause);
+        self.pause_changed();
+    }
+
+    /// Add the application to the ignore list in the settings file, or remove it from there.
+    ///
+    /// The file is edited in place, and loaded again right away, so the menu shows the change the
+    /// next time it's opened.
+    fn toggle_ignored_app(&self) {
+        let target = self.foreground.borrow().target(&Win32WindowSystem).and_then(ignore::identify);
+        let (Some(identity), Some(path)) = (target, self.settings_path()) else {
+            return;
+        };
+
+        let ignored = ignore::is_ignored(&self.settings.borrow().ignored_apps, &identity);
+        match settings_edit::save_app_ignored(path, &identity.executable, !ignored) {
+            Ok(true) => self.reload_settings(),
+            Ok(false) => {
+                let text = "The ignore list wasn't changed, it can't be edited in place.";
+                self.notify_settings_problem(&self.settings.borrow(), text);
+            },
+            Err(error) => {
+                let text = format!("The ignore list wasn't changed.\n{error}");
+                self.notify_settings_problem(&self.settings.borrow(), &text);
+            },
+        }
+    }
+
+    /// Add Rectangular to programs started at login, or remove it from there. A stale entry is
+    /// pointed to this executable.
+    fn toggle_start_at_login(&self) {
+        let toggled = env::current_exe()
+            .and_then(|executable| autostart::toggle(&RegistryStore, &executable));
+
+        match toggled {
+            Ok(enabled) => {
+                *self.start_at_login.borrow_mut() = match enabled {
+                    true => AutostartState::Enabled,
+                    false => AutostartState::Disabled,
+                };
+                self.rebuild_menu(&self.settings.borrow());
+            },
+            Err(error) if self.settings.borrow().tray.show_notifications => {
+                let text = format!("Starting at login wasn't changed.\n{error}");
+                self.notification_icon.show_info("Rectangular", &text);
+            },
+            Err(_) => {},
+        }
+    }
+
+    /// Open the settings file in the editor associated with `.toml` files.
+    ///
+    /// A missing file is created with the default settings first, so there's something to edit.
+    fn open_settings(&self) {
+        let Some(paths) = &self.paths else {
+            return;
+        };
+        let path = &paths.settings;
+
+        if !path.exists() {
+            let created = fs::create_dir_all(&paths.directory)
+                .and_then(|_| fs::write(path, settings::DEFAULT_SETTINGS));
+            if let Err(error) = created {
+                let text = format!("The settings file couldn't be created.\n{error}");
+                self.notify_settings_problem(&self.settings.borrow(), &text);
+                return;
+            }
+        }
+
+        let file = format!("{}\0", path.display());
+        unsafe {
+            ShellExecuteA(self.window_handle, s!("open"), PCSTR(file.as_ptr()), None, None,
+                          SW_SHOWNORMAL);
+        }
+    }
+
+    fn reload_settings(&self) {
+        RectangularWindow::reload_settings(self);
+    }
+
+    fn show_about(&self) {
+        let text = format!("Rectangular {}\n\nRearrange windows with the keyboard or the mouse.\0",
+                           env!("CARGO_PKG_VERSION"));
+        unsafe {
+            MessageBoxA(None, PCSTR(text.as_ptr()), s!("About Rectangular"),
+                        MB_OK | MB_ICONINFORMATION);
+        }
+    }
+}
+
+/// Get shortcuts which should be registered, see [`pause::active_shortcuts`]. None are while
+/// shortcuts are suspended for an ignored application.
+fn wanted_shortcuts(settings: &Settings, paused: bool, suspended: bool) -> Vec<(Shortcut, Hotkey)> {
+    match suspended {
+        true => Vec::new(),
+        false => pause::active_shortcuts(settings, paused),
+    }
+}
+
+/// Get the ID of the hotkey of the shortcut.
+fn shortcut_hotkey_id(shortcut: Shortcut) -> i32 {
+    match shortcut {
+        Shortcut::Leader => HOTKEY_LEADER,
+        Shortcut::Pause => HOTKEY_PAUSE,
+        Shortcut::Action(action) => action_hotkey_id(action),
+    }
+}
+
+/// Get the ID of the hotkey bound to the action.
+fn action_hotkey_id(action: WindowAction) -> i32 {
+    let index = WindowAction::ALL.iter().position(|other| *other == action).unwrap_or_default();
+    HOTKEY_ACTION_BASE + index as i32
+}
+
+/// Create a state machine for the leader-key sequences.
+///
+/// The settings are validated when loaded, so falling back to the default sequences never happens
+/// in practice.
+fn chord_machine(leader: &LeaderConfig) -> ChordMachine {
+    ChordMachine::new(leader.bindings.clone(), leader.timeout).unwrap_or_default()
+}
@@ -0,0 +1,348 @@
+//! This module reloads settings when the settings file changes.
+//!
+//! Reloading is split into a few steps, so most of them can be tested without Windows:
+//!
+//! 1. [`SettingsWatcher`] watches the directory of the settings file and posts
+//!    [`WM_SETTINGS_CHANGED`] to the main window whenever something in it changes. The changes
+//!    come from a [`ChangeSource`], so the watcher can be fed with made-up ones.
+//! 2. [`Debouncer`] waits until the changes settle down. Editors often write a file in a few steps,
+//!    or replace it with a renamed temporary file, and each step is reported separately.
+//! 3. [`reload`] compares freshly loaded settings with the active ones and produces a
+//!    [`SettingsDiff`]. Invalid settings are rejected, and the active settings are kept.
+//! 4. [`apply`] applies only what actually changed to a [`SettingsTarget`], which is the main
+//!    window in the application, and a fake one in tests.
+//!
+//! The time is always passed explicitly, like in [`ChordMachine`](crate::chord::ChordMachine), so
+//! the debouncing can be tested with made-up instants.
+
+use std::fs;
+use std::io;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use windows::core::{HSTRING, PCWSTR};
+use windows::Win32::Foundation::{CloseHandle, HANDLE, HWND, LPARAM, WAIT_OBJECT_0, WPARAM};
+use windows::Win32::Storage::FileSystem::{
+    FindChangeNotificationHandle, FindCloseChangeNotification, FindFirstChangeNotificationW,
+    FindNextChangeNotification, FILE_NOTIFY_CHANGE_FILE_NAME, FILE_NOTIFY_CHANGE_LAST_WRITE,
+};
+use windows::Win32::System::Threading::{CreateEventW, SetEvent, WaitForMultipleObjects};
+use windows::Win32::System::WindowsProgramming::INFINITE;
+use windows::Win32::UI::WindowsAndMessaging::{PostMessageA, WM_USER};
+
+use crate::action::WindowAction;
+use crate::chord::LeaderConfig;
+use crate::context_menu::{self, MenuState};
+use crate::hotkey::Hotkey;
+use crate::keyboard_layout::US_LAYOUT;
+use crate::paths::DataPaths;
+use crate::settings::{
+    ControlCenterSettings, Diagnostic, Loaded, Settings, SettingsError, ThemeSettings,
+};
+
+/// An ID of the message posted to the main window when the settings directory changes.
+pub const WM_SETTINGS_CHANGED: u32 = WM_USER + 2;
+
+/// How long to wait after the last change of the file before reloading it.
+pub const DEBOUNCE_DELAY: Duration = Duration::from_millis(300);
+
+/// Postpones an action until events stop coming for a while.
+#[derive(Clone, Debug)]
+pub struct Debouncer {
+    delay: Duration,
+    deadline: Option<Instant>,
+}
+
+impl Debouncer {
+    pub fn new(delay: Duration) -> Debouncer {
+        Debouncer { delay, deadline: None }
+    }
+
+    /// Record an event. Every event postpones the deadline.
+    pub fn event(&mut self, now: Instant) {
+        self.deadline = Some(now + self.delay);
+    }
+
+    /// The moment the postponed action is due, if there is one.
+    pub fn deadline(&self) -> Option<Instant> {
+        self.deadline
+    }
+
+    /// Check whether the postponed action is due.
+    ///
+    /// Returns `true` once per series of events, when the deadline has passed.
+    pub fn tick(&mut self, now: Instant) -> bool {
+        match self.deadline {
+            Some(deadline) if now >= deadline => {
+                self.deadline = None;
+                true
+            },
+            _ => false,
+        }
+    }
+}
+
+impl Default for Debouncer {
+    fn default() -> Self {
+        Debouncer::new(DEBOUNCE_DELAY)
+    }
+}
+
+/// A change of the shortcut bound to an action.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct HotkeyChange {
+    pub action: WindowAction,
+    pub old: Option<Hotkey>,
+    pub new: Option<Hotkey>,
+}
+
+/// Parts of the settings which differ between two versions.
+///
+/// Only parts which need some work to be applied are listed. Everything else, like gaps or cycling,
+/// is read from the settings whenever it's needed, so it takes effect as soon as the new settings
+/// are stored.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct SettingsDiff {
+    /// Actions whose shortcuts changed, in the order of [`WindowAction::ALL`].
+    pub hotkeys: Vec<HotkeyChange>,
+    /// Whether the leader key, its sequences, or its timeout changed.
+    pub leader: bool,
+    /// Whether the shortcut of pausing changed.
+    pub pause: bool,
+    /// Whether the model of the context menu changed.
+    pub menu: bool,
+    /// Whether the Control Center settings changed.
+    pub control_center: bool,
+    /// Whether the theme preset or any of its colors changed.
+    pub theme: bool,
+}
+
+impl SettingsDiff {
+    /// Compare two versions of the settings.
+    pub fn between(old: &Settings, new: &Settings) -> SettingsDiff {
+        let hotkeys: Vec<HotkeyChange> = WindowAction::ALL.iter()
+            .filter_map(|action| {
+                let old = old.hotkeys.get(*action).copied();
+                let new = new.hotkeys.get(*action).copied();
+                (old != new).then_some(HotkeyChange { action: *action, old, new })
+            })
+            .collect();
+
+        let pause = old.pause.hotkey != new.pause.hotkey;
+
+        SettingsDiff {
+            leader: old.leader != new.leader,
+            pause,
+            // Shortcuts are labeled for the active keyboard layout, where two of them may differ
+            // even if their labels on the US layout are the same.
+            menu: pause || !hotkeys.is_empty()
+                || context_menu::model(old, &MenuState::default(), &US_LAYOUT)
+                    != context_menu::model(new, &MenuState::default(), &US_LAYOUT),
+            control_center: old.control_center != new.control_center,
+            theme: old.theme != new.theme,
+            hotkeys,
+        }
+    }
+
+    /// Whether applying the diff requires any work.
+    pub fn is_empty(&self) -> bool {
+        self.hotkeys.is_empty() && !self.leader && !self.pause && !self.menu && !self.control_center
+            && !self.theme
+    }
+}
+
+/// A result of reloading the settings file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Reload {
+    /// The file has been loaded, but the settings are the same as the active ones.
+    Unchanged,
+    /// The settings changed, and should replace the active ones once the diff is applied.
+    Changed {
+        settings: Box<Settings>,
+        diff: SettingsDiff,
+        warnings: Vec<Diagnostic>,
+    },
+    /// The file is invalid, the active settings stay in effect.
+    Rejected(SettingsError),
+}
+
+/// Decide what to do with freshly loaded settings.
+pub fn reload(active: &Settings, loaded: Result<Loaded, SettingsError>) -> Reload {
+    match loaded {
+        Ok(loaded) if loaded.settings == *active => Reload::Unchanged,
+        Ok(Loaded { settings, warnings }) => {
+            let diff = SettingsDiff::between(active, &settings);
+            Reload::Changed { settings: Box::new(settings), diff, warnings }
+        },
+        Err(error) => Reload::Rejected(error),
+    }
+}
+
+/// Something the settings are applied to.
+///
+/// In the application this is the main window, which owns the hotkeys and the context menu.
+pub trait SettingsTarget {
+    /// Register the shortcut of an action. Returns `false` if the shortcut is already taken.
+    fn register_hotkey(&self, action: WindowAction, hotkey: &Hotkey) -> bool;
+
+    /// Unregister the shortcut of an action.
+    fn unregister_hotkey(&self, action: WindowAction);
+
+    /// Replace the leader key and its sequences. Returns `false` if the leader key is already
+    /// taken.
+    fn replace_leader(&self, leader: &LeaderConfig) -> bool;
+
+    /// Replace the shortcut of pausing. Returns `false` if it's already taken.
+    fn replace_pause_hotkey(&self, hotkey: Option<&Hotkey>) -> bool;
+
+    /// Rebuild the context menu for the new settings.
+    fn rebuild_menu(&self, settings: &Settings);
+
+    /// Apply the new Control Center settings.
+    fn configure_control_center(&self, settings: ControlCenterSettings);
+
+    /// Repaint windows with the new theme.
+    fn apply_theme(&self, settings: &ThemeSettings);
+}
+
+/// Apply the diff to the target.
+///
+/// All old shortcuts are unregistered before new ones are registered, so two actions can swap
+/// their shortcuts. Returns shortcuts which couldn't be registered.
+pub fn apply(diff: &SettingsDiff, settings: &Settings, target: &dyn SettingsTarget) -> Vec<Hotkey> {
+    let mut taken = Vec::new();
+
+    for change in diff.hotkeys.iter().filter(|change| change.old.is_some()) {
+        target.unregister_hotkey(change.action);
+    }
+
+    for change in &diff.hotkeys {
+        if let Some(hotkey) = &change.new {
+            if !target.register_hotkey(change.action, hotkey) {
+                taken.push(*hotkey);
+            }
+        }
+    }
+
+    if diff.leader && !target.replace_leader(&settings.leader) {
+        taken.push(settings.leader.hotkey);
+    }
+
+    if diff.pause && !target.replace_pause_hotkey(settings.pause.hotkey.as_ref()) {
+        taken.extend(settings.pause.hotkey);
+    }
+
+    if diff.menu {
+        target.rebuild_menu(settings);
+    }
+
+    if diff.control_center {
+        target.configure_control_center(settings.control_center.clone());
+    }
+
+    if diff.theme {
+        target.apply_theme(&settings.theme);
+    }
+
+    taken
+}
+
+/// A source of changes in the settings directory, blocking until the next one.
+pub trait ChangeSource: Send + 'static {
+    /// Wait for the next change. Returns `false` when no more changes will come.
+    fn wait(&mut self) -> bool;
+}
+
+/// Call `notify` for every change coming from the source, on a background thread which ends
+/// together with the source.
+pub fn watch<S: ChangeSource>(mut source: S, notify: impl Fn() + Send + 'static)
+                              -> thread::JoinHandle<()> {
+    thread::spawn(move || {
+        while source.wait() {
+            notify();
+        }
+    })
+}
+
+/// Get the directory to watch for the settings file, which is the data directory, creating it if
+/// needed.
+///
+/// On the first run the directory usually doesn't exist yet, but the file may be created later,
+/// e.g. by opening the settings, and it must be noticed then.
+pub fn settings_directory(paths: &DataPaths) -> io::Result<&Path> {
+    fs::create_dir_all(&paths.directory)?;
+    Ok(&paths.directory)
+}
+
+/// Changes reported by a change notification handle of Windows, until the stop event is set.
+struct DirectoryChanges {
+    changes: FindChangeNotificationHandle,
+    stop: HANDLE,
+}
+
+impl ChangeSource for DirectoryChanges {
+    fn wait(&mut self) -> bool {
+        unsafe {
+            // Both handles stay open until the thread has ended, see `SettingsWatcher::drop`.
+            let handles = [HANDLE(self.changes.0), self.stop];
+            WaitForMultipleObjects(&handles, false, INFINITE) == WAIT_OBJECT_0
+                && FindNextChangeNotification(self.changes).as_bool()
+        }
+    }
+}
+
+/// Watches the directory of the settings file.
+///
+/// The directory is watched rather than the file itself, because many editors save a file by
+/// replacing it. Notifications are posted as [`WM_SETTINGS_CHANGED`] from a background thread,
+/// which lives as long as the watcher.
+pub struct SettingsWatcher {
+    changes: FindChangeNotificationHandle,
+    /// An event telling the background thread to end.
+    stop: HANDLE,
+    thread: Option<thread::JoinHandle<()>>,
+}
+
+impl SettingsWatcher {
+    /// Start watching the data directory, creating it if it doesn't exist yet.
+    ///
+    /// Returns `None` if the directory can't be created, or can't be watched.
+    pub fn start(paths: &DataPaths, window: HWND) -> Option<SettingsWatcher> {
+        let directory = settings_directory(paths).ok()?;
+        let filter = FILE_NOTIFY_CHANGE_LAST_WRITE | FILE_NOTIFY_CHANGE_FILE_NAME;
+        let stop = unsafe { CreateEventW(None, true, false, PCWSTR::null()).ok()? };
+        let changes = unsafe {
+            let directory = HSTRING::from(directory.to_string_lossy().as_ref());
+            FindFirstChangeNotificationW(&directory, false, filter)
+        };
+        let Ok(changes) = changes else {
+            unsafe { CloseHandle(stop); }
+            return None;
+        };
+
+        let thread = watch(DirectoryChanges { changes, stop }, move || unsafe {
+            PostMessageA(window, WM_SETTINGS_CHANGED, WPARAM(0), LPARAM(0));
+        });
+
+        Some(SettingsWatcher { changes, stop, thread: Some(thread) })
+    }
+}
+
+impl Drop for SettingsWatcher {
+    fn drop(&mut self) {
+        // The handles are closed only once the thread waiting on them has ended.
+        unsafe {
+            SetEvent(self.stop);
+        }
+
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+
+        unsafe {
+            FindCloseChangeNotification(self.changes);
+            CloseHandle(self.stop);
+        }
+    }
+}
@@ -0,0 +1,436 @@
+//! This module generates a JSON Schema of the settings file.
+//!
+//! Editors understanding JSON Schema, like VS Code with the Even Better TOML extension (which is
+//! based on Taplo), use it to complete keys and values of the settings file, show their
+//! descriptions and flag mistakes as they are typed. The schema is printed by
+//! `rectangular --print-schema`, and a copy is kept in `schema/settings.schema.json`. A settings
+//! file can refer to a copy saved next to it with a directive on its first line:
+//!
+//! ```toml
+//! #:schema ./settings.schema.json
+//! ```
+//!
+//! The schema is built from the same types and defaults the parser uses, so names of actions,
+//! default values and key names of shortcuts can't go out of sync with it. Descriptions follow
+//! the documentation of the [`settings`](crate::settings) types.
+
+use serde_json::Value;
+
+use crate::action::WindowAction;
+use crate::hotkey;
+use crate::migration;
+use crate::grid_picker::MAX_CELLS;
+use crate::settings::{
+    ControlCenterPosition, CyclingMode, DisplayOrder, GridSettings, Settings, ThemePreset,
+    TrayClick,
+};
+use crate::theme::THEME_COLORS;
+
+/// Names of the cycling modes in the settings file.
+pub const CYCLING_MODES: [(CyclingMode, &str); 3] = [
+    (CyclingMode::None, "none"),
+    (CyclingMode::Sizes, "sizes"),
+    (CyclingMode::Displays, "displays"),
+];
+
+/// Names of the display orders in the settings file.
+pub const DISPLAY_ORDERS: [(DisplayOrder, &str); 2] = [
+    (DisplayOrder::LeftToRight, "left-to-right"),
+    (DisplayOrder::TopToBottom, "top-to-bottom"),
+];
+
+/// Names of the Control Center positions in the settings file.
+pub const CONTROL_CENTER_POSITIONS: [(ControlCenterPosition, &str); 2] = [
+    (ControlCenterPosition::NearTray, "near-tray"),
+    (ControlCenterPosition::ScreenCenter, "screen-center"),
+];
+
+/// Names of the reactions to a click on the notification icon in the settings file.
+pub const TRAY_CLICKS: [(TrayClick, &str); 3] = [
+    (TrayClick::ControlCenter, "control-center"),
+    (TrayClick::ContextMenu, "context-menu"),
+    (TrayClick::Nothing, "nothing"),
+];
+
+/// Names of the theme presets in the settings file.
+pub const THEME_PRESETS: [(ThemePreset, &str); 4] = [
+    (ThemePreset::System, "system"),
+    (ThemePreset::Light, "light"),
+    (ThemePreset::Dark, "dark"),
+    (ThemePreset::HighContrast, "high-contrast"),
+];
+
+/// Generate the schema of the settings file.
+pub fn settings_schema() -> Value {
+    let defaults = Settings::default();
+
+    let hotkeys = WindowAction::ALL.iter()
+        .map(|action| {
+            let default = defaults.hotkeys.get(*action).map(ToString::to_string)
+                .unwrap_or_default();
+            let description = format!("The shortcut of \"{}\". An empty shortcut unbinds the \
+                                       action.", action.label());
+            (action.name(), hotkey_schema(&description, true, &default))
+        })
+        .collect();
+
+    let leader = &defaults.leader;
+    let sequences = leader.bindings.iter()
+        .map(|binding| (binding.keys.clone(), Value::from(binding.action.name())))
+        .collect();
+    let pause_hotkey = defaults.pause.hotkey.map(|hotkey| hotkey.to_string()).unwrap_or_default();
+    let resume_after = defaults.pause.resume_after
+        .map_or(0, |duration| (duration.as_secs() / 60) as u32);
+
+    object(vec![
+        ("$schema", "http://json-schema.org/draft-07/schema#".into()),
+        ("title", "Rectangular settings".into()),
+        ("description", "Settings of Rectangular, a window manager for Windows. Every key is \
+                         optional, a missing key keeps its default value.".into()),
+        ("type", "object".into()),
+        ("additionalProperties", false.into()),
+        ("properties", object(vec![
+            ("version", object(vec![
+                ("description", "The version of the settings format. Files without it are \
+                                 treated as version 0, and upgraded when loaded.".into()),
+                ("type", "integer".into()),
+                ("minimum", 0.into()),
+                ("maximum", migration::CURRENT_VERSION.into()),
+                ("default", migration::CURRENT_VERSION.into()),
+            ])),
+            ("hotkeys", table("Keyboard shortcuts bound to actions. Shortcuts given here are \
+                               merged with the default ones.", hotkeys)),
+            ("leader", table("The leader key, which starts a sequence of plain keys selecting an \
+                              action.", vec![
+                ("hotkey", hotkey_schema("The shortcut which starts a sequence.", false,
+                                         &leader.hotkey.to_string())),
+                ("timeout-ms", integer("Time in milliseconds after which an unfinished sequence \
+                                        is cancelled.", 1, leader.timeout.as_millis() as u32)),
+                ("show-hint", boolean("Whether to show a hint listing keys that can be pressed \
+                                       next.", leader.show_hint)),
+                ("sequences", object(vec![
+                    ("description", "Sequences of letters and digits with the actions they \
+                                     select. Sequences given here replace the default ones."
+                        .into()),
+                    ("type", "object".into()),
+                    ("propertyNames", object(vec![("pattern", "^[A-Za-z0-9]+$".into())])),
+                    ("additionalProperties", action_schema()),
+                    ("default", Value::Object(sequences)),
+                ])),
+            ])),
+            ("pause", table("Pausing Rectangular, e.g. during presentations or games.", vec![
+                ("hotkey", hotkey_schema("The shortcut pausing and resuming Rectangular, the \
+                                          only one which works while paused.", true,
+                                         &pause_hotkey)),
+                ("resume-after-minutes", integer("Minutes after which Rectangular resumes by \
+                                                  itself, 0 keeps it paused until it's \
+                                                  resumed.", 0, resume_after)),
+            ])),
+            ("gaps", table("Gaps between arranged windows, in pixels.", vec![
+                ("inner", integer("A gap between two adjacent windows.", 0,
+                                  defaults.gaps.inner)),
+                ("outer", integer("A gap between a window and the edge of the work area.", 0,
+                                  defaults.gaps.outer)),
+            ])),
+            ("cycling", table("What happens when a half action is repeated on a window which is \
+                               already in place.", vec![
+                ("mode", string_enum("\"none\" keeps the window in place, \"sizes\" cycles \
+                                      through the sizes, \"displays\" moves the window to the \
+                                      adjacent display.", &CYCLING_MODES, defaults.cycling.mode)),
+                ("sizes", object(vec![
+                    ("description", "Fractions of the work area a half action cycles through, \
+                                     in order.".into()),
+                    ("type", "array".into()),
+                    ("items", object(vec![
+                        ("type", "number".into()),
+                        ("exclusiveMinimum", 0.into()),
+                        ("maximum", 1.into()),
+                    ])),
+                    ("default", Value::Array(defaults.cycling.sizes.iter()
+                        .map(|size| Value::from(*size))
+                        .collect())),
+                ])),
+                ("display-order", string_enum("The order in which displays are traversed by \
+                                               \"displays\".", &DISPLAY_ORDERS,
+                                              defaults.cycling.display_order)),
+                ("wrap-around", boolean("Whether moving past the last display continues on the \
+                                         first one.", defaults.cycling.wrap_around)),
+            ])),
+            ("ignored-apps", ignored_apps_schema()),
+            ("control-center", table("The window with buttons of all actions, shown by the \
+                                      notification icon.", vec![
+                ("position", string_enum("Where the Control Center shows up.",
+                                         &CONTROL_CENTER_POSITIONS,
+                                         defaults.control_center.position)),
+                ("close-after-action", boolean("Whether the Control Center hides after an \
+                                                action has been chosen.",
+                                               defaults.control_center.close_after_action)),
+                ("width", size("The width of the Control Center in pixels at 100% scaling, \
+                                saved when it's resized.")),
+                ("height", size("The height of the Control Center in pixels at 100% scaling, \
+                                 saved when it's resized.")),
+                ("grid", grid_schema()),
+            ])),
+            ("tray", table("The notification icon.", vec![
+                ("left-click", string_enum("What happens when the icon is clicked with the \
+                                            primary mouse button.", &TRAY_CLICKS,
+                                           defaults.tray.left_click)),
+                ("show-notifications", boolean("Whether to show balloon notifications, e.g. \
+                                                about shortcuts that couldn't be registered.",
+                                               defaults.tray.show_notifications)),
+            ])),
+            ("theme", table("Colors of the Control Center and the placement preview.", vec![
+                ("preset", string_enum("The palette to start from. \"system\" follows the app \
+                                        mode of Windows, and high contrast.", &THEME_PRESETS,
+                                       defaults.theme.preset)),
+                ("colors", theme_colors_schema()),
+            ])),
+            ("custom-groups", custom_groups_schema()),
+        ])),
+        ("definitions", definitions()),
+    ])
+}
+
+/// Generate the schema as text, the way it's printed and checked into the repository.
+pub fn settings_schema_text() -> String {
+    format!("{:#}\n", settings_schema())
+}
+
+fn object(members: Vec<(&str, Value)>) -> Value {
+    Value::Object(members.into_iter().map(|(key, value)| (key.to_string(), value)).collect())
+}
+
+/// A table with known keys only.
+fn table(description: &str, properties: Vec<(&str, Value)>) -> Value {
+    object(vec![
+        ("description", description.into()),
+        ("type", "object".into()),
+        ("additionalProperties", false.into()),
+        ("properties", object(properties)),
+    ])
+}
+
+fn boolean(description: &str, default: bool) -> Value {
+    object(vec![
+        ("description", description.into()),
+        ("type", "boolean".into()),
+        ("default", default.into()),
+    ])
+}
+
+fn integer(description: &str, minimum: u32, default: u32) -> Value {
+    object(vec![
+        ("description", description.into()),
+        ("type", "integer".into()),
+        ("minimum", minimum.into()),
+        ("default", default.into()),
+    ])
+}
+
+/// A length without a default, which is used only when it's given.
+fn size(description: &str) -> Value {
+    object(vec![
+        ("description", description.into()),
+        ("type", "integer".into()),
+        ("minimum", 1.into()),
+    ])
+}
+
+fn string_enum<T: PartialEq>(description: &str, names: &[(T, &str)], default: T) -> Value {
+    let default = names.iter().find(|(value, _)| *value == default).map_or("", |(_, name)| name);
+
+    object(vec![
+        ("description", description.into()),
+        ("type", "string".into()),
+        ("enum", Value::Array(names.iter().map(|(_, name)| Value::from(*name)).collect())),
+        ("default", default.into()),
+    ])
+}
+
+fn action_schema() -> Value {
+    object(vec![
+        ("type", "string".into()),
+        ("enum", Value::Array(WindowAction::ALL.iter()
+            .map(|action| Value::from(action.name()))
+            .collect())),
+    ])
+}
+
+/// A shortcut, like `Ctrl+Alt+Left`. Optional shortcuts can be left empty.
+///
+/// The pattern is long, so it's kept in the definitions of the schema, and referred to. The
+/// reference is wrapped in `allOf`, as keys next to a bare `$ref` are ignored.
+fn hotkey_schema(description: &str, optional: bool, default: &str) -> Value {
+    let definition = match optional {
+        true => "#/definitions/optional-hotkey",
+        false => "#/definitions/hotkey",
+    };
+
+    object(vec![
+        ("description", description.into()),
+        ("allOf", Value::Array(vec![object(vec![("$ref", definition.into())])])),
+        ("default", default.into()),
+    ])
+}
+
+fn definitions() -> Value {
+    let pattern = hotkey::hotkey_pattern();
+
+    object(vec![
+        ("hotkey", object(vec![
+            ("type", "string".into()),
+            ("pattern", pattern.clone().into()),
+        ])),
+        ("optional-hotkey", object(vec![
+            ("type", "string".into()),
+            ("pattern", format!(r"^\s*$|{pattern}").into()),
+        ])),
+    ])
+}
+
+fn grid_schema() -> Value {
+    let defaults = GridSettings::default();
+    let cells = |description: &str, default: Option<u32>| {
+        let mut members = vec![
+            ("description", description.into()),
+            ("type", "integer".into()),
+            ("minimum", 1.into()),
+            ("maximum", MAX_CELLS.into()),
+        ];
+        members.extend(default.map(|default| ("default", default.into())));
+        object(members)
+    };
+    let pixels = |description: &str| object(vec![
+        ("description", description.into()),
+        ("type", "integer".into()),
+        ("minimum", 1.into()),
+    ]);
+
+    let monitor = object(vec![
+        ("description", "The grid of monitors with the given resolution.".into()),
+        ("type", "object".into()),
+        ("additionalProperties", false.into()),
+        ("required", Value::Array(vec!["width".into(), "height".into(), "columns".into(),
+                                      "rows".into()])),
+        ("properties", object(vec![
+            ("width", pixels("The horizontal resolution of the monitor, in pixels.")),
+            ("height", pixels("The vertical resolution of the monitor, in pixels.")),
+            ("columns", cells("The number of columns of the grid.", None)),
+            ("rows", cells("The number of rows of the grid.", None)),
+        ])),
+    ]);
+
+    table("The grid picker, dividing the work area into columns and rows of cells.", vec![
+        ("columns", cells("The number of columns of the grid.", Some(defaults.columns))),
+        ("rows", cells("The number of rows of the grid.", Some(defaults.rows))),
+        ("monitors", object(vec![
+            ("description", "Grids of monitors with particular resolutions, e.g. ultrawide ones, \
+                             used instead of the one above.".into()),
+            ("type", "array".into()),
+            ("items", monitor),
+            ("default", Value::Array(Vec::new())),
+        ])),
+    ])
+}
+
+/// Colors overriding the ones of the preset, each written like `#0078D7`.
+fn theme_colors_schema() -> Value {
+    let colors = THEME_COLORS.iter()
+        .map(|(_, name)| {
+            (*name, object(vec![
+                ("type", "string".into()),
+                ("pattern", "^#[0-9A-Fa-f]{6}$".into()),
+            ]))
+        })
+        .collect();
+
+    table("Colors replacing the ones of the preset, like in HTML.", colors)
+}
+
+fn ignored_apps_schema() -> Value {
+    let pattern = |description: &str| object(vec![
+        ("description", description.into()),
+        ("type", "string".into()),
+    ]);
+
+    let app = object(vec![
+        ("description", "The executable name, like \"mstsc.exe\", or its full path.".into()),
+        ("type", "string".into()),
+        ("minLength", 1.into()),
+    ]);
+
+    let entry = object(vec![
+        ("type", "object".into()),
+        ("additionalProperties", false.into()),
+        ("required", Value::Array(vec!["app".into()])),
+        ("properties", object(vec![
+            ("app", app.clone()),
+            ("class", pattern("A pattern of the window class, where * matches any text and ? \
+                               any character.")),
+            ("title", pattern("A pattern of the window title, where * matches any text and ? \
+                               any character.")),
+            ("suspend-hotkeys", boolean("Whether shortcuts of Rectangular are released while a \
+                                         matching window is in the foreground.", false)),
+        ])),
+    ]);
+
+    object(vec![
+        ("description", "Applications whose windows are never moved, given by the executable \
+                         name, e.g. \"mstsc.exe\", or by a table narrowing the entry down to \
+                         some windows.".into()),
+        ("type", "array".into()),
+        ("items", object(vec![("oneOf", Value::Array(vec![app, entry]))])),
+        ("default", Value::Array(Vec::new())),
+    ])
+}
+
+fn custom_groups_schema() -> Value {
+    let fraction = |description: &str| object(vec![
+        ("description", description.into()),
+        ("type", "number".into()),
+        ("minimum", 0.into()),
+        ("maximum", 1.into()),
+    ]);
+
+    let rect = object(vec![
+        ("description", "The region of the work area the window should occupy, as fractions of \
+                         its size.".into()),
+        ("type", "object".into()),
+        ("additionalProperties", false.into()),
+        ("required", Value::Array(vec!["x".into(), "y".into(), "width".into(), "height".into()])),
+        ("properties", object(vec![
+            ("x", fraction("The left edge.")),
+            ("y", fraction("The top edge.")),
+            ("width", fraction("The width.")),
+            ("height", fraction("The height.")),
+        ])),
+    ]);
+
+    let action = object(vec![
+        ("description", "An action moving a window to an arbitrary region of the work area."
+            .into()),
+        ("type", "object".into()),
+        ("additionalProperties", false.into()),
+        ("required", Value::Array(vec!["name".into(), "rect".into()])),
+        ("properties", object(vec![
+            ("name", object(vec![("type", "string".into()), ("minLength", 1.into())])),
+            ("rect", rect),
+        ])),
+    ]);
+
+    object(vec![
+        ("description", "Named groups of custom actions, e.g. zones of a layout imported from \
+                         FancyZones.".into()),
+        ("type", "array".into()),
+        ("items", object(vec![
+            ("type", "object".into()),
+            ("additionalProperties", false.into()),
+            ("required", Value::Array(vec!["name".into(), "actions".into()])),
+            ("properties", object(vec![
+                ("name", object(vec![("type", "string".into()), ("minLength", 1.into())])),
+                ("actions", object(vec![("type", "array".into()), ("items", action)])),
+            ])),
+        ])),
+        ("default", Value::Array(Vec::new())),
+    ])
+}
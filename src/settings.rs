@@ -0,0 +1,831 @@
+//! This module contains Rectangular's settings and the parser of the settings file.
+//!
+//! Settings are kept in a TOML file, by default `%APPDATA%\Rectangular\settings.toml`, see
+//! [`paths`](crate::paths) for other locations. Every key of the file is optional, a missing key
+//! simply keeps its default value, so an empty file (or no file at all) is a valid configuration.
+//! An example of the file:
+//!
+//! ```toml
+//! version = 1
+//!
+//! [hotkeys]
+//! left-half = "Ctrl+Alt+Left"
+//! maximize = ""                # an empty shortcut unbinds the action
+//!
+//! [gaps]
+//! inner = 8
+//! outer = 8
+//!
+//! [cycling]
+//! mode = "sizes"
+//! sizes = [0.5, 0.6667, 0.3333]
+//!
+//! [[custom-groups]]
+//! name = "Three columns"
+//! actions = [
+//!     { name = "Left", rect = { x = 0.0, y = 0.0, width = 0.25, height = 1.0 } },
+//!     { name = "Middle", rect = { x = 0.25, y = 0.0, width = 0.5, height = 1.0 } },
+//! ]
+//! ```
+//!
+//! Parsing never panics and never gives up silently. Syntax and type errors are reported as
+//! [`Diagnostic`]s with a line and column, and keys which Rectangular doesn't know are reported as
+//! warnings, so a typo doesn't go unnoticed. The parser is platform-independent.
+
+use std::collections::BTreeMap;
+use std::error::Error;
+use std::fmt;
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+use std::time::Duration;
+
+use serde::de::value::MapAccessDeserializer;
+use serde::de::{MapAccess, Visitor};
+use serde::{Deserialize, Deserializer};
+use toml::de::{DeTable, DeValue};
+use toml::Spanned;
+
+use crate::action::WindowAction;
+use crate::chord::{ChordMachine, LeaderConfig};
+use crate::display_list::Color;
+use crate::geometry::{FracRect, Rect};
+use crate::grid_picker::{GridSize, MAX_CELLS};
+use crate::hotkey::Hotkey;
+use crate::keyboard_layout::{self, LAYOUTS, US_LAYOUT};
+use crate::migration;
+use crate::theme::ThemeColor;
+
+/// A name of the settings file.
+pub const SETTINGS_FILE_NAME: &str = "settings.toml";
+
+/// A settings file listing every setting with its default value, and a short explanation.
+pub const DEFAULT_SETTINGS: &str = include_str!("../resources/default-settings.toml");
+
+/// All settings of Rectangular.
+#[derive(Clone, Debug, Default, PartialEq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Settings {
+    pub hotkeys: Hotkeys,
+    pub leader: LeaderConfig,
+    pub pause: PauseSettings,
+    pub gaps: Gaps,
+    pub cycling: Cycling,
+    /// Applications whose windows are never moved, see [`ignore`](crate::ignore).
+    pub ignored_apps: Vec<IgnoredApp>,
+    pub control_center: ControlCenterSettings,
+    pub tray: TraySettings,
+    pub theme: ThemeSettings,
+    pub custom_groups: Vec<CustomGroup>,
+}
+
+/// Keyboard shortcuts bound to actions.
+///
+/// Shortcuts given in the settings file are merged with the default ones, an empty string unbinds
+/// an action.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hotkeys(pub BTreeMap<WindowAction, Hotkey>);
+
+impl Hotkeys {
+    /// Get the shortcut bound to the action.
+    pub fn get(&self, action: WindowAction) -> Option<&Hotkey> {
+        self.0.get(&action)
+    }
+
+    /// Find the action bound to the shortcut.
+    pub fn action_for(&self, hotkey: &Hotkey) -> Option<WindowAction> {
+        self.0.iter().find(|(_, bound)| *bound == hotkey).map(|(action, _)| *action)
+    }
+}
+
+impl Default for Hotkeys {
+    /// The default shortcuts follow the ones of Rectangle, with `Ctrl+Alt` in place of `Ctrl+Opt`.
+    fn default() -> Self {
+        const DEFAULTS: [(WindowAction, &str); 15] = [
+            (WindowAction::LeftHalf, "Ctrl+Alt+Left"),
+            (WindowAction::RightHalf, "Ctrl+Alt+Right"),
+            (WindowAction::TopHalf, "Ctrl+Alt+Up"),
+            (WindowAction::BottomHalf, "Ctrl+Alt+Down"),
+            (WindowAction::TopLeft, "Ctrl+Alt+U"),
+            (WindowAction::TopRight, "Ctrl+Alt+I"),
+            (WindowAction::BottomLeft, "Ctrl+Alt+J"),
+            (WindowAction::BottomRight, "Ctrl+Alt+K"),
+            (WindowAction::FirstThird, "Ctrl+Alt+D"),
+            (WindowAction::CenterThird, "Ctrl+Alt+F"),
+            (WindowAction::LastThird, "Ctrl+Alt+G"),
+            (WindowAction::FirstTwoThirds, "Ctrl+Alt+E"),
+            (WindowAction::LastTwoThirds, "Ctrl+Alt+T"),
+            (WindowAction::Maximize, "Ctrl+Alt+Enter"),
+            (WindowAction::Center, "Ctrl+Alt+C"),
+        ];
+
+        Hotkeys(DEFAULTS.iter()
+            .map(|(action, hotkey)| {
+                (*action, hotkey.parse().expect("Default shortcuts should be valid."))
+            })
+            .collect())
+    }
+}
+
+impl<'de> Deserialize<'de> for Hotkeys {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Hotkeys, D::Error> {
+        let overrides = BTreeMap::<WindowAction, OptionalHotkey>::deserialize(deserializer)?;
+        let mut hotkeys = Hotkeys::default();
+
+        for (action, hotkey) in overrides {
+            match hotkey.0 {
+                Some(hotkey) => hotkeys.0.insert(action, hotkey),
+                None => hotkeys.0.remove(&action),
+            };
+        }
+
+        Ok(hotkeys)
+    }
+}
+
+/// A shortcut which can be left empty to unbind an action.
+struct OptionalHotkey(Option<Hotkey>);
+
+impl<'de> Deserialize<'de> for OptionalHotkey {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<OptionalHotkey, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        if text.trim().is_empty() {
+            return Ok(OptionalHotkey(None));
+        }
+
+        text.parse()
+            .map(|hotkey| OptionalHotkey(Some(hotkey)))
+            .map_err(|error| serde::de::Error::custom(format!("invalid shortcut: {error}")))
+    }
+}
+
+/// Pausing Rectangular, e.g. during presentations or games.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct PauseSettings {
+    /// The shortcut pausing and resuming Rectangular, the only one which works while paused.
+    #[serde(deserialize_with = "deserialize_optional_hotkey")]
+    pub hotkey: Option<Hotkey>,
+    /// Time after which Rectangular resumes by itself, `None` to stay paused until resumed.
+    #[serde(rename = "resume-after-minutes", deserialize_with = "deserialize_minutes")]
+    pub resume_after: Option<Duration>,
+}
+
+impl Default for PauseSettings {
+    fn default() -> Self {
+        PauseSettings {
+            hotkey: Some("Ctrl+Alt+P".parse().expect("The default shortcut should be valid.")),
+            resume_after: None,
+        }
+    }
+}
+
+fn deserialize_optional_hotkey<'de, D: Deserializer<'de>>(deserializer: D)
+    -> Result<Option<Hotkey>, D::Error> {
+    OptionalHotkey::deserialize(deserializer).map(|hotkey| hotkey.0)
+}
+
+/// Read a number of minutes, where zero means never.
+fn deserialize_minutes<'de, D: Deserializer<'de>>(deserializer: D)
+    -> Result<Option<Duration>, D::Error> {
+    let minutes = u64::deserialize(deserializer)?;
+    Ok((minutes > 0).then(|| Duration::from_secs(minutes.saturating_mul(60))))
+}
+
+/// An entry of the ignore list, written either as an executable, e.g. `"mstsc.exe"`, or as a
+/// table, which can narrow the entry down to some windows of the application.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct IgnoredApp {
+    /// The executable name, like `mstsc.exe`, or its full path.
+    pub app: String,
+    /// A pattern of the window class, where `*` matches any text and `?` any character.
+    pub class: Option<String>,
+    /// A pattern of the window title, like the one of the class.
+    pub title: Option<String>,
+    /// Whether shortcuts of Rectangular are released while a matching window is in the
+    /// foreground, so they reach the application.
+    pub suspend_hotkeys: bool,
+}
+
+impl IgnoredApp {
+    /// Create an entry matching every window of the application.
+    pub fn new(app: &str) -> IgnoredApp {
+        IgnoredApp { app: app.to_string(), class: None, title: None, suspend_hotkeys: false }
+    }
+}
+
+/// The table form of [`IgnoredApp`].
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields, rename_all = "kebab-case")]
+struct IgnoredAppTable {
+    app: String,
+    class: Option<String>,
+    title: Option<String>,
+    #[serde(default)]
+    suspend_hotkeys: bool,
+}
+
+impl<'de> Deserialize<'de> for IgnoredApp {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<IgnoredApp, D::Error> {
+        struct EntryVisitor;
+
+        impl<'de> Visitor<'de> for EntryVisitor {
+            type Value = IgnoredApp;
+
+            fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.write_str("an executable name, or a table with the `app` key")
+            }
+
+            fn visit_str<E: serde::de::Error>(self, app: &str) -> Result<IgnoredApp, E> {
+                Ok(IgnoredApp::new(app))
+            }
+
+            fn visit_map<A: MapAccess<'de>>(self, map: A) -> Result<IgnoredApp, A::Error> {
+                let table = IgnoredAppTable::deserialize(MapAccessDeserializer::new(map))?;
+                Ok(IgnoredApp {
+                    app: table.app,
+                    class: table.class,
+                    title: table.title,
+                    suspend_hotkeys: table.suspend_hotkeys,
+                })
+            }
+        }
+
+        deserializer.deserialize_any(EntryVisitor)
+    }
+}
+
+/// Gaps between arranged windows, in pixels.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Gaps {
+    /// A gap between two adjacent windows.
+    pub inner: u32,
+    /// A gap between a window and the edge of the work area.
+    pub outer: u32,
+}
+
+/// What happens when a half action is repeated on a window which is already in place.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum CyclingMode {
+    /// Nothing, the window stays where it is.
+    #[default]
+    None,
+    /// The window size cycles through [`Cycling::sizes`].
+    Sizes,
+    /// The window moves to the adjacent display, see [`Cycling::display_order`].
+    Displays,
+}
+
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Cycling {
+    pub mode: CyclingMode,
+    /// Fractions of the work area a half action cycles through, in order.
+    pub sizes: Vec<f64>,
+    /// The order in which displays are traversed when a window moves between them.
+    pub display_order: DisplayOrder,
+    /// Whether moving past the last display continues on the first one.
+    pub wrap_around: bool,
+}
+
+impl Default for Cycling {
+    fn default() -> Self {
+        Cycling {
+            mode: CyclingMode::None,
+            sizes: vec![1.0 / 2.0, 2.0 / 3.0, 1.0 / 3.0],
+            display_order: DisplayOrder::LeftToRight,
+            wrap_around: true,
+        }
+    }
+}
+
+/// The order in which displays are traversed when a window moves between them.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum DisplayOrder {
+    #[default]
+    LeftToRight,
+    TopToBottom,
+}
+
+/// A named group of custom actions, e.g. zones of a layout imported from FancyZones.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomGroup {
+    pub name: String,
+    pub actions: Vec<CustomAction>,
+}
+
+/// An action moving a window to an arbitrary region of the work area.
+#[derive(Clone, Debug, PartialEq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CustomAction {
+    pub name: String,
+    /// The region of the work area the window should occupy.
+    pub rect: FracRect,
+}
+
+/// Where the Control Center shows up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ControlCenterPosition {
+    #[default]
+    NearTray,
+    ScreenCenter,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ControlCenterSettings {
+    pub position: ControlCenterPosition,
+    /// Whether the Control Center hides after an action has been chosen.
+    pub close_after_action: bool,
+    /// The size of the client area in pixels at 100% scaling, remembered when the Control Center
+    /// is resized. Without it, buttons have their preferred size.
+    pub width: Option<u32>,
+    pub height: Option<u32>,
+    pub grid: GridSettings,
+}
+
+impl Default for ControlCenterSettings {
+    fn default() -> Self {
+        ControlCenterSettings {
+            position: ControlCenterPosition::NearTray,
+            close_after_action: true,
+            width: None,
+            height: None,
+            grid: Default::default(),
+        }
+    }
+}
+
+/// Sizes of the grid of the [grid picker](crate::grid_picker).
+#[derive(Clone, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct GridSettings {
+    /// The size of the grid of monitors not listed in [`GridSettings::monitors`].
+    pub columns: u32,
+    pub rows: u32,
+    /// Sizes of grids of monitors with particular resolutions, e.g. ultrawide ones.
+    pub monitors: Vec<MonitorGrid>,
+}
+
+impl GridSettings {
+    /// Get the size of the grid of a monitor, given its full rectangle in pixels.
+    pub fn size_for(&self, monitor: &Rect) -> GridSize {
+        self.monitors.iter()
+            .find(|grid| {
+                grid.width as i32 == monitor.width() && grid.height as i32 == monitor.height()
+            })
+            .map_or(GridSize::new(self.columns, self.rows),
+                    |grid| GridSize::new(grid.columns, grid.rows))
+    }
+}
+
+impl Default for GridSettings {
+    fn default() -> Self {
+        let size = GridSize::default();
+        GridSettings { columns: size.columns, rows: size.rows, monitors: Vec::new() }
+    }
+}
+
+/// The size of the grid of monitors with the given resolution.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MonitorGrid {
+    /// The resolution of the monitor, in pixels.
+    pub width: u32,
+    pub height: u32,
+    pub columns: u32,
+    pub rows: u32,
+}
+
+/// What happens when the notification icon is clicked with the primary mouse button.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum TrayClick {
+    #[default]
+    ControlCenter,
+    ContextMenu,
+    Nothing,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct TraySettings {
+    pub left_click: TrayClick,
+    /// Whether to show balloon notifications, e.g. about shortcuts that couldn't be registered.
+    pub show_notifications: bool,
+}
+
+impl Default for TraySettings {
+    fn default() -> Self {
+        TraySettings { left_click: TrayClick::ControlCenter, show_notifications: true }
+    }
+}
+
+/// The palette the Control Center and the overlays are drawn with, see [`Theme`].
+///
+/// [`Theme`]: crate::theme::Theme
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemePreset {
+    /// The light or dark theme, following the app mode of the system, or the colors of the
+    /// high-contrast theme of the system when high contrast is turned on.
+    #[default]
+    System,
+    Light,
+    Dark,
+    HighContrast,
+}
+
+#[derive(Clone, Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct ThemeSettings {
+    pub preset: ThemePreset,
+    /// Colors replacing the ones of the preset, e.g. `accent = "#E81123"`.
+    pub colors: BTreeMap<ThemeColor, Color>,
+}
+
+/// A position in the settings file, both values start from 1.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Location {
+    pub line: usize,
+    pub column: usize,
+}
+
+impl Location {
+    /// Convert a byte offset in the text to a line and column.
+    pub fn from_offset(text: &str, offset: usize) -> Location {
+        let before = &text[..offset.min(text.len())];
+        let line_start = before.rfind('\n').map_or(0, |index| index + 1);
+
+        Location {
+            line: before.matches('\n').count() + 1,
+            column: before[line_start..].chars().count() + 1,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Severity {
+    /// The settings were loaded, but something looks wrong.
+    Warning,
+    /// The settings couldn't be loaded.
+    Error,
+}
+
+/// A problem found in the settings file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Diagnostic {
+    pub severity: Severity,
+    pub location: Option<Location>,
+    pub message: String,
+}
+
+impl Diagnostic {
+    pub fn error(location: Option<Location>, message: impl Into<String>) -> Diagnostic {
+        Diagnostic { severity: Severity::Error, location, message: message.into() }
+    }
+
+    pub fn warning(location: Option<Location>, message: impl Into<String>) -> Diagnostic {
+        Diagnostic { severity: Severity::Warning, location, message: message.into() }
+    }
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(location) = self.location {
+            write!(f, "{}:{}: ", location.line, location.column)?;
+        }
+
+        match self.severity {
+            Severity::Warning => write!(f, "warning: {}", self.message),
+            Severity::Error => write!(f, "error: {}", self.message),
+        }
+    }
+}
+
+/// An error returned when the settings couldn't be loaded.
+///
+/// The error carries all diagnostics found in the file, including warnings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SettingsError(pub Vec<Diagnostic>);
+
+impl fmt::Display for SettingsError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let lines: Vec<String> = self.0.iter().map(Diagnostic::to_string).collect();
+        f.write_str(&lines.join("\n"))
+    }
+}
+
+impl Error for SettingsError {}
+
+/// Successfully loaded settings, along with warnings found on the way.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Loaded {
+    pub settings: Settings,
+    pub warnings: Vec<Diagnostic>,
+}
+
+/// Parse the content of a settings file.
+///
+/// Content written by an older version of Rectangular is [migrated](crate::migration) in memory
+/// before it's parsed. If the migration changes the settings, diagnostics refer to the upgraded
+/// content rather than to the given one, so they come without locations.
+pub fn parse(text: &str) -> Result<Loaded, SettingsError> {
+    let mut root = DeTable::parse(text)
+        .map_err(|error| SettingsError(vec![toml_diagnostic(text, &error)]))?;
+
+    let version = take_version(text, &mut root)?;
+    if version < migration::CURRENT_VERSION {
+        let migrated = migration::migrate(text)
+            .map_err(|message| SettingsError(vec![Diagnostic::error(None, message)]))?;
+
+        if let Some(migrated) = migrated.filter(|migrated| migrated.settings_changed) {
+            let unlocated = |diagnostics: Vec<Diagnostic>| -> Vec<Diagnostic> {
+                diagnostics.into_iter()
+                    .map(|diagnostic| Diagnostic {
+                        location: None,
+                        message: format!("in the settings upgraded from version {version}: {}",
+                                         diagnostic.message),
+                        ..diagnostic
+                    })
+                    .collect()
+            };
+
+            return match parse(&migrated.text) {
+                Ok(loaded) => Ok(Loaded { warnings: unlocated(loaded.warnings), ..loaded }),
+                Err(error) => Err(SettingsError(unlocated(error.0))),
+            };
+        }
+    }
+
+    let mut unknown_keys: Vec<Vec<String>> = Vec::new();
+    let deserializer = toml::Deserializer::from(root.clone());
+    let settings: Settings = serde_ignored::deserialize(deserializer, |path| {
+        unknown_keys.push(path_segments(&path));
+    }).map_err(|error| SettingsError(vec![toml_diagnostic(text, &error)]))?;
+
+    // Keys are reported in the order of their paths, not in the order they are found.
+    unknown_keys.sort();
+    let warnings: Vec<Diagnostic> = unknown_keys.iter()
+        .map(|path| {
+            let location = locate(text, root.get_ref(), path);
+            Diagnostic::warning(location, format!("unknown key \"{}\"", path.join(".")))
+        })
+        .collect();
+
+    let errors = validate(&settings, text, root.get_ref());
+    if !errors.is_empty() {
+        return Err(SettingsError(errors.into_iter().chain(warnings).collect()));
+    }
+
+    Ok(Loaded { settings, warnings })
+}
+
+/// Load settings from a file.
+///
+/// A file written by an older version of Rectangular whose settings have changed since is
+/// upgraded in place first, see [`migration::upgrade_changed_file`], so diagnostics point into the
+/// file as it's now, and a warning tells where the original is kept. A missing file is not an
+/// error, the default settings are returned instead.
+pub fn load(path: &Path) -> Result<Loaded, SettingsError> {
+    let upgraded = migration::upgrade_changed_file(path);
+
+    let mut loaded = match fs::read_to_string(path) {
+        Ok(text) => parse(&text)?,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            return Ok(Loaded { settings: Settings::default(), warnings: Vec::new() });
+        },
+        Err(error) => return Err(SettingsError(vec![Diagnostic::error(
+            None, format!("cannot read {}: {error}", path.display()))])),
+    };
+
+    // Invalid upgraded settings are reported by parsing the file, and a missing file needs no
+    // upgrade.
+    let warning = match upgraded {
+        Ok(Some(version)) => Some(format!("the settings file has been upgraded from version \
+                                           {version}, the original is kept in {}",
+                                          migration::backup_path(path).display())),
+        Ok(None) => None,
+        Err(error) if matches!(error.kind(), io::ErrorKind::NotFound
+                                            | io::ErrorKind::InvalidData) => None,
+        Err(error) => Some(format!("cannot upgrade {}: {error}", path.display())),
+    };
+    if let Some(warning) = warning {
+        loaded.warnings.insert(0, Diagnostic::warning(None, warning));
+    }
+
+    Ok(loaded)
+}
+
+/// Check the version of the settings file, and remove it from the table, as it's not a setting.
+fn take_version(text: &str, root: &mut Spanned<DeTable>) -> Result<u32, SettingsError> {
+    let entry = root.get_ref().iter()
+        .find(|(key, _)| key.get_ref() == migration::VERSION_KEY);
+
+    let location = entry.map(|(_, value)| Location::from_offset(text, value.span().start));
+    let error = |message: String| SettingsError(vec![Diagnostic::error(location, message)]);
+
+    let version = match entry.map(|(_, value)| value.get_ref()) {
+        None => None,
+        Some(DeValue::Integer(integer)) => {
+            let digits = integer.as_str().replace('_', "");
+            let version = i64::from_str_radix(&digits, integer.radix())
+                .map_err(|_| error("the version is too large".to_string()))?;
+            Some(version)
+        },
+        Some(_) => return Err(error("the version must be a number".to_string())),
+    };
+
+    let version = migration::check_version(version).map_err(error)?;
+    if let Some(key) = entry.map(|(key, _)| key.clone()) {
+        root.get_mut().remove(&key);
+    }
+
+    Ok(version)
+}
+
+/// Check the rules which can't be expressed by types alone.
+fn validate(settings: &Settings, text: &str, root: &DeTable) -> Vec<Diagnostic> {
+    let mut errors = Vec::new();
+    let mut error_at = |path: &[&str], message: String| {
+        let path: Vec<String> = path.iter().map(|segment| segment.to_string()).collect();
+        errors.push(Diagnostic::error(locate(text, root, &path), message));
+    };
+
+    if settings.leader.timeout.is_zero() {
+        error_at(&["leader", "timeout-ms"], "the timeout must be greater than zero".to_string());
+    }
+
+    if let Err(error) = ChordMachine::new(settings.leader.bindings.clone(),
+                                          settings.leader.timeout) {
+        error_at(&["leader", "sequences"], error.to_string());
+    }
+
+    // Shortcuts are compared by the keys they register, so a key given by its scan code conflicts
+    // with the same key given by its name. The settings are checked without a live keyboard
+    // layout, so scan codes are resolved with every built-in one, and a conflict on a layout other
+    // than the US one names it.
+    let conflict = |first: &Hotkey, second: &Hotkey| {
+        LAYOUTS.iter()
+            .find(|layout| keyboard_layout::same_shortcut(first, second, *layout))
+            .map(|layout| match layout.language_id == US_LAYOUT.language_id {
+                true => String::new(),
+                false => format!(" on the {} layout", layout.name),
+            })
+    };
+
+    let mut bound: Vec<(&Hotkey, &str)> = vec![(&settings.leader.hotkey, "the leader key")];
+    if let Some(hotkey) = &settings.pause.hotkey {
+        if let Some(layout) = conflict(hotkey, &settings.leader.hotkey) {
+            error_at(&["pause", "hotkey"],
+                     format!("the shortcut {hotkey} is already used by the leader key{layout}"));
+        }
+        bound.push((hotkey, "the pause shortcut"));
+    }
+    for (action, hotkey) in &settings.hotkeys.0 {
+        let used = bound.iter()
+            .find_map(|(other, owner)| conflict(other, hotkey).map(|layout| (owner, layout)));
+        if let Some((owner, layout)) = used {
+            error_at(&["hotkeys", action.name()],
+                     format!("the shortcut {hotkey} of \"{action}\" is already used by \
+                              {owner}{layout}"));
+        }
+
+        bound.push((hotkey, action.name()));
+    }
+
+    for (position, ignored) in settings.ignored_apps.iter().enumerate() {
+        if ignored.app.trim().is_empty() {
+            error_at(&["ignored-apps", &position.to_string(), "app"],
+                     "the application must not be empty".to_string());
+        }
+    }
+
+    if settings.cycling.mode == CyclingMode::Sizes && settings.cycling.sizes.is_empty() {
+        error_at(&["cycling", "sizes"], "at least one size is required".to_string());
+    }
+
+    if settings.cycling.sizes.iter().any(|size| !(*size > 0.0 && *size <= 1.0)) {
+        error_at(&["cycling", "sizes"], "sizes must be greater than 0 and at most 1".to_string());
+    }
+
+    let control_center = &settings.control_center;
+    for (key, length) in [("width", control_center.width), ("height", control_center.height)] {
+        if length == Some(0) {
+            error_at(&["control-center", key], "the size must be greater than zero".to_string());
+        }
+    }
+
+    let grid = &settings.control_center.grid;
+    let cells_error = format!("the number of cells must be between 1 and {MAX_CELLS}");
+    for (key, count) in [("columns", grid.columns), ("rows", grid.rows)] {
+        if !(1..=MAX_CELLS).contains(&count) {
+            error_at(&["control-center", "grid", key], cells_error.clone());
+        }
+    }
+
+    for (position, monitor) in grid.monitors.iter().enumerate() {
+        let index = position.to_string();
+        for (key, count) in [("columns", monitor.columns), ("rows", monitor.rows)] {
+            if !(1..=MAX_CELLS).contains(&count) {
+                error_at(&["control-center", "grid", "monitors", &index, key], cells_error.clone());
+            }
+        }
+
+        if grid.monitors[..position].iter()
+            .any(|other| (other.width, other.height) == (monitor.width, monitor.height)) {
+            error_at(&["control-center", "grid", "monitors", &index],
+                     format!("the grid of {}x{} monitors is defined twice", monitor.width,
+                             monitor.height));
+        }
+    }
+
+    for (index, group) in settings.custom_groups.iter().enumerate() {
+        let group_path = ["custom-groups".to_string(), index.to_string()];
+        let mut error_in_group = |path: &[&str], message: String| {
+            let path: Vec<&str> = group_path.iter().map(String::as_str).chain(path.iter().copied())
+                .collect();
+            error_at(&path, message);
+        };
+
+        if group.name.trim().is_empty() {
+            error_in_group(&["name"], "the name of a group must not be empty".to_string());
+        } else if settings.custom_groups[..index].iter().any(|other| other.name == group.name) {
+            error_in_group(&["name"], format!("the group \"{}\" is defined twice", group.name));
+        }
+
+        for (action_index, action) in group.actions.iter().enumerate() {
+            let path = ["actions", &action_index.to_string()];
+            if group.actions[..action_index].iter().any(|other| other.name == action.name) {
+                error_in_group(&[&path[..], &["name"]].concat(),
+                               format!("the action \"{}\" is defined twice", action.name));
+            }
+
+            if !action.rect.is_valid() {
+                error_in_group(&[&path[..], &["rect"]].concat(),
+                               "the rect must be a non-empty part of the work area".to_string());
+            }
+        }
+    }
+
+    errors
+}
+
+/// Convert an error reported by the TOML parser to a diagnostic.
+fn toml_diagnostic(text: &str, error: &toml::de::Error) -> Diagnostic {
+    let location = error.span().map(|span| Location::from_offset(text, span.start));
+    Diagnostic::error(location, error.message().trim_end())
+}
+
+/// Convert a path reported by `serde_ignored` into a list of keys.
+fn path_segments(path: &serde_ignored::Path) -> Vec<String> {
+    match path {
+        serde_ignored::Path::Root => Vec::new(),
+        serde_ignored::Path::Seq { parent, index } => {
+            let mut segments = path_segments(parent);
+            segments.push(index.to_string());
+            segments
+        },
+        serde_ignored::Path::Map { parent, key } => {
+            let mut segments = path_segments(parent);
+            segments.push(key.clone());
+            segments
+        },
+        serde_ignored::Path::Some { parent }
+        | serde_ignored::Path::NewtypeStruct { parent }
+        | serde_ignored::Path::NewtypeVariant { parent } => path_segments(parent),
+    }
+}
+
+/// Find the location of a key in the settings file.
+///
+/// The path is a list of table keys and array indices. If the full path can't be found, the
+/// location of its longest existing prefix is returned.
+pub(crate) fn locate(text: &str, root: &DeTable, path: &[String]) -> Option<Location> {
+    locate_span(root, path).map(|span| Location::from_offset(text, span.start))
+}
+
+fn locate_span(table: &DeTable, path: &[String]) -> Option<Range<usize>> {
+    let (first, rest) = path.split_first()?;
+    let (key, value) = table.iter().find(|(key, _)| key.get_ref() == first)?;
+
+    Some(locate_in_value(value, rest).unwrap_or_else(|| key.span()))
+}
+
+fn locate_in_value(value: &Spanned<DeValue>, path: &[String]) -> Option<Range<usize>> {
+    match (value.get_ref(), path.split_first()) {
+        (_, None) => None,
+        (DeValue::Table(table), Some(_)) => locate_span(table, path),
+        (DeValue::Array(array), Some((index, rest))) => {
+            let item = array.get(index.parse::<usize>().ok()?)?;
+            Some(locate_in_value(item, rest).unwrap_or_else(|| item.span()))
+        },
+        _ => None,
+    }
+}
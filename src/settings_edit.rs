@@ -0,0 +1,291 @@
+//! This module makes small changes to the settings file on behalf of the user.
+//!
+//! Some settings are remembered rather than typed, like the size of the Control Center, or changed
+//! from the context menu, like the ignore list. They are written into the same file the user edits,
+//! so a change has to keep everything else as it is, including comments and the order of keys.
+//! This is unlike [`migration`], which rewrites the whole file. Values are replaced where they are,
+//! missing keys are added right below the header of their table, and a table without a header gets
+//! one at the end of the file. Entries of the ignore list are added at the end of the array, and
+//! removed together with their separators.
+//!
+//! The edited content is parsed again before it's used. If it doesn't hold the new values, e.g.
+//! because the table is written inline, the file is left alone.
+
+use std::fs;
+use std::io;
+use std::ops::Range;
+use std::path::Path;
+
+use toml::de::{DeTable, DeValue};
+use toml::{Spanned, Table, Value};
+
+use crate::ignore;
+use crate::migration::{self, CURRENT_VERSION, VERSION_KEY};
+
+/// The key of the ignore list.
+const IGNORED_APPS_KEY: &str = "ignored-apps";
+
+/// Set integer values of keys of a top-level table in the content of a settings file.
+///
+/// Returns the edited content, or `None` if it can't be edited without rewriting the file, or the
+/// content isn't valid TOML in the first place.
+pub fn set_integers(text: &str, table: &str, values: &[(&str, i64)]) -> Option<String> {
+    let root = DeTable::parse(text).ok()?;
+    let existing = root.get_ref().iter()
+        .find(|(key, _)| key.get_ref().as_ref() == table)
+        .and_then(|(_, value)| match value.get_ref() {
+            DeValue::Table(table) => Some(table),
+            _ => None,
+        });
+
+    let mut edits: Vec<(Range<usize>, String)> = Vec::new();
+    let mut missing = String::new();
+    for (name, value) in values {
+        let span = existing.and_then(|table| {
+            table.iter().find(|(key, _)| key.get_ref().as_ref() == *name)
+                .map(|(_, value)| value.span())
+        });
+
+        match span {
+            Some(span) => edits.push((span, value.to_string())),
+            None => missing.push_str(&format!("{name} = {value}\n")),
+        }
+    }
+
+    if !missing.is_empty() {
+        match header_end(text, table) {
+            Some(end) if text[..end].ends_with('\n') => edits.push((end..end, missing)),
+            Some(end) => edits.push((end..end, format!("\n{missing}"))),
+            None => {
+                let separator = match text {
+                    "" => "",
+                    text if text.ends_with('\n') => "\n",
+                    _ => "\n\n",
+                };
+                edits.push((text.len()..text.len(), format!("{separator}[{table}]\n{missing}")));
+            },
+        }
+    }
+
+    // Later edits first, so offsets of earlier ones stay valid.
+    edits.sort_by_key(|(span, _)| std::cmp::Reverse(span.start));
+    let mut edited = text.to_string();
+    for (span, replacement) in edits {
+        edited.replace_range(span, &replacement);
+    }
+
+    let parsed: Table = edited.parse().ok()?;
+    let written = values.iter().all(|(name, value)| {
+        parsed.get(table).and_then(|table| table.get(*name)) == Some(&Value::Integer(*value))
+    });
+
+    written.then_some(edited)
+}
+
+/// Add the application to the ignore list, or remove it, in the content of a settings file.
+///
+/// An application is added by the name of its executable. It's removed together with every entry
+/// naming it, including ones narrowed down to some windows, so no window of it stays ignored.
+/// Returns `None` if the list can't be edited in place, e.g. because it's written as an array of
+/// tables, or the content isn't valid TOML in the first place.
+pub fn set_app_ignored(text: &str, executable: &str, ignored: bool) -> Option<String> {
+    if lists_app(text, executable)? == ignored {
+        return Some(text.to_string());
+    }
+
+    let root = DeTable::parse(text).ok()?;
+    let existing = root.get_ref().iter()
+        .find(|(key, _)| key.get_ref().as_ref() == IGNORED_APPS_KEY)
+        .map(|(_, value)| (value.get_ref(), value.span()));
+
+    let entry = format!("\"{}\"", ignore::app_name(executable));
+    let mut edits = match (existing, ignored) {
+        (Some((DeValue::Array(entries), span)), true) => match entries.last() {
+            Some(last) => vec![(last.span().end..last.span().end, format!(", {entry}"))],
+            None => vec![(span, format!("[{entry}]"))],
+        },
+        (Some((DeValue::Array(entries), _)), false) => removals(text, entries, executable),
+        (None, true) => {
+            let end = top_level_end(text, &root);
+            let separator = match text[..end].is_empty() || text[..end].ends_with('\n') {
+                true => "",
+                false => "\n",
+            };
+            vec![(end..end, format!("{separator}{IGNORED_APPS_KEY} = [{entry}]\n"))]
+        },
+        _ => return None,
+    };
+
+    // Later edits first, so offsets of earlier ones stay valid.
+    edits.sort_by_key(|(span, _)| std::cmp::Reverse(span.start));
+    let mut edited = text.to_string();
+    for (span, replacement) in edits {
+        edited.replace_range(span, &replacement);
+    }
+
+    (lists_app(&edited, executable)? == ignored).then_some(edited)
+}
+
+/// Remember the size of the Control Center in the settings file.
+///
+/// A missing file is created. Returns `false` if the file already has the size, or can't be edited,
+/// see [`set_integers`].
+pub fn save_control_center_size(path: &Path, width: u32, height: u32) -> io::Result<bool> {
+    edit_file(path, |text| {
+        set_integers(text, "control-center", &[("width", width as i64), ("height", height as i64)])
+    })
+}
+
+/// Add the application to the ignore list in the settings file, or remove it from the list.
+///
+/// A missing file is created. Returns `false` if the list is already as requested, or can't be
+/// edited, see [`set_app_ignored`].
+pub fn save_app_ignored(path: &Path, executable: &str, ignored: bool) -> io::Result<bool> {
+    edit_file(path, |text| set_app_ignored(text, executable, ignored))
+}
+
+/// Edit the settings file in place with the given function, creating the file if it's missing.
+///
+/// Returns `false` if the file is left alone, because the function doesn't change it, or can't.
+fn edit_file(path: &Path, edit: impl FnOnce(&str) -> Option<String>) -> io::Result<bool> {
+    let text = match fs::read_to_string(path) {
+        Ok(text) => text,
+        Err(error) if error.kind() == io::ErrorKind::NotFound => {
+            format!("{VERSION_KEY} = {CURRENT_VERSION}\n")
+        },
+        Err(error) => return Err(error),
+    };
+
+    // A file whose settings are changed by a migration has to be upgraded first, editing it could
+    // only get in the way. Other old files are the same as current ones, apart from the version.
+    if migration::migrate(&text).is_ok_and(|migrated| {
+        migrated.is_some_and(|migrated| migrated.settings_changed)
+    }) {
+        return Ok(false);
+    }
+
+    match edit(&text) {
+        Some(edited) if edited != text => {
+            if let Some(directory) = path.parent() {
+                fs::create_dir_all(directory)?;
+            }
+            fs::write(path, edited)?;
+            Ok(true)
+        },
+        _ => Ok(false),
+    }
+}
+
+/// Find the end of the line with the header of the table, e.g. `[control-center]`, including the
+/// line break, if there's one.
+fn header_end(text: &str, table: &str) -> Option<usize> {
+    let header = format!("[{table}]");
+    let mut offset = 0;
+
+    for line in text.split_inclusive('\n') {
+        offset += line.len();
+
+        let content = line.split('#').next().unwrap_or_default().trim();
+        if content == header {
+            return Some(offset);
+        }
+    }
+
+    None
+}
+
+/// Find where a new top-level key can be added: after the line with the version, or at the start of
+/// the file without one, so it comes before every table.
+fn top_level_end(text: &str, root: &Spanned<DeTable>) -> usize {
+    let Some((_, version)) = root.get_ref().iter()
+        .find(|(key, _)| key.get_ref().as_ref() == VERSION_KEY) else {
+        return 0;
+    };
+
+    let end = version.span().end;
+    text[end..].find('\n').map_or(text.len(), |line_end| end + line_end + 1)
+}
+
+/// Get the application of an entry of the ignore list, as it's written in the file.
+fn entry_app<'a>(entry: &'a DeValue) -> Option<&'a str> {
+    match entry {
+        DeValue::String(app) => Some(app.as_ref()),
+        DeValue::Table(table) => table.iter()
+            .find(|(key, _)| key.get_ref().as_ref() == "app")
+            .and_then(|(_, value)| match value.get_ref() {
+                DeValue::String(app) => Some(app.as_ref()),
+                _ => None,
+            }),
+        _ => None,
+    }
+}
+
+/// Check whether the ignore list in the content of a settings file names the executable.
+///
+/// Returns `None` if the content isn't valid TOML, or the list isn't an array.
+fn lists_app(text: &str, executable: &str) -> Option<bool> {
+    let parsed: Table = text.parse().ok()?;
+    let entries = match parsed.get(IGNORED_APPS_KEY) {
+        Some(Value::Array(entries)) => entries,
+        Some(_) => return None,
+        None => return Some(false),
+    };
+
+    Some(entries.iter().any(|entry| {
+        let app = match entry {
+            Value::String(app) => Some(app.as_str()),
+            Value::Table(table) => table.get("app").and_then(Value::as_str),
+            _ => None,
+        };
+        app.is_some_and(|app| ignore::names_app(app, executable))
+    }))
+}
+
+/// Get edits removing entries naming the executable from the ignore list.
+///
+/// An entry on a line of its own is removed with the whole line. Otherwise, an entry followed by a
+/// kept one is removed up to the start of that one, and entries after the last kept one are removed
+/// from the end of it, together with the separator before them.
+fn removals(text: &str, entries: &[Spanned<DeValue>], executable: &str)
+            -> Vec<(Range<usize>, String)> {
+    let removed: Vec<bool> = entries.iter()
+        .map(|entry| {
+            entry_app(entry.get_ref()).is_some_and(|app| ignore::names_app(app, executable))
+        })
+        .collect();
+
+    let lines: Option<Vec<Range<usize>>> = entries.iter().zip(&removed)
+        .filter(|(_, removed)| **removed)
+        .map(|(entry, _)| whole_lines(text, entry.span()))
+        .collect();
+    if let Some(lines) = lines {
+        return lines.into_iter().map(|lines| (lines, String::new())).collect();
+    }
+
+    let last_kept = removed.iter().rposition(|removed| !removed);
+    let trailing = last_kept.map_or(0, |last_kept| last_kept + 1);
+    let mut edits: Vec<(Range<usize>, String)> = (0..trailing)
+        .filter(|index| removed[*index])
+        .map(|index| (entries[index].span().start..entries[index + 1].span().start, String::new()))
+        .collect();
+
+    if let Some(last) = entries[trailing..].last() {
+        let start = match last_kept {
+            Some(last_kept) => entries[last_kept].span().end,
+            None => entries[0].span().start,
+        };
+        edits.push((start..last.span().end, String::new()));
+    }
+
+    edits
+}
+
+/// Get the lines of an entry of an array, including the comma after it, if nothing else is on them.
+fn whole_lines(text: &str, span: Range<usize>) -> Option<Range<usize>> {
+    let start = text[..span.start].rfind('\n').map_or(0, |line_end| line_end + 1);
+    let end = span.end + text[span.end..].find('\n')? + 1;
+
+    let before = text[start..span.start].trim();
+    let after = text[span.end..end].trim();
+    (before.is_empty() && (after.is_empty() || after == ",")).then_some(start..end)
+}
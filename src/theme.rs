@@ -0,0 +1,322 @@
+//! This module contains the colors the Control Center and the overlays are drawn with.
+//!
+//! A [`Theme`] is a palette of colors, each with a role, like the background or the accent.
+//! There are three presets: light, dark and high-contrast. By default, the theme follows the
+//! system: whenever high contrast is turned on, it's made of the colors of the high-contrast theme
+//! chosen in Windows, and otherwise it's the light or dark preset, depending on the app mode
+//! chosen in the personalization settings. The settings can pick a preset explicitly, and override
+//! any of its colors, see [`ThemeSettings`].
+//!
+//! Views building [display lists](crate::display_list) and painters take the theme, so nothing
+//! else hardcodes a color. Resolving a theme is free of any Windows API, only
+//! [`SystemAppearance::current`] asks the system.
+
+use std::collections::BTreeMap;
+use std::ffi::c_void;
+use std::mem;
+
+use serde::Deserialize;
+use windows::s;
+use windows::Win32::Foundation::ERROR_SUCCESS;
+use windows::Win32::Graphics::Gdi::{COLOR_GRAYTEXT, COLOR_HIGHLIGHT, COLOR_HOTLIGHT, COLOR_WINDOW,
+                                    COLOR_WINDOWTEXT, GetSysColor, SYS_COLOR_INDEX};
+use windows::Win32::System::Registry::{HKEY_CURRENT_USER, RegGetValueA, RRF_RT_REG_DWORD};
+use windows::Win32::UI::Accessibility::{HCF_HIGHCONTRASTON, HIGHCONTRASTA};
+use windows::Win32::UI::WindowsAndMessaging::{
+    SPI_GETHIGHCONTRAST, SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS, SystemParametersInfoA,
+};
+
+use crate::display_list::Color;
+use crate::settings::{ThemePreset, ThemeSettings};
+
+/// A palette of colors, each with a role.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Theme {
+    /// The background of windows.
+    pub background: Color,
+    pub text: Color,
+    /// Secondary text, like the hint of the empty state.
+    pub hint: Color,
+    pub button: Color,
+    pub button_hovered: Color,
+    pub button_pressed: Color,
+    pub border: Color,
+    /// Target regions, the focus outline, and anything else which should stand out.
+    pub accent: Color,
+    /// The part of the monitor outside of the work area in miniatures, usually the taskbar.
+    pub monitor: Color,
+    pub work_area: Color,
+    /// The translucent fill of the placement preview.
+    pub preview_fill: Color,
+    pub preview_border: Color,
+}
+
+/// A role of a color in a [`Theme`], used to override the color in the settings.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ThemeColor {
+    Background,
+    Text,
+    Hint,
+    Button,
+    ButtonHovered,
+    ButtonPressed,
+    Border,
+    Accent,
+    Monitor,
+    WorkArea,
+    PreviewFill,
+    PreviewBorder,
+}
+
+/// Names of the color roles in the settings file.
+pub const THEME_COLORS: [(ThemeColor, &str); 12] = [
+    (ThemeColor::Background, "background"),
+    (ThemeColor::Text, "text"),
+    (ThemeColor::Hint, "hint"),
+    (ThemeColor::Button, "button"),
+    (ThemeColor::ButtonHovered, "button-hovered"),
+    (ThemeColor::ButtonPressed, "button-pressed"),
+    (ThemeColor::Border, "border"),
+    (ThemeColor::Accent, "accent"),
+    (ThemeColor::Monitor, "monitor"),
+    (ThemeColor::WorkArea, "work-area"),
+    (ThemeColor::PreviewFill, "preview-fill"),
+    (ThemeColor::PreviewBorder, "preview-border"),
+];
+
+impl Theme {
+    /// The colors of classic Windows controls.
+    pub const LIGHT: Theme = Theme {
+        background: Color::rgb(0xF0, 0xF0, 0xF0),
+        text: Color::rgb(0x00, 0x00, 0x00),
+        hint: Color::rgb(0x60, 0x60, 0x60),
+        button: Color::rgb(0xFF, 0xFF, 0xFF),
+        button_hovered: Color::rgb(0xE5, 0xF1, 0xFB),
+        button_pressed: Color::rgb(0xCC, 0xE4, 0xF7),
+        border: Color::rgb(0xAD, 0xAD, 0xAD),
+        accent: Color::rgb(0x00, 0x78, 0xD7),
+        monitor: Color::rgb(0x60, 0x60, 0x60),
+        work_area: Color::rgb(0xD9, 0xD9, 0xD9),
+        preview_fill: Color::rgb(0xCC, 0xE4, 0xF7),
+        preview_border: Color::rgb(0x00, 0x78, 0xD7),
+    };
+
+    /// The colors of Windows in the dark app mode, with a lighter accent, which is readable on a
+    /// dark background.
+    pub const DARK: Theme = Theme {
+        background: Color::rgb(0x20, 0x20, 0x20),
+        text: Color::rgb(0xFF, 0xFF, 0xFF),
+        hint: Color::rgb(0xA0, 0xA0, 0xA0),
+        button: Color::rgb(0x2D, 0x2D, 0x2D),
+        button_hovered: Color::rgb(0x1F, 0x3A, 0x52),
+        button_pressed: Color::rgb(0x1A, 0x4D, 0x78),
+        border: Color::rgb(0x5A, 0x5A, 0x5A),
+        accent: Color::rgb(0x4C, 0xC2, 0xFF),
+        monitor: Color::rgb(0x10, 0x10, 0x10),
+        work_area: Color::rgb(0x45, 0x45, 0x45),
+        preview_fill: Color::rgb(0x1A, 0x4D, 0x78),
+        preview_border: Color::rgb(0x4C, 0xC2, 0xFF),
+    };
+
+    /// Pure black and white, with a bright accent, like the high-contrast themes of Windows.
+    ///
+    /// It's used only when picked in the settings. When high contrast is turned on, the theme is
+    /// made of the system colors instead, see [`Theme::from_system_colors`].
+    ///
+    /// Hovering over a button only changes its border, the fill stays black, so text and
+    /// miniatures keep the full contrast.
+    pub const HIGH_CONTRAST: Theme = Theme {
+        background: Color::rgb(0x00, 0x00, 0x00),
+        text: Color::rgb(0xFF, 0xFF, 0xFF),
+        hint: Color::rgb(0xFF, 0xFF, 0xFF),
+        button: Color::rgb(0x00, 0x00, 0x00),
+        button_hovered: Color::rgb(0x00, 0x00, 0x00),
+        button_pressed: Color::rgb(0x00, 0x00, 0x00),
+        border: Color::rgb(0xFF, 0xFF, 0xFF),
+        accent: Color::rgb(0x1A, 0xEB, 0xFF),
+        monitor: Color::rgb(0xFF, 0xFF, 0xFF),
+        work_area: Color::rgb(0x00, 0x00, 0x00),
+        preview_fill: Color::rgb(0x00, 0x00, 0x00),
+        preview_border: Color::rgb(0x1A, 0xEB, 0xFF),
+    };
+
+    /// Make a theme of the colors of the high-contrast theme chosen in Windows, laid out like
+    /// [`Theme::HIGH_CONTRAST`].
+    ///
+    /// Regions which should stand out take the color of selected items, and the placement preview
+    /// the color of hyperlinks, so it differs from the focus outline under it.
+    pub fn from_system_colors(colors: &SystemColors) -> Theme {
+        Theme {
+            background: colors.window,
+            text: colors.window_text,
+            hint: colors.gray_text,
+            button: colors.window,
+            button_hovered: colors.window,
+            button_pressed: colors.window,
+            border: colors.window_text,
+            accent: colors.highlight,
+            monitor: colors.window_text,
+            work_area: colors.window,
+            preview_fill: colors.window,
+            preview_border: colors.hot_light,
+        }
+    }
+
+    /// Pick the theme chosen in the settings, and apply the overridden colors.
+    pub fn resolve(settings: &ThemeSettings, system: SystemAppearance) -> Theme {
+        let mut theme = match settings.preset {
+            ThemePreset::System if system.high_contrast => {
+                Theme::from_system_colors(&system.colors)
+            },
+            ThemePreset::System if system.dark => Theme::DARK,
+            ThemePreset::System | ThemePreset::Light => Theme::LIGHT,
+            ThemePreset::Dark => Theme::DARK,
+            ThemePreset::HighContrast => Theme::HIGH_CONTRAST,
+        };
+
+        theme.apply(&settings.colors);
+        theme
+    }
+
+    /// The theme following the system, without any settings.
+    ///
+    /// It's meant for windows which aren't configured, like the main window.
+    pub fn system() -> Theme {
+        Theme::resolve(&ThemeSettings::default(), SystemAppearance::current())
+    }
+
+    /// Get the color of the role.
+    pub fn color(&self, role: ThemeColor) -> Color {
+        *self.slot(role)
+    }
+
+    /// Replace colors of the palette.
+    pub fn apply(&mut self, colors: &BTreeMap<ThemeColor, Color>) {
+        for (role, color) in colors {
+            *self.slot_mut(*role) = *color;
+        }
+    }
+
+    fn slot(&self, role: ThemeColor) -> &Color {
+        match role {
+            ThemeColor::Background => &self.background,
+            ThemeColor::Text => &self.text,
+            ThemeColor::Hint => &self.hint,
+            ThemeColor::Button => &self.button,
+            ThemeColor::ButtonHovered => &self.button_hovered,
+            ThemeColor::ButtonPressed => &self.button_pressed,
+            ThemeColor::Border => &self.border,
+            ThemeColor::Accent => &self.accent,
+            ThemeColor::Monitor => &self.monitor,
+            ThemeColor::WorkArea => &self.work_area,
+            ThemeColor::PreviewFill => &self.preview_fill,
+            ThemeColor::PreviewBorder => &self.preview_border,
+        }
+    }
+
+    fn slot_mut(&mut self, role: ThemeColor) -> &mut Color {
+        match role {
+            ThemeColor::Background => &mut self.background,
+            ThemeColor::Text => &mut self.text,
+            ThemeColor::Hint => &mut self.hint,
+            ThemeColor::Button => &mut self.button,
+            ThemeColor::ButtonHovered => &mut self.button_hovered,
+            ThemeColor::ButtonPressed => &mut self.button_pressed,
+            ThemeColor::Border => &mut self.border,
+            ThemeColor::Accent => &mut self.accent,
+            ThemeColor::Monitor => &mut self.monitor,
+            ThemeColor::WorkArea => &mut self.work_area,
+            ThemeColor::PreviewFill => &mut self.preview_fill,
+            ThemeColor::PreviewBorder => &mut self.preview_border,
+        }
+    }
+}
+
+/// The light theme, which Windows uses unless told otherwise.
+impl Default for Theme {
+    fn default() -> Self {
+        Theme::LIGHT
+    }
+}
+
+/// How the system is set up to look.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SystemAppearance {
+    /// Whether applications should use the dark mode.
+    pub dark: bool,
+    /// Whether high contrast is turned on.
+    pub high_contrast: bool,
+    /// Colors of the high-contrast theme, only read while high contrast is turned on.
+    pub colors: SystemColors,
+}
+
+/// Colors of user interface elements, set by the high-contrast theme chosen in Windows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct SystemColors {
+    /// The background of windows, `COLOR_WINDOW`.
+    pub window: Color,
+    /// Text in windows, `COLOR_WINDOWTEXT`.
+    pub window_text: Color,
+    /// The background of selected items, `COLOR_HIGHLIGHT`.
+    pub highlight: Color,
+    /// Hyperlinks and hot-tracked items, `COLOR_HOTLIGHT`.
+    pub hot_light: Color,
+    /// Disabled text, `COLOR_GRAYTEXT`.
+    pub gray_text: Color,
+}
+
+impl SystemColors {
+    /// Ask the system for the colors.
+    pub fn current() -> SystemColors {
+        let color = |index: SYS_COLOR_INDEX| {
+            // The color is given as 0x00BBGGRR.
+            let [red, green, blue, _] = unsafe { GetSysColor(index) }.to_le_bytes();
+            Color::rgb(red, green, blue)
+        };
+
+        SystemColors {
+            window: color(COLOR_WINDOW),
+            window_text: color(COLOR_WINDOWTEXT),
+            highlight: color(COLOR_HIGHLIGHT),
+            hot_light: color(COLOR_HOTLIGHT),
+            gray_text: color(COLOR_GRAYTEXT),
+        }
+    }
+}
+
+impl SystemAppearance {
+    /// Ask the system how it looks now.
+    ///
+    /// There's no API for the app mode, so it's read from the registry, like other applications
+    /// do. Windows older than 10 don't have the value, so they are light.
+    pub fn current() -> SystemAppearance {
+        let mut light = 1u32;
+        let mut size = mem::size_of::<u32>() as u32;
+        let result = unsafe {
+            RegGetValueA(HKEY_CURRENT_USER,
+                         s!("Software\\Microsoft\\Windows\\CurrentVersion\\Themes\\Personalize"),
+                         s!("AppsUseLightTheme"), RRF_RT_REG_DWORD, None,
+                         Some(&mut light as *mut u32 as *mut c_void), Some(&mut size))
+        };
+
+        let mut contrast = HIGHCONTRASTA {
+            cbSize: mem::size_of::<HIGHCONTRASTA>() as u32,
+            ..Default::default()
+        };
+        let high_contrast = unsafe {
+            SystemParametersInfoA(SPI_GETHIGHCONTRAST, contrast.cbSize,
+                                  Some(&mut contrast as *mut HIGHCONTRASTA as *mut c_void),
+                                  SYSTEM_PARAMETERS_INFO_UPDATE_FLAGS(0))
+        };
+
+        let high_contrast = high_contrast.as_bool()
+            && contrast.dwFlags.0 & HCF_HIGHCONTRASTON.0 != 0;
+
+        SystemAppearance {
+            dark: result == ERROR_SUCCESS && light == 0,
+            high_contrast,
+            colors: if high_contrast { SystemColors::current() } else { SystemColors::default() },
+        }
+    }
+}
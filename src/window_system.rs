@@ -0,0 +1,111 @@
+//! This module contains an abstraction over the windows of other applications.
+//!
+//! Logic deciding which window to act on, and when, talks to the system through the
+//! [`WindowSystem`] trait. [`Win32WindowSystem`] implements it with the Windows API, while tests
+//! use fake implementations describing made-up windows, so the logic can be tested on any
+//! platform.
+
+use std::ffi::c_void;
+use std::mem;
+
+use windows::Win32::Foundation::HWND;
+use windows::Win32::Graphics::Dwm::{DWMWA_CLOAKED, DwmGetWindowAttribute};
+use windows::Win32::System::Threading::GetCurrentProcessId;
+use windows::Win32::UI::WindowsAndMessaging::{
+    GA_ROOT, GetAncestor, GetClassNameA, GetForegroundWindow, GetWindowThreadProcessId,
+    IsWindow, IsWindowVisible, SetForegroundWindow,
+};
+
+use crate::engine;
+use crate::geometry::Rect;
+
+/// What is known about a window.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct WindowInfo {
+    /// The name of the window class, e.g. `Shell_TrayWnd` for the taskbar.
+    pub class_name: String,
+    pub visible: bool,
+    /// Whether the window is a top-level one, rather than a child of another window.
+    pub top_level: bool,
+    /// Whether the window is hidden by the system, like windows on other virtual desktops.
+    pub cloaked: bool,
+    /// Whether the window belongs to Rectangular itself.
+    pub own: bool,
+}
+
+/// Access to the windows of the system.
+pub trait WindowSystem {
+    /// The foreground window, if there is one.
+    fn foreground(&self) -> Option<HWND>;
+
+    /// Describe the window, `None` if it doesn't exist anymore.
+    fn info(&self, window: HWND) -> Option<WindowInfo>;
+
+    /// Bring the window to the foreground. Returns `false` if the system refused to.
+    fn activate(&self, window: HWND) -> bool;
+
+    /// The full rectangle of the window, and its visible part, see
+    /// [`FrameInsets`](crate::engine::FrameInsets).
+    fn frame(&self, window: HWND) -> Option<(Rect, Rect)>;
+
+    /// The work area of the monitor the window is on, or the nearest one.
+    fn work_area(&self, window: HWND) -> Option<Rect>;
+
+    /// Work areas of all displays.
+    fn displays(&self) -> Vec<Rect>;
+}
+
+/// The [`WindowSystem`] of Windows.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Win32WindowSystem;
+
+impl WindowSystem for Win32WindowSystem {
+    fn foreground(&self) -> Option<HWND> {
+        let window = unsafe { GetForegroundWindow() };
+        (window.0 != 0).then_some(window)
+    }
+
+    fn info(&self, window: HWND) -> Option<WindowInfo> {
+        unsafe {
+            if window.0 == 0 || !IsWindow(window).as_bool() {
+                return None;
+            }
+
+            let mut class_name = [0u8; 256];
+            let length = GetClassNameA(window, &mut class_name).max(0) as usize;
+
+            let mut process = 0u32;
+            GetWindowThreadProcessId(window, Some(&mut process));
+
+            let mut cloaked = 0u32;
+            let cloaked = DwmGetWindowAttribute(window, DWMWA_CLOAKED,
+                                                &mut cloaked as *mut u32 as *mut c_void,
+                                                mem::size_of::<u32>() as u32)
+                .is_ok_and(|_| cloaked != 0);
+
+            Some(WindowInfo {
+                class_name: String::from_utf8_lossy(&class_name[..length]).into_owned(),
+                visible: IsWindowVisible(window).as_bool(),
+                top_level: GetAncestor(window, GA_ROOT) == window,
+                cloaked,
+                own: process == GetCurrentProcessId(),
+            })
+        }
+    }
+
+    fn activate(&self, window: HWND) -> bool {
+        unsafe { SetForegroundWindow(window).as_bool() }
+    }
+
+    fn frame(&self, window: HWND) -> Option<(Rect, Rect)> {
+        engine::window_rects(window)
+    }
+
+    fn work_area(&self, window: HWND) -> Option<Rect> {
+        engine::work_area_of(window)
+    }
+
+    fn displays(&self) -> Vec<Rect> {
+        engine::display_work_areas()
+    }
+}
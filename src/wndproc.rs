@@ -4,12 +4,13 @@ use std::cell::RefCell;
 use std::rc::Rc;
 
 use windows::Win32::Foundation::{HWND, LPARAM, LRESULT, WPARAM};
-use windows::Win32::Graphics::Gdi::{
-    BeginPaint, COLOR_WINDOWFRAME, EndPaint, FillRect, HBRUSH, PAINTSTRUCT,
-};
+use windows::Win32::Graphics::Gdi::{BeginPaint, EndPaint, PAINTSTRUCT};
 use windows::Win32::UI::WindowsAndMessaging::*;
 
 use crate::app_window::AppWindow;
+use crate::display_list::DisplayList;
+use crate::gdi_painter;
+use crate::theme::Theme;
 use crate::WINDOW_EXTRAS_MAIN;
 
 /// This is a window procedure, the glue between Windows and the application.
@@ -37,6 +38,9 @@ use crate::WINDOW_EXTRAS_MAIN;
 ///
 /// ### `WM_PAINT`
 /// It's a default painting implementation as suggested by [`WM_PAINT` documentation](https://learn.microsoft.com/en-us/windows/win32/gdi/wm-paint#example).
+/// Windows painting themselves do it in [`AppWindow::paint`], which is called between
+/// [`BeginPaint`] and [`EndPaint`]. Other windows are filled with the background of the
+/// [`Theme`] following the system.
 ///
 /// ### `WM_CLOSE`
 /// Triggers [`DestroyWindow`].
@@ -68,7 +72,15 @@ pub extern "system" fn wndproc(window: HWND, message: u32, wparam: WPARAM,
                 let mut ps = PAINTSTRUCT::default();
                 let hdc = BeginPaint(window, &mut ps);
 
-                FillRect(hdc, &ps.rcPaint, HBRUSH(COLOR_WINDOWFRAME.0 as i32 as isize));
+                let app_window = GetWindowLongPtrA(window, WINDOW_EXTRAS_MAIN)
+                    as *mut Rc<RefCell<dyn AppWindow>>;
+                let painted = !app_window.is_null()
+                    && (*app_window).borrow().paint(hdc, &ps.rcPaint);
+
+                if !painted {
+                    gdi_painter::paint(hdc, &ps.rcPaint.into(), &DisplayList::new(),
+                                       &Theme::system());
+                }
                 EndPaint(window, &ps);
                 LRESULT(0)
             }
@@ -0,0 +1,111 @@
+//! Helpers shared by the tests.
+//!
+//! Every test file is a crate of its own, which includes this module, and uses only some of the
+//! helpers.
+#![allow(dead_code)]
+
+pub mod raster;
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+
+use rectangular::geometry::Rect;
+use rectangular::window_system::{WindowInfo, WindowSystem};
+use windows::Win32::Foundation::HWND;
+
+/// Invisible borders of made-up windows, like the ones of Windows 10.
+pub const BORDER: i32 = 7;
+
+/// A made-up window, with its visible rectangle and the work area of its display.
+struct FakeWindow {
+    info: WindowInfo,
+    visible: Rect,
+    work_area: Rect,
+}
+
+/// Made-up windows keyed by their handles, on made-up displays.
+#[derive(Default)]
+pub struct FakeWindowSystem {
+    windows: RefCell<HashMap<isize, FakeWindow>>,
+    displays: Vec<Rect>,
+    foreground: Cell<Option<HWND>>,
+}
+
+impl FakeWindowSystem {
+    /// Create a system with the given displays and no windows.
+    pub fn with_displays(displays: &[Rect]) -> FakeWindowSystem {
+        FakeWindowSystem { displays: displays.to_vec(), ..Default::default() }
+    }
+
+    /// Add a window whose position doesn't matter.
+    pub fn add(&self, window: isize, info: WindowInfo) -> HWND {
+        self.add_window(window, info, Rect::default(), Rect::default())
+    }
+
+    /// Add an application window with the given visible rectangle, on a display with the given
+    /// work area.
+    pub fn add_at(&self, window: isize, visible: Rect, work_area: Rect) -> HWND {
+        self.add_window(window, app("Notepad"), visible, work_area)
+    }
+
+    pub fn move_to(&self, window: HWND, visible: Rect) {
+        self.windows.borrow_mut().get_mut(&window.0).unwrap().visible = visible;
+    }
+
+    pub fn update(&self, window: HWND, change: impl FnOnce(&mut WindowInfo)) {
+        change(&mut self.windows.borrow_mut().get_mut(&window.0).unwrap().info);
+    }
+
+    pub fn close(&self, window: HWND) {
+        self.windows.borrow_mut().remove(&window.0);
+    }
+
+    pub fn set_foreground(&self, window: Option<HWND>) {
+        self.foreground.set(window);
+    }
+
+    fn add_window(&self, window: isize, info: WindowInfo, visible: Rect, work_area: Rect) -> HWND {
+        self.windows.borrow_mut().insert(window, FakeWindow { info, visible, work_area });
+        HWND(window)
+    }
+}
+
+impl WindowSystem for FakeWindowSystem {
+    fn foreground(&self) -> Option<HWND> {
+        self.foreground.get()
+    }
+
+    fn info(&self, window: HWND) -> Option<WindowInfo> {
+        self.windows.borrow().get(&window.0).map(|window| window.info.clone())
+    }
+
+    fn activate(&self, window: HWND) -> bool {
+        self.windows.borrow().contains_key(&window.0)
+    }
+
+    fn frame(&self, window: HWND) -> Option<(Rect, Rect)> {
+        let visible = self.windows.borrow().get(&window.0)?.visible;
+        let full = Rect::new(visible.left - BORDER, visible.top, visible.right + BORDER,
+                             visible.bottom + BORDER);
+        Some((full, visible))
+    }
+
+    fn work_area(&self, window: HWND) -> Option<Rect> {
+        self.windows.borrow().get(&window.0).map(|window| window.work_area)
+    }
+
+    fn displays(&self) -> Vec<Rect> {
+        self.displays.clone()
+    }
+}
+
+/// Get the information of a regular application window of the given class.
+pub fn app(class_name: &str) -> WindowInfo {
+    WindowInfo {
+        class_name: class_name.to_string(),
+        visible: true,
+        top_level: true,
+        cloaked: false,
+        own: false,
+    }
+}
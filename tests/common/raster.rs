@@ -0,0 +1,205 @@
+//! This module renders a [`DisplayList`] in software.
+//!
+//! It's the backend used by tests: a window rendered to an [`Image`] can be saved as PNG and
+//! compared with a golden image kept in the repository, so changes of its looks are caught on any
+//! platform. Fills and outlines produce the same pixels as the
+//! [`gdi_painter`](rectangular::gdi_painter).
+//!
+//! Text is greeked: every character is drawn as a block, taller for capital letters and digits,
+//! and spaces are left empty. Fonts differ between machines, while the goal is catching changes of
+//! the layout and colors, which greeked text shows just as well.
+
+use png::{BitDepth, ColorType, Decoder, DecodingError, Encoder, Transformations};
+
+use rectangular::display_list::{Color, DisplayList, DrawCommand, TextAlign};
+use rectangular::geometry::Rect;
+
+/// The color marking pixels which differ in [`Image::diff`].
+pub const DIFF_COLOR: Color = Color::rgb(0xFF, 0x00, 0xFF);
+
+/// An image in memory.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Image {
+    width: u32,
+    height: u32,
+    pixels: Vec<Color>,
+}
+
+impl Image {
+    /// Create an image filled with the color.
+    pub fn new(width: u32, height: u32, color: Color) -> Image {
+        Image { width, height, pixels: vec![color; width as usize * height as usize] }
+    }
+
+    pub fn width(&self) -> u32 {
+        self.width
+    }
+
+    pub fn height(&self) -> u32 {
+        self.height
+    }
+
+    /// Get the color of a pixel, `None` outside of the image.
+    pub fn pixel(&self, x: i32, y: i32) -> Option<Color> {
+        self.bounds().contains(x, y).then(|| self.pixels[self.index(x, y)])
+    }
+
+    /// Fill the rectangle with the color, clipped to the image.
+    pub fn fill(&mut self, rect: &Rect, color: Color) {
+        let bounds = self.bounds();
+        let rect = Rect::new(rect.left.max(0), rect.top.max(0), rect.right.min(bounds.right),
+                             rect.bottom.min(bounds.bottom));
+
+        for y in rect.top..rect.bottom {
+            for x in rect.left..rect.right {
+                let index = self.index(x, y);
+                self.pixels[index] = color;
+            }
+        }
+    }
+
+    /// Draw all commands of the list.
+    pub fn draw(&mut self, list: &DisplayList) {
+        for command in list.commands() {
+            match command {
+                DrawCommand::Fill { rect, color } => self.fill(rect, *color),
+                DrawCommand::Stroke { rect, color, width } => self.stroke(rect, *color, *width),
+                DrawCommand::Text { rect, text, color, align } => {
+                    self.greek(rect, text, *color, *align)
+                },
+            }
+        }
+    }
+
+    /// Compare the image with another one.
+    ///
+    /// Returns the number of differing pixels, and an image showing them in [`DIFF_COLOR`] over a
+    /// faded copy of this image. Images of different sizes differ in all pixels of this one.
+    pub fn diff(&self, other: &Image) -> (usize, Image) {
+        let mut diff = Image::new(self.width, self.height, Color::default());
+        let same_size = (self.width, self.height) == (other.width, other.height);
+        let mut count = 0;
+
+        for y in 0..self.height as i32 {
+            for x in 0..self.width as i32 {
+                let index = self.index(x, y);
+                let pixel = self.pixels[index];
+
+                diff.pixels[index] = match other.pixel(x, y) {
+                    Some(other) if same_size && other == pixel => fade(pixel),
+                    _ => {
+                        count += 1;
+                        DIFF_COLOR
+                    },
+                };
+            }
+        }
+
+        (count, diff)
+    }
+
+    /// Encode the image as an 8-bit RGB PNG.
+    pub fn to_png(&self) -> Vec<u8> {
+        let rgb: Vec<u8> = self.pixels.iter()
+            .flat_map(|color| [color.red, color.green, color.blue])
+            .collect();
+
+        let mut data = Vec::new();
+        let mut encoder = Encoder::new(&mut data, self.width, self.height);
+        encoder.set_color(ColorType::Rgb);
+        encoder.set_depth(BitDepth::Eight);
+        encoder.write_header()
+            .and_then(|mut writer| writer.write_image_data(&rgb))
+            .expect("An image in memory should be encoded");
+
+        data
+    }
+
+    /// Decode an image from PNG. Transparency is dropped.
+    pub fn from_png(data: &[u8]) -> Result<Image, DecodingError> {
+        let mut decoder = Decoder::new(data);
+        decoder.set_transformations(Transformations::normalize_to_color8());
+        let mut reader = decoder.read_info()?;
+        let mut buffer = vec![0; reader.output_buffer_size()];
+        let frame = reader.next_frame(&mut buffer)?;
+
+        let pixels = buffer[..frame.buffer_size()].chunks(frame.color_type.samples())
+            .map(|pixel| match frame.color_type {
+                ColorType::Grayscale | ColorType::GrayscaleAlpha => {
+                    Color::rgb(pixel[0], pixel[0], pixel[0])
+                },
+                _ => Color::rgb(pixel[0], pixel[1], pixel[2]),
+            })
+            .collect();
+
+        Ok(Image { width: frame.width, height: frame.height, pixels })
+    }
+
+    fn bounds(&self) -> Rect {
+        Rect::new(0, 0, self.width as i32, self.height as i32)
+    }
+
+    fn index(&self, x: i32, y: i32) -> usize {
+        y as usize * self.width as usize + x as usize
+    }
+
+    /// Draw an outline along the inner edges of the rectangle, like the GDI painter does.
+    fn stroke(&mut self, rect: &Rect, color: Color, width: i32) {
+        let width = width.min(rect.width() / 2).min(rect.height() / 2);
+        if width <= 0 {
+            return;
+        }
+
+        let Rect { left, top, right, bottom } = *rect;
+        self.fill(&Rect::new(left, top, right, top + width), color);
+        self.fill(&Rect::new(left, bottom - width, right, bottom), color);
+        self.fill(&Rect::new(left, top + width, left + width, bottom - width), color);
+        self.fill(&Rect::new(right - width, top + width, right, bottom - width), color);
+    }
+
+    /// Draw greeked text, centered vertically in the rectangle, and clipped to it.
+    fn greek(&mut self, rect: &Rect, text: &str, color: Color, align: TextAlign) {
+        let height = rect.height();
+        let advance = (height * 2 / 5).max(2);
+        let capital = (height / 2).max(1);
+        let small = (height * 3 / 10).max(1);
+        let baseline = rect.top + (height + capital) / 2;
+
+        let width = text.chars().count() as i32 * advance;
+        let left = match align {
+            TextAlign::Left => rect.left,
+            TextAlign::Center => rect.left + (rect.width() - width) / 2,
+        };
+
+        for (index, character) in text.chars().enumerate() {
+            if character.is_whitespace() {
+                continue;
+            }
+
+            let glyph_height = match character.is_uppercase() || character.is_ascii_digit() {
+                true => capital,
+                false => small,
+            };
+            let glyph_left = left + index as i32 * advance;
+            let glyph = Rect::new(glyph_left, baseline - glyph_height,
+                                  glyph_left + advance - 1, baseline);
+
+            let clipped = Rect::new(glyph.left.max(rect.left), glyph.top.max(rect.top),
+                                    glyph.right.min(rect.right), glyph.bottom.min(rect.bottom));
+            self.fill(&clipped, color);
+        }
+    }
+}
+
+/// Render the display list on an image of the given size.
+pub fn render(list: &DisplayList, width: u32, height: u32) -> Image {
+    let mut image = Image::new(width, height, Color::default());
+    image.draw(list);
+    image
+}
+
+/// Blend the color with white, to make differences stand out in a diff image.
+fn fade(color: Color) -> Color {
+    let fade = |channel: u8| ((channel as u16 + 3 * 0xFF) / 4) as u8;
+    Color::rgb(fade(color.red), fade(color.green), fade(color.blue))
+}
@@ -0,0 +1,116 @@
+#[cfg(test)]
+mod test_autostart {
+    use std::cell::RefCell;
+    use std::io;
+    use std::path::{Path, PathBuf};
+
+    use rectangular::autostart::{
+        command_line, executable_of, set_enabled, state, toggle, AutostartState,
+        AutostartStore,
+    };
+
+    const EXECUTABLE: &str = r"C:\Program Files\Rectangular\rectangular.exe";
+
+    /// A store keeping the entry in memory, which can be made to fail.
+    #[derive(Default)]
+    struct MemoryStore {
+        entry: RefCell<Option<String>>,
+        broken: bool,
+    }
+
+    impl MemoryStore {
+        fn with_entry(command_line: &str) -> MemoryStore {
+            MemoryStore { entry: RefCell::new(Some(command_line.to_string())), broken: false }
+        }
+
+        fn check(&self) -> io::Result<()> {
+            match self.broken {
+                true => Err(io::Error::new(io::ErrorKind::PermissionDenied, "access denied")),
+                false => Ok(()),
+            }
+        }
+    }
+
+    impl AutostartStore for MemoryStore {
+        fn read(&self) -> io::Result<Option<String>> {
+            self.check()?;
+            Ok(self.entry.borrow().clone())
+        }
+
+        fn write(&self, command_line: &str) -> io::Result<()> {
+            self.check()?;
+            *self.entry.borrow_mut() = Some(command_line.to_string());
+            Ok(())
+        }
+
+        fn remove(&self) -> io::Result<()> {
+            self.check()?;
+            *self.entry.borrow_mut() = None;
+            Ok(())
+        }
+    }
+
+    fn executable() -> &'static Path {
+        Path::new(EXECUTABLE)
+    }
+
+    #[test]
+    fn command_lines_are_quoted() {
+        assert_eq!(command_line(executable()),
+                   "\"C:\\Program Files\\Rectangular\\rectangular.exe\"");
+        assert_eq!(executable_of(&command_line(executable())), executable());
+        assert_eq!(executable_of(r#"  "C:\Tools\rectangular.exe" --no-tray"#),
+                   Path::new(r"C:\Tools\rectangular.exe"));
+        assert_eq!(executable_of(r"C:\Tools\rectangular.exe --no-tray"),
+                   Path::new(r"C:\Tools\rectangular.exe"));
+        assert_eq!(executable_of(""), PathBuf::new());
+    }
+
+    #[test]
+    fn toggling_writes_and_removes_the_entry() {
+        let store = MemoryStore::default();
+        assert_eq!(state(&store, executable()).unwrap(), AutostartState::Disabled);
+
+        assert!(toggle(&store, executable()).unwrap());
+        assert_eq!(*store.entry.borrow(), Some(command_line(executable())));
+        assert_eq!(state(&store, executable()).unwrap(), AutostartState::Enabled);
+
+        assert!(!toggle(&store, executable()).unwrap());
+        assert_eq!(*store.entry.borrow(), None);
+
+        // Removing a missing entry is fine.
+        set_enabled(&store, executable(), false).unwrap();
+        assert_eq!(state(&store, executable()).unwrap(), AutostartState::Disabled);
+    }
+
+    #[test]
+    fn paths_are_compared_case_insensitively() {
+        let store = MemoryStore::with_entry(r#""c:\program files\RECTANGULAR\rectangular.exe""#);
+        assert_eq!(state(&store, executable()).unwrap(), AutostartState::Enabled);
+    }
+
+    #[test]
+    fn moved_executable_leaves_a_stale_entry() {
+        let store = MemoryStore::with_entry(r#""C:\Downloads\rectangular.exe""#);
+        let stale = state(&store, executable()).unwrap();
+
+        assert_eq!(stale, AutostartState::Stale {
+            registered: PathBuf::from(r"C:\Downloads\rectangular.exe"),
+        });
+        assert!(stale.is_enabled());
+
+        // The entry is left alone until starting at login is turned on again.
+        assert!(toggle(&store, executable()).unwrap());
+        assert_eq!(state(&store, executable()).unwrap(), AutostartState::Enabled);
+        assert_eq!(*store.entry.borrow(), Some(command_line(executable())));
+    }
+
+    #[test]
+    fn store_errors_are_passed_on() {
+        let store = MemoryStore { broken: true, ..MemoryStore::with_entry("") };
+
+        assert_eq!(toggle(&store, executable()).unwrap_err().kind(),
+                   io::ErrorKind::PermissionDenied);
+        assert!(state(&store, executable()).is_err());
+    }
+}
@@ -0,0 +1,173 @@
+#[cfg(test)]
+mod test_chord {
+    use std::time::{Duration, Instant};
+
+    use rectangular::action::WindowAction;
+    use rectangular::chord::{
+        CancelReason, ChordBinding, ChordConfigError, ChordEvent, ChordHint, ChordKey,
+        ChordMachine,
+    };
+
+    const TIMEOUT: Duration = Duration::from_millis(1000);
+
+    fn machine() -> ChordMachine {
+        ChordMachine::new(vec![
+            ChordBinding::new("h", WindowAction::LeftHalf),
+            ChordBinding::new("l", WindowAction::RightHalf),
+            ChordBinding::new("tl", WindowAction::TopLeft),
+            ChordBinding::new("tr", WindowAction::TopRight),
+        ], TIMEOUT).unwrap()
+    }
+
+    #[test]
+    fn keys_are_ignored_without_leader() {
+        let mut machine = machine();
+
+        assert_eq!(machine.key(ChordKey::Char('h'), Instant::now()), ChordEvent::Ignored);
+        assert!(!machine.is_active());
+    }
+
+    #[test]
+    fn single_key_sequence_completes() {
+        let mut machine = machine();
+        let now = Instant::now();
+
+        assert_eq!(machine.leader(now), ChordEvent::Started);
+        assert_eq!(machine.key(ChordKey::Char('h'), now),
+                   ChordEvent::Completed(WindowAction::LeftHalf));
+        assert!(!machine.is_active());
+    }
+
+    #[test]
+    fn multi_key_sequence_advances_then_completes() {
+        let mut machine = machine();
+        let now = Instant::now();
+
+        machine.leader(now);
+        assert_eq!(machine.key(ChordKey::Char('t'), now), ChordEvent::Advanced);
+        assert_eq!(machine.key(ChordKey::Char('r'), now),
+                   ChordEvent::Completed(WindowAction::TopRight));
+    }
+
+    #[test]
+    fn escape_cancels_sequence() {
+        let mut machine = machine();
+        let now = Instant::now();
+
+        machine.leader(now);
+        machine.key(ChordKey::Char('t'), now);
+        assert_eq!(machine.key(ChordKey::Escape, now),
+                   ChordEvent::Cancelled(CancelReason::Escape));
+        assert!(!machine.is_active());
+    }
+
+    #[test]
+    fn unknown_key_cancels_sequence() {
+        let mut machine = machine();
+        let now = Instant::now();
+
+        machine.leader(now);
+        assert_eq!(machine.key(ChordKey::Char('x'), now),
+                   ChordEvent::Cancelled(CancelReason::UnknownKey('x')));
+    }
+
+    #[test]
+    fn all_keys_cover_every_chord_key() {
+        let all = ChordKey::all();
+
+        assert_eq!(all.len(), 26 + 10 + 1);
+        for vk in 0..=u8::MAX as u16 {
+            if let Some(key) = ChordKey::from_vk(vk) {
+                assert!(all.contains(&key), "{key:?} is missing");
+                assert_eq!(key.vk(), vk);
+            }
+        }
+    }
+
+    #[test]
+    fn sequence_times_out() {
+        let mut machine = machine();
+        let start = Instant::now();
+
+        machine.leader(start);
+        assert_eq!(machine.tick(start + TIMEOUT / 2), ChordEvent::Ignored);
+        assert_eq!(machine.tick(start + TIMEOUT), ChordEvent::Cancelled(CancelReason::Timeout));
+        assert!(!machine.is_active());
+    }
+
+    #[test]
+    fn key_after_deadline_is_a_timeout() {
+        let mut machine = machine();
+        let start = Instant::now();
+
+        machine.leader(start);
+        assert_eq!(machine.key(ChordKey::Char('h'), start + TIMEOUT * 2),
+                   ChordEvent::Cancelled(CancelReason::Timeout));
+    }
+
+    #[test]
+    fn each_key_extends_deadline() {
+        let mut machine = machine();
+        let start = Instant::now();
+
+        machine.leader(start);
+        machine.key(ChordKey::Char('t'), start + TIMEOUT / 2);
+        assert_eq!(machine.deadline(), Some(start + TIMEOUT / 2 + TIMEOUT));
+        assert_eq!(machine.key(ChordKey::Char('l'), start + TIMEOUT),
+                   ChordEvent::Completed(WindowAction::TopLeft));
+    }
+
+    #[test]
+    fn next_keys_lists_possible_continuations() {
+        let mut machine = machine();
+        let now = Instant::now();
+
+        assert!(machine.next_keys().is_empty());
+
+        machine.leader(now);
+        assert_eq!(machine.next_keys(), vec![
+            ChordHint { key: 'h', action: Some(WindowAction::LeftHalf) },
+            ChordHint { key: 'l', action: Some(WindowAction::RightHalf) },
+            ChordHint { key: 't', action: None },
+        ]);
+
+        machine.key(ChordKey::Char('t'), now);
+        assert_eq!(machine.hint_text(), "l  Top Left\nr  Top Right");
+    }
+
+    #[test]
+    fn keys_are_case_insensitive() {
+        let mut machine = ChordMachine::new(vec![ChordBinding::new("H", WindowAction::LeftHalf)],
+                                            TIMEOUT).unwrap();
+        let now = Instant::now();
+
+        machine.leader(now);
+        assert_eq!(machine.key(ChordKey::from_vk(0x48).unwrap(), now),
+                   ChordEvent::Completed(WindowAction::LeftHalf));
+    }
+
+    #[test]
+    fn invalid_bindings_are_rejected() {
+        let prefix = ChordMachine::new(vec![
+            ChordBinding::new("t", WindowAction::TopHalf),
+            ChordBinding::new("tl", WindowAction::TopLeft),
+        ], TIMEOUT);
+        assert_eq!(prefix.err(), Some(ChordConfigError::Prefix {
+            shorter: "t".to_string(),
+            longer: "tl".to_string(),
+        }));
+
+        let duplicate = ChordMachine::new(vec![
+            ChordBinding::new("h", WindowAction::LeftHalf),
+            ChordBinding::new("H", WindowAction::RightHalf),
+        ], TIMEOUT);
+        assert_eq!(duplicate.err(), Some(ChordConfigError::Duplicate("h".to_string())));
+
+        let invalid = ChordMachine::new(vec![ChordBinding::new("h!", WindowAction::LeftHalf)],
+                                        TIMEOUT);
+        assert_eq!(invalid.err(), Some(ChordConfigError::InvalidKey {
+            keys: "h!".to_string(),
+            key: '!',
+        }));
+    }
+}
@@ -0,0 +1,146 @@
+#[cfg(test)]
+mod test_cli {
+    use std::ffi::OsString;
+    use std::fs;
+    use std::path::PathBuf;
+
+    use rectangular::action::WindowAction;
+    use rectangular::cli::{check_config, parse, CliError, ImportSource, RunOptions, Startup};
+    use rectangular::hotkey::Modifiers;
+    use rectangular::rectangle_import::RectangleImportOptions;
+
+    fn parsed(args: &[&str]) -> Result<Startup, CliError> {
+        parse(args.iter().map(OsString::from))
+    }
+
+    fn error(args: &[&str]) -> String {
+        parsed(args).expect_err("The command line should be rejected.").0
+    }
+
+    fn fixture(path: &str) -> PathBuf {
+        PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures").join(path)
+    }
+
+    #[test]
+    fn no_arguments_run_the_application() {
+        assert_eq!(parsed(&[]), Ok(Startup::Run(RunOptions::default())));
+    }
+
+    #[test]
+    fn run_options_are_collected() {
+        let expected = Startup::Run(RunOptions {
+            config: Some(PathBuf::from("my settings.toml")),
+            tray: false,
+        });
+
+        assert_eq!(parsed(&["--no-tray", "--config", "my settings.toml"]), Ok(expected.clone()));
+        assert_eq!(parsed(&["--config=my settings.toml", "--no-tray"]), Ok(expected));
+    }
+
+    #[test]
+    fn commands_are_recognized() {
+        assert_eq!(parsed(&["--help"]), Ok(Startup::PrintHelp));
+        assert_eq!(parsed(&["--print-default-config"]), Ok(Startup::PrintDefaultConfig));
+        assert_eq!(parsed(&["--print-schema"]), Ok(Startup::PrintSchema));
+        assert_eq!(parsed(&["--check-config", "a.toml"]),
+                   Ok(Startup::CheckConfig(PathBuf::from("a.toml"))));
+        assert_eq!(parsed(&["--upgrade-config", "a.toml"]),
+                   Ok(Startup::UpgradeConfig(PathBuf::from("a.toml"))));
+        assert_eq!(parsed(&["--import-fancyzones=layouts.json"]), Ok(Startup::Import {
+            source: ImportSource::FancyZones,
+            path: PathBuf::from("layouts.json"),
+        }));
+    }
+
+    #[test]
+    fn layout_names_the_group_and_the_action() {
+        assert_eq!(parsed(&["--config", "a.toml", "--layout", "Coding/Editor"]),
+                   Ok(Startup::ApplyLayout {
+                       group: "Coding".to_string(),
+                       action: "Editor".to_string(),
+                       config: Some(PathBuf::from("a.toml")),
+                   }));
+        assert_eq!(parsed(&["--layout=Work/Home/Zone 1"]), Ok(Startup::ApplyLayout {
+            group: "Work/Home".to_string(),
+            action: "Zone 1".to_string(),
+            config: None,
+        }));
+
+        for value in ["Coding", "Coding/", "/Editor"] {
+            assert!(error(&["--layout", value]).starts_with("invalid layout"), "{value}");
+        }
+        assert_eq!(error(&["--layout", "Coding/Editor", "--action", "center"]),
+                   "--action can't be combined with --layout");
+    }
+
+    #[test]
+    fn command_modifier_applies_to_rectangle_import() {
+        let import = |command| Ok(Startup::Import {
+            source: ImportSource::Rectangle(RectangleImportOptions { command }),
+            path: PathBuf::from("rectangle.json"),
+        });
+
+        assert_eq!(parsed(&["--import-rectangle", "rectangle.json"]), import(Modifiers::WIN));
+        assert_eq!(parsed(&["--command-modifier=ctrl", "--import-rectangle", "rectangle.json"]),
+                   import(Modifiers::CTRL));
+        assert_eq!(parsed(&["--import-rectangle", "rectangle.json", "--command-modifier", "alt"]),
+                   import(Modifiers::ALT));
+        assert!(error(&["--import-rectangle", "a.json", "--command-modifier", "shift"])
+            .starts_with("unknown command modifier \"shift\""));
+        assert_eq!(error(&["--import-fancyzones", "a.json", "--command-modifier", "alt"]),
+                   "--command-modifier can be given only with --import-rectangle");
+        assert_eq!(error(&["--command-modifier", "alt"]),
+                   "--command-modifier can be given only with --import-rectangle");
+    }
+
+    #[test]
+    fn action_uses_the_given_settings() {
+        assert_eq!(parsed(&["--action", "left-half"]),
+                   Ok(Startup::ApplyAction { action: WindowAction::LeftHalf, config: None }));
+        assert_eq!(parsed(&["--config", "a.toml", "--action", "center"]), Ok(Startup::ApplyAction {
+            action: WindowAction::Center,
+            config: Some(PathBuf::from("a.toml")),
+        }));
+    }
+
+    #[test]
+    fn invalid_command_lines_are_rejected() {
+        assert!(error(&["--action", "left"]).starts_with("unknown action \"left\""));
+        assert_eq!(error(&["--config"]), "--config requires a value");
+        assert_eq!(error(&["--no-tray=yes"]), "--no-tray doesn't take a value");
+        assert_eq!(error(&["--verbose"]), "unknown option --verbose");
+        assert_eq!(error(&["settings.toml"]), "unexpected argument settings.toml");
+        assert_eq!(error(&["--config", "a.toml", "--config", "b.toml"]),
+                   "--config can be given only once");
+    }
+
+    #[test]
+    fn options_must_apply_to_the_command() {
+        assert_eq!(error(&["--print-default-config", "--check-config", "a.toml"]),
+                   "--check-config can't be combined with --print-default-config");
+        assert_eq!(error(&["--action", "center", "--no-tray"]),
+                   "--no-tray can't be combined with --action");
+        assert_eq!(error(&["--config", "a.toml", "--check-config", "b.toml"]),
+                   "--config can't be combined with --check-config");
+    }
+
+    #[test]
+    fn check_reports_diagnostics_with_the_path() {
+        let valid = fixture("settings/v1.toml");
+        let report = check_config(&valid).unwrap();
+        assert!(report.ends_with(": the settings are valid"), "{report}");
+
+        let invalid = std::env::temp_dir().join("rectangular-test-cli-check.toml");
+        fs::write(&invalid, "[gaps]\ninner = -1\n").unwrap();
+        let report = check_config(&invalid).unwrap_err();
+        fs::remove_file(&invalid).unwrap();
+
+        assert!(report.starts_with(&format!("{}:2:9: error: ", invalid.display())), "{report}");
+    }
+
+    #[test]
+    fn check_fails_for_missing_files() {
+        let report = check_config(&fixture("settings/missing.toml")).unwrap_err();
+        assert!(report.contains("cannot read the file"), "{report}");
+    }
+}
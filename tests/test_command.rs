@@ -0,0 +1,140 @@
+#[cfg(test)]
+mod test_command {
+    use std::cell::RefCell;
+
+    use rectangular::action::WindowAction;
+    use rectangular::command::{dispatch, Command, CommandError, CommandTarget};
+    use rectangular::geometry::FracRect;
+    use rectangular::settings::{parse, Settings};
+
+    /// A target recording every call.
+    #[derive(Default)]
+    struct FakeTarget {
+        calls: RefCell<Vec<String>>,
+    }
+
+    impl FakeTarget {
+        fn record(&self, call: impl Into<String>) {
+            self.calls.borrow_mut().push(call.into());
+        }
+    }
+
+    impl CommandTarget for FakeTarget {
+        fn apply(&self, action: WindowAction, _settings: &Settings) {
+            self.record(format!("apply {action}"));
+        }
+
+        fn apply_fraction(&self, fraction: &FracRect, _settings: &Settings) {
+            self.record(format!("apply {} {} {} {}", fraction.x, fraction.y, fraction.width,
+                                fraction.height));
+        }
+
+        fn exit(&self) {
+            self.record("exit");
+        }
+
+        fn toggle_pause(&self) {
+            self.record("pause");
+        }
+
+        fn toggle_ignored_app(&self) {
+            self.record("ignore app");
+        }
+
+        fn toggle_start_at_login(&self) {
+            self.record("start at login");
+        }
+
+        fn open_settings(&self) {
+            self.record("open settings");
+        }
+
+        fn reload_settings(&self) {
+            self.record("reload settings");
+        }
+
+        fn show_about(&self) {
+            self.record("about");
+        }
+    }
+
+    fn with_layouts() -> Settings {
+        parse("[[custom-groups]]\nname = \"Coding\"\n\n\
+               [[custom-groups.actions]]\nname = \"Editor\"\n\
+               rect = { x = 0.25, y = 0.0, width = 0.5, height = 1.0 }\n\n\
+               [[custom-groups.actions]]\nname = \"Terminal\"\n\
+               rect = { x = 0.75, y = 0.0, width = 0.25, height = 1.0 }\n\n\
+               [[custom-groups]]\nname = \"Reading\"\n\n\
+               [[custom-groups.actions]]\nname = \"Editor\"\n\
+               rect = { x = 0.0, y = 0.0, width = 0.5, height = 1.0 }\n").unwrap().settings
+    }
+
+    fn layout(group: &str, action: &str) -> Command {
+        Command::ApplyLayout { group: group.to_string(), action: action.to_string() }
+    }
+
+    #[test]
+    fn commands_reach_the_target() {
+        let settings = Settings::default();
+        let target = FakeTarget::default();
+
+        for command in [Command::Apply(WindowAction::LeftHalf), Command::Pause,
+                        Command::IgnoreApp, Command::StartAtLogin, Command::OpenSettings,
+                        Command::ReloadSettings, Command::About, Command::Exit] {
+            assert_eq!(dispatch(&command, &settings, &target), Ok(()));
+        }
+
+        assert_eq!(*target.calls.borrow(), ["apply left-half", "pause", "ignore app",
+                                            "start at login", "open settings", "reload settings",
+                                            "about", "exit"]);
+    }
+
+    #[test]
+    fn layouts_are_found_by_name() {
+        let settings = with_layouts();
+        let target = FakeTarget::default();
+
+        assert_eq!(dispatch(&layout("Coding", "Editor"), &settings, &target), Ok(()));
+        assert_eq!(dispatch(&layout("Reading", "Editor"), &settings, &target), Ok(()));
+        assert_eq!(*target.calls.borrow(), ["apply 0.25 0 0.5 1", "apply 0 0 0.5 1"]);
+
+        assert_eq!(dispatch(&layout("Reading", "Terminal"), &settings, &target),
+                   Err(CommandError::UnknownLayout { group: "Reading".to_string(),
+                                                     action: "Terminal".to_string() }));
+        assert_eq!(target.calls.borrow().len(), 2);
+    }
+
+    #[test]
+    fn menu_ids_map_back_to_commands() {
+        let settings = with_layouts();
+        let commands = [Command::Exit, Command::Pause, Command::IgnoreApp, Command::StartAtLogin,
+                        Command::OpenSettings, Command::ReloadSettings, Command::About];
+        let commands = commands.into_iter()
+            .chain(WindowAction::ALL.into_iter().map(Command::Apply))
+            .chain([layout("Coding", "Editor"), layout("Coding", "Terminal"),
+                    layout("Reading", "Editor")]);
+
+        let mut ids = Vec::new();
+        for command in commands {
+            let id = command.menu_id(&settings).unwrap();
+            assert_ne!(id, 0);
+            assert_eq!(Command::from_menu_id(id, &settings), Some(command));
+            ids.push(id);
+        }
+
+        ids.sort();
+        ids.dedup();
+        assert_eq!(ids.len(), 7 + WindowAction::ALL.len() + 3);
+    }
+
+    #[test]
+    fn unknown_menu_ids_give_nothing() {
+        let settings = with_layouts();
+
+        assert_eq!(Command::from_menu_id(0, &settings), None);
+        assert_eq!(Command::from_menu_id(0x100 + WindowAction::ALL.len(), &settings), None);
+        assert_eq!(Command::from_menu_id(0x1000 + 3, &settings), None);
+        assert_eq!(layout("Reading", "Terminal").menu_id(&settings), None);
+        assert_eq!(layout("Coding", "Editor").menu_id(&Settings::default()), None);
+    }
+}
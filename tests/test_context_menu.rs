@@ -0,0 +1,261 @@
+#[cfg(test)]
+mod test_context_menu {
+    use std::path::PathBuf;
+
+    use rectangular::action::WindowAction;
+    use rectangular::autostart::AutostartState;
+    use rectangular::command::Command;
+    use rectangular::context_menu::{
+        menu_text, model, MenuError, MenuItem, MenuModel, MenuNode, MenuState,
+    };
+    use rectangular::control_center_layout::ROWS;
+    use rectangular::keyboard_layout::{LayoutTable, US_LAYOUT};
+    use rectangular::settings::{parse, Settings};
+
+    fn layout() -> MenuModel {
+        let mut sizes = MenuModel::new();
+        sizes.item(MenuItem { checked: true, radio: true, ..MenuItem::new(3, "Small") });
+        sizes.item(MenuItem { radio: true, ..MenuItem::new(4, "Large") });
+
+        let mut menu = MenuModel::new();
+        menu.item(MenuItem { shortcut: Some("Ctrl+Alt+P".to_string()),
+                             ..MenuItem::new(2, "Pause") });
+        menu.submenu("Size", sizes);
+        menu.separator();
+        menu.item(MenuItem { enabled: false, ..MenuItem::new(5, "Nothing to do") });
+
+        menu
+    }
+
+    #[test]
+    fn models_keep_the_order_of_entries() {
+        let menu = layout();
+
+        assert_eq!(menu.nodes().len(), 4);
+        assert!(matches!(&menu.nodes()[1],
+                         MenuNode::Submenu { label, model } if label == "Size"
+                             && model.nodes().len() == 2));
+        assert_eq!(menu.nodes()[2], MenuNode::Separator);
+        assert_eq!(menu.items().iter().map(|item| item.command).collect::<Vec<_>>(),
+                   [2, 3, 4, 5]);
+    }
+
+    #[test]
+    fn items_are_found_in_submenus() {
+        let menu = layout();
+
+        assert_eq!(menu.find(3).map(|item| item.label.as_str()), Some("Small"));
+        assert!(menu.find(3).unwrap().checked);
+        assert!(!menu.find(5).unwrap().enabled);
+        assert_eq!(menu.find(6), None);
+    }
+
+    #[test]
+    fn shortcuts_are_separated_by_a_tab() {
+        let menu = layout();
+
+        assert_eq!(menu.find(2).unwrap().text(), "Pause\tCtrl+Alt+P");
+        assert_eq!(menu.find(4).unwrap().text(), "Large");
+    }
+
+    #[test]
+    fn menu_text_is_utf16_with_ampersands_shown() {
+        assert_eq!(menu_text("Ignore Notepad++.exe"),
+                   "Ignore Notepad++.exe\0".encode_utf16().collect::<Vec<u16>>());
+        assert_eq!(menu_text("Zoom & Teams\tCtrl+Alt+Z"),
+                   "Zoom && Teams\tCtrl+Alt+Z\0".encode_utf16().collect::<Vec<u16>>());
+        assert_eq!(menu_text("Größe"), [0x47, 0x72, 0xF6, 0xDF, 0x65, 0]);
+    }
+
+    #[test]
+    fn commands_must_be_unique_and_not_zero() {
+        assert_eq!(layout().invalid_command(), None);
+        assert_eq!(MenuModel::new().invalid_command(), None);
+
+        let mut nested = MenuModel::new();
+        nested.item(MenuItem::new(2, "Again"));
+        let mut menu = layout();
+        menu.submenu("More", nested);
+        assert_eq!(menu.invalid_command(), Some(2));
+        assert_eq!(menu.validate(), Err(MenuError::InvalidCommand(2)));
+
+        let mut menu = MenuModel::new();
+        menu.item(MenuItem::new(0, "Nothing"));
+        assert_eq!(menu.invalid_command(), Some(0));
+        assert_eq!(menu.validate(), Err(MenuError::InvalidCommand(0)));
+        assert_eq!(layout().validate(), Ok(()));
+    }
+
+    #[test]
+    fn application_menu_is_valid() {
+        let settings = Settings::default();
+        let menu = model(&settings, &MenuState::default(), &US_LAYOUT);
+
+        assert_eq!(menu.invalid_command(), None);
+        assert_eq!(menu.find(Command::Exit.menu_id(&settings).unwrap())
+                       .map(|item| item.label.as_str()),
+                   Some("Exit"));
+
+        // Every item chooses a command.
+        for item in menu.items() {
+            assert!(Command::from_menu_id(item.command, &settings).is_some(), "{item:?}");
+        }
+    }
+
+    #[test]
+    fn pause_item_shows_the_state_and_the_shortcut() {
+        let settings = Settings::default();
+        let pause = Command::Pause.menu_id(&settings).unwrap();
+
+        let running = model(&settings, &MenuState::default(), &US_LAYOUT);
+        assert!(!running.find(pause).unwrap().checked);
+        assert_eq!(running.find(pause).unwrap().text(), "Pause\tCtrl+Alt+P");
+
+        let state = MenuState { paused: true, ..Default::default() };
+        let paused = model(&settings, &state, &US_LAYOUT);
+        assert!(paused.find(pause).unwrap().checked);
+        assert_ne!(running, paused);
+    }
+
+    #[test]
+    fn ignore_item_names_the_app() {
+        let settings = Settings::default();
+        let ignore = Command::IgnoreApp.menu_id(&settings).unwrap();
+
+        let nothing = model(&settings, &MenuState::default(), &US_LAYOUT);
+        assert!(!nothing.find(ignore).unwrap().enabled);
+
+        let state = MenuState { app: Some("mstsc.exe".to_string()), ..Default::default() };
+        let menu = model(&settings, &state, &US_LAYOUT);
+        assert_eq!(menu.find(ignore).unwrap().label, "Ignore mstsc.exe");
+        assert!(menu.find(ignore).unwrap().enabled);
+        assert!(!menu.find(ignore).unwrap().checked);
+
+        let menu = model(&settings, &MenuState { app_ignored: true, ..state }, &US_LAYOUT);
+        assert!(menu.find(ignore).unwrap().checked);
+    }
+
+    #[test]
+    fn start_at_login_is_checked_when_enabled() {
+        let settings = Settings::default();
+        let start_at_login = Command::StartAtLogin.menu_id(&settings).unwrap();
+
+        let menu = model(&settings, &MenuState::default(), &US_LAYOUT);
+        assert!(!menu.find(start_at_login).unwrap().checked);
+
+        let menu = model(&settings, &MenuState {
+            start_at_login: AutostartState::Enabled,
+            ..Default::default()
+        }, &US_LAYOUT);
+        assert!(menu.find(start_at_login).unwrap().checked);
+        assert_eq!(menu.find(start_at_login).unwrap().label, "Start at Login");
+    }
+
+    #[test]
+    fn stale_start_at_login_is_shown_as_moved() {
+        let settings = Settings::default();
+        let start_at_login = Command::StartAtLogin.menu_id(&settings).unwrap();
+
+        let stale = AutostartState::Stale { registered: PathBuf::from(r"C:\Downloads\a.exe") };
+        let state = MenuState { start_at_login: stale, ..Default::default() };
+        let menu = model(&settings, &state, &US_LAYOUT);
+        let item = menu.find(start_at_login).unwrap();
+        assert!(!item.checked);
+        assert_eq!(item.label, "Start at Login (moved)");
+    }
+
+    #[test]
+    fn arrange_submenu_lists_actions_with_shortcuts() {
+        let settings = parse("[hotkeys]\nmaximize = \"\"\ncenter = \"Ctrl+Shift+C\"")
+            .unwrap().settings;
+        let menu = model(&settings, &MenuState::default(), &US_LAYOUT);
+
+        let Some(MenuNode::Submenu { label, model: arrange }) = menu.nodes().first() else {
+            panic!("The menu should start with a submenu: {menu:?}");
+        };
+        assert_eq!(label, "Arrange");
+
+        // Every action is there once, in the rows of the Control Center.
+        let commands: Vec<usize> = arrange.items().iter().map(|item| item.command).collect();
+        let expected: Vec<usize> = ROWS.iter()
+            .flat_map(|(_, actions)| actions.iter())
+            .map(|action| Command::Apply(*action).menu_id(&settings).unwrap())
+            .collect();
+        assert_eq!(commands, expected);
+        assert_eq!(commands.len(), WindowAction::ALL.len());
+        assert_eq!(arrange.nodes().iter().filter(|node| **node == MenuNode::Separator).count(),
+                   ROWS.len() - 1);
+
+        let find = |action| arrange.find(Command::Apply(action).menu_id(&settings).unwrap());
+        assert_eq!(find(WindowAction::LeftHalf).unwrap().text(), "Left Half\tCtrl+Alt+Left");
+        assert_eq!(find(WindowAction::Center).unwrap().text(), "Center\tCtrl+Shift+C");
+        assert_eq!(find(WindowAction::Maximize).unwrap().text(), "Maximize");
+    }
+
+    #[test]
+    fn custom_groups_have_submenus() {
+        let settings = parse("[[custom-groups]]\nname = \"Coding\"\n\n\
+                              [[custom-groups.actions]]\nname = \"Editor\"\n\
+                              rect = { x = 0.25, y = 0.0, width = 0.5, height = 1.0 }\n\n\
+                              [[custom-groups.actions]]\nname = \"Terminal\"\n\
+                              rect = { x = 0.75, y = 0.0, width = 0.25, height = 1.0 }\n\n\
+                              [[custom-groups]]\nname = \"Empty\"\nactions = []\n")
+            .unwrap().settings;
+        let menu = model(&settings, &MenuState::default(), &US_LAYOUT);
+
+        // Groups without actions have nothing to show.
+        let Some(MenuNode::Submenu { label, model: coding }) = menu.nodes().get(1) else {
+            panic!("The group should follow the Arrange submenu: {menu:?}");
+        };
+        assert_eq!(label, "Coding");
+        assert_eq!(menu.nodes()[2], MenuNode::Separator);
+        assert_eq!(menu.invalid_command(), None);
+
+        let labels: Vec<&str> = coding.items().iter().map(|item| item.label.as_str()).collect();
+        assert_eq!(labels, ["Editor", "Terminal"]);
+
+        let terminal = Command::ApplyLayout { group: "Coding".to_string(),
+                                              action: "Terminal".to_string() };
+        let id = coding.items()[1].command;
+        assert_eq!(Command::from_menu_id(id, &settings), Some(terminal));
+
+        // Custom actions are found by name, so one repeated in a group can't be told apart.
+        let mut repeated = settings.clone();
+        let editor = repeated.custom_groups[0].actions[0].clone();
+        repeated.custom_groups[0].actions.push(editor);
+        let menu = model(&repeated, &MenuState::default(), &US_LAYOUT);
+        let editor = coding.items()[0].command;
+        assert_eq!(menu.validate(), Err(MenuError::InvalidCommand(editor)));
+    }
+
+    #[test]
+    fn arrange_submenu_follows_bindings() {
+        let settings = Settings::default();
+        let rebound = parse("[hotkeys]\nleft-half = \"Ctrl+Shift+H\"").unwrap().settings;
+
+        assert_ne!(model(&settings, &MenuState::default(), &US_LAYOUT),
+                   model(&rebound, &MenuState::default(), &US_LAYOUT));
+        let gaps = parse("[gaps]\ninner = 8").unwrap().settings;
+        assert_eq!(model(&settings, &MenuState::default(), &US_LAYOUT),
+                   model(&gaps, &MenuState::default(), &US_LAYOUT));
+    }
+
+    #[test]
+    fn shortcuts_are_labeled_for_the_keyboard_layout() {
+        let settings = parse("[hotkeys]
+center = \"Ctrl+Alt+sc:0x1B\"\n\n\
+                              [pause]\nhotkey = \"Ctrl+Alt+[\"")
+            .unwrap().settings;
+        let german = LayoutTable::find(0x0407).unwrap();
+        let pause = Command::Pause.menu_id(&settings).unwrap();
+        let center = Command::Apply(WindowAction::Center).menu_id(&settings).unwrap();
+
+        let menu = model(&settings, &MenuState::default(), &US_LAYOUT);
+        assert_eq!(menu.find(pause).unwrap().text(), "Pause\tCtrl+Alt+[");
+        assert_eq!(menu.find(center).unwrap().text(), "Center\tCtrl+Alt+]");
+
+        let menu = model(&settings, &MenuState::default(), german);
+        assert_eq!(menu.find(pause).unwrap().text(), "Pause\tCtrl+Alt+ß");
+        assert_eq!(menu.find(center).unwrap().text(), "Center\tCtrl+Alt++");
+    }
+}
@@ -0,0 +1,186 @@
+#[cfg(test)]
+mod test_control_center_focus {
+    use rectangular::action::WindowAction::{self, *};
+    use rectangular::control_center_focus::{mnemonic, Direction, Focus, Key, KeyOutcome};
+    use rectangular::control_center_layout::ControlCenterLayout;
+
+    fn layout() -> ControlCenterLayout {
+        let (width, height) = ControlCenterLayout::preferred_size(96);
+        ControlCenterLayout::new(width, height, 96)
+    }
+
+    /// Press arrow keys, returning the focused action after every one.
+    fn walk(focus: &mut Focus, directions: &[Direction]) -> Vec<Option<WindowAction>> {
+        let layout = layout();
+        directions.iter()
+            .map(|direction| {
+                focus.key(Key::Arrow(*direction), &layout);
+                focus.focused()
+            })
+            .collect()
+    }
+
+    /// Focus the action by tabbing to it.
+    fn focused_on(action: WindowAction) -> Focus {
+        let layout = layout();
+        let mut focus = Focus::default();
+        while focus.focused() != Some(action) {
+            focus.key(Key::Tab { backwards: false }, &layout);
+        }
+        focus
+    }
+
+    #[test]
+    fn first_arrow_focuses_the_first_button() {
+        let mut focus = Focus::default();
+        assert_eq!(focus.focused(), None);
+
+        assert_eq!(focus.key(Key::Arrow(Direction::Down), &layout()), KeyOutcome::FocusMoved);
+        assert_eq!(focus.focused(), Some(LeftHalf));
+    }
+
+    #[test]
+    fn left_and_right_stop_at_the_ends_of_a_row() {
+        let mut focus = focused_on(LeftHalf);
+        use Direction::*;
+
+        assert_eq!(walk(&mut focus, &[Left, Right, Right, Right, Right]),
+                   [Some(LeftHalf), Some(RightHalf), Some(TopHalf), Some(BottomHalf),
+                    Some(BottomHalf)]);
+        assert_eq!(focus.key(Key::Arrow(Right), &layout()), KeyOutcome::Ignored);
+    }
+
+    #[test]
+    fn up_and_down_pick_the_closest_button() {
+        use Direction::*;
+
+        // From the second of four buttons to the second of five, whose centers are the closest.
+        let mut focus = focused_on(RightHalf);
+        assert_eq!(walk(&mut focus, &[Down, Down]), [Some(TopRight), Some(CenterThird)]);
+
+        // Five buttons onto two: the middle one is equally close to both, the left one wins.
+        let mut focus = focused_on(CenterThird);
+        assert_eq!(walk(&mut focus, &[Down]), [Some(Maximize)]);
+
+        let mut focus = focused_on(LastTwoThirds);
+        assert_eq!(walk(&mut focus, &[Down, Down, Up, Up]),
+                   [Some(Center), Some(Center), Some(LastTwoThirds), Some(BottomRight)]);
+    }
+
+    #[test]
+    fn column_is_kept_while_moving_vertically() {
+        use Direction::*;
+
+        // Going through the wide buttons of the last row leads back to the same third.
+        let mut focus = focused_on(FirstTwoThirds);
+        assert_eq!(walk(&mut focus, &[Down, Up]), [Some(Center), Some(FirstTwoThirds)]);
+
+        let mut focus = focused_on(FirstThird);
+        assert_eq!(walk(&mut focus, &[Down, Up, Up, Up]),
+                   [Some(Maximize), Some(FirstThird), Some(TopLeft), Some(LeftHalf)]);
+
+        // Moving sideways starts a new column, in the middle of the right half.
+        let mut focus = focused_on(FirstThird);
+        assert_eq!(walk(&mut focus, &[Down, Right, Up]),
+                   [Some(Maximize), Some(Center), Some(FirstTwoThirds)]);
+    }
+
+    #[test]
+    fn tab_follows_reading_order_and_wraps() {
+        let layout = layout();
+        let mut focus = Focus::default();
+
+        let order: Vec<WindowAction> = (0..WindowAction::ALL.len())
+            .map(|_| {
+                focus.key(Key::Tab { backwards: false }, &layout);
+                focus.focused().unwrap()
+            })
+            .collect();
+        assert_eq!(order, WindowAction::ALL);
+
+        focus.key(Key::Tab { backwards: false }, &layout);
+        assert_eq!(focus.focused(), Some(LeftHalf));
+        focus.key(Key::Tab { backwards: true }, &layout);
+        assert_eq!(focus.focused(), Some(Center));
+    }
+
+    #[test]
+    fn enter_applies_the_focused_action() {
+        let layout = layout();
+        let mut focus = Focus::default();
+        assert_eq!(focus.key(Key::Enter, &layout), KeyOutcome::Ignored);
+
+        let mut focus = focused_on(TopLeft);
+        assert_eq!(focus.key(Key::Enter, &layout), KeyOutcome::Apply(TopLeft));
+        assert_eq!(focus.key(Key::Space, &layout), KeyOutcome::Apply(TopLeft));
+    }
+
+    #[test]
+    fn escape_hides() {
+        assert_eq!(Focus::default().key(Key::Escape, &layout()), KeyOutcome::Hide);
+    }
+
+    #[test]
+    fn mnemonics_apply_actions_directly() {
+        let layout = layout();
+        let mut focus = Focus::default();
+
+        for (key, action) in [('h', LeftHalf), ('L', RightHalf), ('t', TopHalf),
+                              ('b', BottomHalf), ('1', FirstThird), ('2', CenterThird),
+                              ('3', LastThird), ('m', Maximize), ('c', Center)] {
+            assert_eq!(mnemonic(key), Some(action));
+            assert_eq!(focus.key(Key::Char(key), &layout), KeyOutcome::Apply(action));
+            assert_eq!(focus.focused(), Some(action));
+        }
+
+        for key in ['x', '4', ' ', '\r', '\u{1b}'] {
+            assert_eq!(focus.key(Key::Char(key), &layout), KeyOutcome::Ignored, "{key:?}");
+        }
+    }
+
+    #[test]
+    fn reset_removes_the_focus() {
+        let mut focus = focused_on(Maximize);
+        focus.reset();
+
+        assert_eq!(focus, Focus::default());
+    }
+
+    #[test]
+    fn empty_layout_ignores_navigation() {
+        let layout = ControlCenterLayout::default();
+        let mut focus = Focus::default();
+
+        assert_eq!(focus.key(Key::Arrow(Direction::Down), &layout), KeyOutcome::Ignored);
+        assert_eq!(focus.key(Key::Tab { backwards: true }, &layout), KeyOutcome::Ignored);
+        assert_eq!(focus.key(Key::Char('h'), &layout), KeyOutcome::Ignored);
+    }
+
+    #[test]
+    fn up_and_down_go_through_wrapped_lines() {
+        use Direction::*;
+
+        // Thirds wrap onto two lines, three buttons and two.
+        let layout = ControlCenterLayout::new(200, 300, 96);
+        let mut focus = Focus::default();
+        while focus.focused() != Some(LastThird) {
+            focus.key(Key::Tab { backwards: false }, &layout);
+        }
+
+        let mut walk = |directions: &[Direction]| -> Vec<Option<WindowAction>> {
+            directions.iter()
+                .map(|direction| {
+                    focus.key(Key::Arrow(*direction), &layout);
+                    focus.focused()
+                })
+                .collect()
+        };
+
+        // The last column of the short line is empty, so the closest button is the one on its
+        // left. Going back up returns to the column it started in.
+        assert_eq!(walk(&[Down, Up, Up]),
+                   [Some(LastTwoThirds), Some(LastThird), Some(BottomRight)]);
+        assert_eq!(walk(&[Left, Down, Down, Right]),
+                   [Some(BottomLeft), Some(CenterThird), Some(LastTwoThirds), Some(LastTwoThirds)]);
+    }
+}
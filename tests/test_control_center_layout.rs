@@ -0,0 +1,209 @@
+#[cfg(test)]
+mod test_control_center_layout {
+    use rectangular::action::WindowAction;
+    use rectangular::control_center_layout::{
+        scale, unscale, Category, ControlCenterLayout, MIN_BUTTON_HEIGHT, MIN_BUTTON_WIDTH,
+        MIN_COLUMNS, ROWS,
+    };
+
+    #[test]
+    fn every_action_has_exactly_one_button() {
+        let layout = ControlCenterLayout::new(400, 300, 96);
+
+        for action in WindowAction::ALL {
+            assert_eq!(layout.buttons().filter(|button| button.action == action).count(), 1,
+                       "{action:?}");
+        }
+    }
+
+    #[test]
+    fn rows_follow_categories_in_order() {
+        let layout = ControlCenterLayout::new(400, 300, 96);
+        let categories: Vec<Category> = layout.rows().iter().map(|row| row.category).collect();
+
+        assert_eq!(categories, [Category::Halves, Category::Quarters, Category::Thirds,
+                                Category::Other]);
+
+        for (row, (_, actions)) in layout.rows().iter().zip(ROWS) {
+            let row_actions: Vec<WindowAction> = row.buttons.iter()
+                .map(|button| button.action)
+                .collect();
+            assert_eq!(row_actions, actions);
+        }
+    }
+
+    #[test]
+    fn buttons_fill_rows_without_overlapping() {
+        let layout = ControlCenterLayout::new(401, 300, 96);
+
+        for row in layout.rows() {
+            let first = row.buttons.first().unwrap();
+            let last = row.buttons.last().unwrap();
+            assert_eq!(first.rect.left, 8);
+            assert_eq!(last.rect.right, 401 - 8);
+
+            for pair in row.buttons.windows(2) {
+                assert_eq!(pair[1].rect.left - pair[0].rect.right, 6);
+                assert_eq!(pair[0].rect.top, pair[1].rect.top);
+                assert_eq!(pair[0].rect.bottom, pair[1].rect.bottom);
+            }
+
+            assert_eq!(row.caption.bottom, first.rect.top);
+        }
+
+        let rows = layout.rows();
+        assert_eq!(rows[0].caption.top, 8);
+        assert_eq!(rows[3].buttons[0].rect.bottom, 300 - 8);
+
+        for pair in rows.windows(2) {
+            assert_eq!(pair[1].caption.top - pair[0].buttons[0].rect.bottom, 6);
+        }
+    }
+
+    #[test]
+    fn preferred_size_gives_preferred_buttons() {
+        for dpi in [96, 144, 192] {
+            let (width, height) = ControlCenterLayout::preferred_size(dpi);
+            let layout = ControlCenterLayout::new(width, height, dpi);
+
+            // Thirds make the longest row.
+            let third = layout.button(WindowAction::FirstThird).unwrap().rect;
+            assert!((third.width() - scale(60, dpi)).abs() <= 1, "{dpi}: {third:?}");
+            assert!((third.height() - scale(40, dpi)).abs() <= 1, "{dpi}: {third:?}");
+        }
+
+        assert_eq!(ControlCenterLayout::preferred_size(96), (340, 266));
+        assert_eq!(ControlCenterLayout::preferred_size(192), (2 * 340, 2 * 266));
+    }
+
+    #[test]
+    fn metrics_scale_with_dpi() {
+        assert_eq!(scale(8, 96), 8);
+        assert_eq!(scale(8, 120), 10);
+        assert_eq!(scale(6, 144), 9);
+        assert_eq!(scale(5, 120), 6);
+
+        let layout = ControlCenterLayout::new(800, 600, 192);
+        assert_eq!(layout.rows()[0].caption.top, 16);
+        assert_eq!(layout.rows()[0].caption.height(), 36);
+    }
+
+    #[test]
+    fn hit_test_finds_buttons() {
+        let layout = ControlCenterLayout::new(400, 300, 96);
+
+        for button in layout.buttons() {
+            let rect = button.rect;
+            assert_eq!(layout.hit_test(rect.left, rect.top), Some(button.action));
+            assert_eq!(layout.hit_test(rect.right - 1, rect.bottom - 1), Some(button.action));
+        }
+
+        let left = layout.button(WindowAction::LeftHalf).unwrap().rect;
+        let right = layout.button(WindowAction::RightHalf).unwrap().rect;
+
+        // The spacing between buttons, a caption, the padding, and outside the window.
+        assert_eq!(layout.hit_test(left.right, left.top), None);
+        assert_eq!(layout.hit_test(right.left - 1, left.top), None);
+        assert_eq!(layout.hit_test(left.left, left.top - 1), None);
+        assert_eq!(layout.hit_test(2, 2), None);
+        assert_eq!(layout.hit_test(-10, 50), None);
+        assert_eq!(layout.hit_test(500, 50), None);
+    }
+
+    #[test]
+    fn tiny_area_gives_empty_buttons() {
+        for (width, height) in [(0, 0), (10, 10), (30, 400)] {
+            let layout = ControlCenterLayout::new(width, height, 96);
+
+            for button in layout.buttons() {
+                assert!(button.rect.width() >= 0, "{width}x{height}: {button:?}");
+                assert!(button.rect.height() >= 0, "{width}x{height}: {button:?}");
+            }
+        }
+
+        assert_eq!(ControlCenterLayout::new(10, 10, 96).hit_test(5, 5), None);
+    }
+
+    /// Count lines of every row.
+    fn lines(layout: &ControlCenterLayout) -> Vec<usize> {
+        layout.rows().iter().map(|row| row.lines().count()).collect()
+    }
+
+    #[test]
+    fn wide_window_keeps_rows_on_single_lines() {
+        let (width, height) = ControlCenterLayout::preferred_size(96);
+        assert_eq!(lines(&ControlCenterLayout::new(width, height, 96)), [1, 1, 1, 1]);
+        assert_eq!(lines(&ControlCenterLayout::new(2000, 300, 96)), [1, 1, 1, 1]);
+
+        // Five buttons of the minimum width, and spacing between them, still fit.
+        let width = 2 * 8 + 5 * (MIN_BUTTON_WIDTH + 6) - 6;
+        assert_eq!(lines(&ControlCenterLayout::new(width, 300, 96)), [1, 1, 1, 1]);
+        assert_eq!(lines(&ControlCenterLayout::new(width - 1, 300, 96)), [1, 1, 2, 1]);
+    }
+
+    #[test]
+    fn narrow_window_wraps_long_rows() {
+        let layout = ControlCenterLayout::new(200, 300, 96);
+        let thirds = &layout.rows()[2];
+        let lines: Vec<Vec<WindowAction>> = thirds.lines()
+            .map(|line| line.iter().map(|button| button.action).collect())
+            .collect();
+
+        assert_eq!(lines, [
+            vec![WindowAction::FirstThird, WindowAction::CenterThird, WindowAction::LastThird],
+            vec![WindowAction::FirstTwoThirds, WindowAction::LastTwoThirds],
+        ]);
+
+        // The shorter line keeps buttons in the columns of the first one.
+        let (first, second) = (&thirds.buttons[0].rect, &thirds.buttons[3].rect);
+        assert_eq!((first.left, first.right), (second.left, second.right));
+        assert_eq!(second.top - first.bottom, 6);
+        assert_eq!(thirds.caption.bottom, first.top);
+
+        // Lines of all rows share the height, the last one ends at the padding.
+        let heights: Vec<i32> = layout.buttons().map(|button| button.rect.height()).collect();
+        assert!(heights.iter().all(|height| (height - heights[0]).abs() <= 1), "{heights:?}");
+        assert_eq!(layout.button(WindowAction::Center).unwrap().rect.bottom, 300 - 8);
+        assert_eq!(layout.hit_test(second.left, second.top), Some(WindowAction::FirstTwoThirds));
+    }
+
+    #[test]
+    fn rows_never_wrap_below_the_minimum_number_of_columns() {
+        for width in [0, 30, 120] {
+            let layout = ControlCenterLayout::new(width, 300, 96);
+            assert_eq!(lines(&layout), [1, 1, 2, 1], "{width}");
+            assert!(layout.rows().iter().all(|row| row.lines().all(|line| {
+                line.len() <= MIN_COLUMNS
+            })));
+        }
+    }
+
+    #[test]
+    fn minimum_size_gives_minimum_buttons() {
+        for dpi in [96, 120, 144, 192] {
+            let (width, height) = ControlCenterLayout::minimum_size(dpi);
+            let layout = ControlCenterLayout::new(width, height, dpi);
+
+            assert_eq!(lines(&layout), [1, 1, 2, 1], "{dpi}");
+            let button = layout.button(WindowAction::TopLeft).unwrap().rect;
+            assert!((button.width() - scale(MIN_BUTTON_WIDTH, dpi)).abs() <= 1,
+                    "{dpi}: {button:?}");
+            assert!((button.height() - scale(MIN_BUTTON_HEIGHT, dpi)).abs() <= 1,
+                    "{dpi}: {button:?}");
+        }
+
+        // The preferred size is never below the minimum.
+        let (width, height) = ControlCenterLayout::minimum_size(96);
+        let (preferred_width, preferred_height) = ControlCenterLayout::preferred_size(96);
+        assert!(width <= preferred_width && height <= preferred_height);
+        assert_eq!((width, height), (194, 252));
+    }
+
+    #[test]
+    fn sizes_are_converted_back_to_the_base_dpi() {
+        assert_eq!(unscale(340, 96), 340);
+        assert_eq!(unscale(680, 192), 340);
+        assert_eq!(unscale(scale(266, 144), 144), 266);
+        assert_eq!(unscale(101, 120), 81);
+    }
+}
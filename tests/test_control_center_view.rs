@@ -0,0 +1,325 @@
+mod common;
+
+#[cfg(test)]
+mod test_control_center_view {
+    use std::env;
+    use std::fs;
+
+    use rectangular::action::WindowAction;
+    use rectangular::control_center_layout::{ControlCenterLayout, GridPanel};
+    use rectangular::control_center_view::{render, render_empty, render_grid, ButtonStates,
+                                           Screen};
+    use rectangular::display_list::{Color, DisplayList, DrawCommand, TextAlign};
+    use rectangular::geometry::Rect;
+    use rectangular::grid_picker::{Cell, CellSpan, GridDrag, GridSize};
+    use rectangular::theme::Theme;
+
+    use crate::common::raster::{self, Image};
+
+    /// Set this variable to write rendered snapshots to the repository instead of comparing them.
+    const UPDATE_VARIABLE: &str = "RECTANGULAR_UPDATE_SNAPSHOTS";
+
+    const ACCENT: Color = Color::rgb(0x00, 0x78, 0xD7);
+
+    fn render_preferred(states: &ButtonStates, screen: &Screen, dpi: u32) -> DisplayList {
+        render_themed(states, screen, dpi, &Theme::LIGHT)
+    }
+
+    fn render_themed(states: &ButtonStates, screen: &Screen, dpi: u32,
+                     theme: &Theme) -> DisplayList {
+        let (width, height) = ControlCenterLayout::preferred_size(dpi);
+        let layout = ControlCenterLayout::new(width, height, dpi);
+        render(&layout, states, screen, &Rect::new(0, 0, width, height), dpi, theme)
+    }
+
+    fn check_snapshot(name: &str, list: &DisplayList) {
+        let path = format!("{}/tests/snapshots/{name}.txt", env!("CARGO_MANIFEST_DIR"));
+        let rendered = list.snapshot();
+
+        if env::var_os(UPDATE_VARIABLE).is_some() {
+            fs::write(&path, &rendered).unwrap();
+        }
+
+        let expected = fs::read_to_string(&path).unwrap_or_default().replace("\r\n", "\n");
+        assert!(expected == rendered,
+                "{path} doesn't match, run the tests with {UPDATE_VARIABLE}=1 to update it, and \
+                 review the diff");
+    }
+
+    /// Render the list, and compare it with the golden image.
+    ///
+    /// When the images differ, the rendered image and a diff image are written next to each other
+    /// in the temporary directory of the tests, so they can be compared by looking at them.
+    fn check_golden(name: &str, list: &DisplayList, dpi: u32) {
+        let (width, height) = ControlCenterLayout::preferred_size(dpi);
+        let rendered = raster::render(list, width as u32, height as u32);
+        let path = format!("{}/tests/golden/{name}.png", env!("CARGO_MANIFEST_DIR"));
+
+        if env::var_os(UPDATE_VARIABLE).is_some() {
+            fs::write(&path, rendered.to_png()).unwrap();
+        }
+
+        let golden = fs::read(&path).map_err(|error| error.to_string())
+            .and_then(|data| Image::from_png(&data).map_err(|error| error.to_string()))
+            .unwrap_or_else(|error| panic!("{path}: {error}"));
+        let (count, diff) = rendered.diff(&golden);
+
+        if count > 0 {
+            let directory = format!("{}/golden", env!("CARGO_TARGET_TMPDIR"));
+            fs::create_dir_all(&directory).unwrap();
+            fs::write(format!("{directory}/{name}.actual.png"), rendered.to_png()).unwrap();
+            fs::write(format!("{directory}/{name}.diff.png"), diff.to_png()).unwrap();
+
+            panic!("{count} pixels differ from {path}, see {directory}/{name}.*.png, or run the \
+                    tests with {UPDATE_VARIABLE}=1 to update it");
+        }
+    }
+
+    /// Find the rectangles filled with the accent color, which are the target regions.
+    fn targets(list: &DisplayList) -> Vec<Rect> {
+        list.commands().iter()
+            .filter_map(|command| match command {
+                DrawCommand::Fill { rect, color } if *color == ACCENT => Some(*rect),
+                _ => None,
+            })
+            .collect()
+    }
+
+    fn captions(list: &DisplayList) -> Vec<String> {
+        list.commands().iter()
+            .filter_map(|command| match command {
+                DrawCommand::Text { text, align: TextAlign::Left, .. } => Some(text.clone()),
+                _ => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn snapshot_default() {
+        check_snapshot("control-center-96dpi",
+                       &render_preferred(&ButtonStates::default(), &Screen::default(), 96));
+    }
+
+    #[test]
+    fn snapshot_hovered_and_pressed() {
+        let states = ButtonStates {
+            hovered: Some(WindowAction::Maximize),
+            pressed: Some(WindowAction::TopLeft),
+            focused: None,
+        };
+        check_snapshot("control-center-pressed",
+                       &render_preferred(&states, &Screen::default(), 96));
+    }
+
+    #[test]
+    fn snapshot_portrait_monitor_at_150_percent() {
+        let screen = Screen {
+            monitor: Rect::new(-1200, 0, 0, 1920),
+            work_area: Rect::new(-1200, 0, 0, 1872),
+        };
+        check_snapshot("control-center-portrait-144dpi",
+                       &render_preferred(&ButtonStates::default(), &screen, 144));
+    }
+
+    #[test]
+    fn every_button_shows_its_target() {
+        let list = render_preferred(&ButtonStates::default(), &Screen::default(), 96);
+        let targets = targets(&list);
+
+        assert_eq!(targets.len(), WindowAction::ALL.len());
+
+        // Halves of the same miniature split it in two, without a gap or an overlap.
+        let (left, right) = (targets[0], targets[1]);
+        assert!(left.width() > 0 && left.height() > 0);
+        let maximize = targets[13];
+        assert_eq!(maximize.width(), left.width() + right.width());
+        assert_eq!(maximize.height(), left.height());
+    }
+
+    #[test]
+    fn miniatures_keep_the_aspect_ratio_of_the_monitor() {
+        let screen = Screen {
+            monitor: Rect::new(0, 0, 3440, 1440),
+            work_area: Rect::new(0, 0, 3440, 1440),
+        };
+        let list = render_preferred(&ButtonStates::default(), &screen, 96);
+        let maximize = targets(&list)[13];
+        let ratio = maximize.width() as f64 / maximize.height() as f64;
+
+        assert!((ratio - 3440.0 / 1440.0).abs() < 0.15, "{maximize:?}");
+    }
+
+    #[test]
+    fn work_area_leaves_room_for_the_taskbar() {
+        let screen = Screen {
+            monitor: Rect::new(0, 0, 1000, 1000),
+            work_area: Rect::new(200, 0, 1000, 1000),
+        };
+        let list = render_preferred(&ButtonStates::default(), &screen, 192);
+        let maximize = targets(&list)[13];
+
+        // The taskbar takes a fifth of the monitor on the left.
+        assert!((maximize.width() as f64 / maximize.height() as f64 - 0.8).abs() < 0.05,
+                "{maximize:?}");
+    }
+
+    #[test]
+    fn caption_names_the_highlighted_action() {
+        let states = ButtonStates {
+            hovered: Some(WindowAction::CenterThird),
+            ..Default::default()
+        };
+        let list = render_preferred(&states, &Screen::default(), 96);
+
+        assert_eq!(captions(&list), ["Halves", "Quarters", "Thirds: Center Third", "Other"]);
+
+        let states = ButtonStates {
+            hovered: Some(WindowAction::CenterThird),
+            pressed: Some(WindowAction::LeftHalf),
+            focused: Some(WindowAction::Center),
+        };
+        let list = render_preferred(&states, &Screen::default(), 96);
+
+        assert_eq!(captions(&list), ["Halves: Left Half", "Quarters", "Thirds", "Other"]);
+
+        let states = ButtonStates { focused: Some(WindowAction::Center), ..Default::default() };
+        let list = render_preferred(&states, &Screen::default(), 96);
+
+        assert_eq!(captions(&list), ["Halves", "Quarters", "Thirds", "Other: Center"]);
+    }
+
+    #[test]
+    fn golden_images_at_every_scale() {
+        for (dpi, name) in [(96, "control-center-100"), (144, "control-center-150"),
+                            (192, "control-center-200")] {
+            let list = render_preferred(&ButtonStates::default(), &Screen::default(), dpi);
+            check_golden(name, &list, dpi);
+        }
+    }
+
+    #[test]
+    fn golden_images_of_mouse_states() {
+        let hovered = ButtonStates {
+            hovered: Some(WindowAction::LeftHalf),
+            ..Default::default()
+        };
+        check_golden("control-center-hovered",
+                     &render_preferred(&hovered, &Screen::default(), 96), 96);
+
+        let pressed = ButtonStates {
+            hovered: Some(WindowAction::Maximize),
+            pressed: Some(WindowAction::TopLeft),
+            focused: None,
+        };
+        check_golden("control-center-pressed",
+                     &render_preferred(&pressed, &Screen::default(), 96), 96);
+
+        let focused = ButtonStates {
+            focused: Some(WindowAction::LastTwoThirds),
+            ..Default::default()
+        };
+        check_golden("control-center-focused",
+                     &render_preferred(&focused, &Screen::default(), 96), 96);
+    }
+
+    #[test]
+    fn golden_images_of_other_themes() {
+        let states = ButtonStates {
+            hovered: Some(WindowAction::Maximize),
+            focused: Some(WindowAction::LastTwoThirds),
+            ..Default::default()
+        };
+
+        for (theme, name) in [(Theme::DARK, "control-center-dark"),
+                              (Theme::HIGH_CONTRAST, "control-center-high-contrast")] {
+            check_golden(name, &render_themed(&states, &Screen::default(), 96, &theme), 96);
+        }
+    }
+
+    #[test]
+    fn themes_change_only_colors() {
+        let states = ButtonStates { pressed: Some(WindowAction::Center), ..Default::default() };
+        let geometry = |theme: &Theme| -> Vec<Rect> {
+            render_themed(&states, &Screen::default(), 96, theme).commands().iter()
+                .map(|command| match command {
+                    DrawCommand::Fill { rect, .. } | DrawCommand::Stroke { rect, .. }
+                        | DrawCommand::Text { rect, .. } => *rect,
+                })
+                .collect()
+        };
+
+        assert_eq!(geometry(&Theme::DARK), geometry(&Theme::LIGHT));
+        assert_eq!(geometry(&Theme::HIGH_CONTRAST), geometry(&Theme::LIGHT));
+
+        let dark = render_themed(&states, &Screen::default(), 96, &Theme::DARK);
+        assert!(matches!(dark.commands()[0],
+                         DrawCommand::Fill { color, .. } if color == Theme::DARK.background));
+        // Nothing is left in the light accent.
+        assert!(targets(&dark).is_empty());
+    }
+
+    #[test]
+    fn snapshot_empty_state() {
+        let (width, height) = ControlCenterLayout::preferred_size(96);
+        let list = render_empty(&Rect::new(0, 0, width, height), 96, &Theme::LIGHT);
+        check_snapshot("control-center-empty", &list);
+
+        // Only the text explaining what to do, no buttons.
+        assert!(targets(&list).is_empty());
+        assert_eq!(list.commands().len(), 3);
+    }
+
+    /// Lay out the grid panel next to buttons of the preferred size, for an ultrawide monitor.
+    fn grid_panel(dpi: u32) -> GridPanel {
+        let (width, height) = ControlCenterLayout::preferred_size(dpi);
+        let client = Rect::new(0, 0, width + GridPanel::width(dpi), height);
+        let work_area = Rect::new(0, 0, 5120, 1400);
+        GridPanel::new(&client, dpi, GridSize::new(8, 2), &work_area)
+    }
+
+    #[test]
+    fn grid_keeps_the_aspect_ratio_of_the_work_area() {
+        let (width, _) = ControlCenterLayout::preferred_size(96);
+        let panel = grid_panel(96);
+        let area = panel.grid.area();
+
+        assert!(area.left >= width && panel.caption.left >= width);
+        assert!(area.top >= panel.caption.bottom);
+        assert!((area.width() as f64 / area.height() as f64 - 5120.0 / 1400.0).abs() < 0.05);
+    }
+
+    #[test]
+    fn snapshot_grid_while_dragging() {
+        let panel = grid_panel(96);
+        let area = panel.grid.area();
+        let mut drag = GridDrag::default();
+        drag.press(&panel.grid, area.left + 1, area.top + 1);
+        drag.move_to(&panel.grid, area.left + area.width() / 2 - 1, area.bottom - 1);
+
+        let mut list = DisplayList::new();
+        render_grid(&mut list, &panel, &drag, 96, &Theme::LIGHT);
+        check_snapshot("control-center-grid-dragging", &list);
+
+        let selection = panel.grid.span_rect(&CellSpan::between(Cell::new(0, 0),
+                                                               Cell::new(3, 1)));
+        assert_eq!(targets(&list), [selection]);
+        assert!(list.snapshot().contains("\"Grid: 4 x 2 of 8 x 2\""));
+    }
+
+    #[test]
+    fn hovered_grid_cell_is_outlined() {
+        let panel = grid_panel(96);
+        let area = panel.grid.area();
+        let mut drag = GridDrag::default();
+        drag.move_to(&panel.grid, area.right - 1, area.top + 1);
+
+        let mut list = DisplayList::new();
+        render_grid(&mut list, &panel, &drag, 96, &Theme::LIGHT);
+
+        let cell = panel.grid.span_rect(&CellSpan::single(Cell::new(7, 0)));
+        assert!(list.commands().contains(&DrawCommand::Stroke { rect: cell, color: ACCENT,
+                                                                 width: 1 }));
+        assert!(targets(&list).is_empty());
+        assert!(list.snapshot().contains("\"Grid: 8 x 2\""));
+    }
+}
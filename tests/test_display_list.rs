@@ -0,0 +1,40 @@
+#[cfg(test)]
+mod test_display_list {
+    use rectangular::display_list::{Color, ColorParseError, DisplayList, DrawCommand, TextAlign};
+    use rectangular::geometry::Rect;
+
+    #[test]
+    fn colors_are_written_like_in_html() {
+        assert_eq!(Color::rgb(0, 0x78, 0xD7).to_string(), "#0078D7");
+        assert_eq!(Color::rgb(255, 255, 255).to_string(), "#FFFFFF");
+    }
+
+    #[test]
+    fn colors_are_parsed_like_in_html() {
+        assert_eq!("#0078D7".parse(), Ok(Color::rgb(0, 0x78, 0xD7)));
+        assert_eq!("#e81123".parse(), Ok(Color::rgb(0xE8, 0x11, 0x23)));
+
+        for text in ["0078D7", "#0078D", "#0078D7FF", "#00 8D7", "#GG0000", "#+1+1+1", ""] {
+            assert_eq!(text.parse::<Color>(), Err(ColorParseError), "{text:?}");
+        }
+    }
+
+    #[test]
+    fn commands_keep_their_order() {
+        let mut list = DisplayList::new();
+        list.fill(Rect::new(0, 0, 10, 10), Color::rgb(1, 2, 3));
+        list.stroke(Rect::new(1, 1, 9, 9), Color::rgb(4, 5, 6), 2);
+        list.text(Rect::new(0, 0, 10, 5), "A \"quoted\" label", Color::default(),
+                  TextAlign::Center);
+
+        assert_eq!(list.commands()[0], DrawCommand::Fill {
+            rect: Rect::new(0, 0, 10, 10),
+            color: Color::rgb(1, 2, 3),
+        });
+        assert_eq!(list.snapshot(), "\
+fill 0,0 10,10 #010203
+stroke 1,1 9,9 2 #040506
+text 0,0 10,5 center #000000 \"A \\\"quoted\\\" label\"
+");
+    }
+}
@@ -0,0 +1,143 @@
+#[cfg(test)]
+mod test_engine {
+    use rectangular::action::WindowAction;
+    use rectangular::engine::{adjacent_display, plan, target_rect, Context, FrameInsets};
+    use rectangular::geometry::Rect;
+    use rectangular::settings::{CyclingMode, DisplayOrder, Gaps, Settings};
+
+    const WORK_AREA: Rect = Rect::new(0, 0, 1920, 1040);
+    const NO_GAPS: Gaps = Gaps { inner: 0, outer: 0 };
+
+    fn context(current: Rect) -> Context {
+        Context { work_area: WORK_AREA, current, displays: vec![WORK_AREA] }
+    }
+
+    #[test]
+    fn halves_split_work_area() {
+        let current = Rect::from_size(100, 100, 800, 600);
+
+        assert_eq!(target_rect(WindowAction::LeftHalf, &WORK_AREA, &current, &NO_GAPS),
+                   Rect::new(0, 0, 960, 1040));
+        assert_eq!(target_rect(WindowAction::BottomHalf, &WORK_AREA, &current, &NO_GAPS),
+                   Rect::new(0, 520, 1920, 1040));
+    }
+
+    #[test]
+    fn adjacent_thirds_share_edges() {
+        let work_area = Rect::new(0, 0, 1000, 1000);
+        let current = Rect::default();
+
+        let first = target_rect(WindowAction::FirstThird, &work_area, &current, &NO_GAPS);
+        let center = target_rect(WindowAction::CenterThird, &work_area, &current, &NO_GAPS);
+        let last = target_rect(WindowAction::LastThird, &work_area, &current, &NO_GAPS);
+
+        assert_eq!(first.right, center.left);
+        assert_eq!(center.right, last.left);
+        assert_eq!(last.right, 1000);
+    }
+
+    #[test]
+    fn work_area_offset_is_respected() {
+        let work_area = Rect::new(1920, 40, 3840, 1080);
+
+        assert_eq!(target_rect(WindowAction::TopRight, &work_area, &Rect::default(), &NO_GAPS),
+                   Rect::new(2880, 40, 3840, 560));
+    }
+
+    #[test]
+    fn center_keeps_size() {
+        let current = Rect::from_size(0, 0, 800, 600);
+
+        assert_eq!(target_rect(WindowAction::Center, &WORK_AREA, &current, &NO_GAPS),
+                   Rect::from_size(560, 220, 800, 600));
+    }
+
+    #[test]
+    fn center_shrinks_oversized_window() {
+        let current = Rect::from_size(0, 0, 3000, 600);
+
+        assert_eq!(target_rect(WindowAction::Center, &WORK_AREA, &current, &NO_GAPS),
+                   Rect::from_size(0, 220, 1920, 600));
+    }
+
+    #[test]
+    fn frame_insets_round_trip() {
+        let window = Rect::new(93, 100, 907, 707);
+        let visible = Rect::new(100, 100, 900, 700);
+        let insets = FrameInsets::between(&window, &visible);
+
+        assert_eq!(insets, FrameInsets { left: 7, top: 0, right: 7, bottom: 7 });
+        assert_eq!(insets.expand(&visible), window);
+    }
+
+    #[test]
+    fn gaps_separate_adjacent_windows() {
+        let gaps = Gaps { inner: 10, outer: 20 };
+        let current = Rect::default();
+
+        let left = target_rect(WindowAction::LeftHalf, &WORK_AREA, &current, &gaps);
+        let right = target_rect(WindowAction::RightHalf, &WORK_AREA, &current, &gaps);
+
+        assert_eq!(left, Rect::new(20, 20, 955, 1020));
+        assert_eq!(right, Rect::new(965, 20, 1900, 1020));
+        assert_eq!(target_rect(WindowAction::Maximize, &WORK_AREA, &current, &gaps),
+                   Rect::new(20, 20, 1900, 1020));
+    }
+
+    #[test]
+    fn repeated_half_cycles_sizes() {
+        let mut settings = Settings::default();
+        settings.cycling.mode = CyclingMode::Sizes;
+
+        let half = plan(WindowAction::LeftHalf, &context(Rect::default()), &settings);
+        assert_eq!(half, Rect::new(0, 0, 960, 1040));
+
+        let two_thirds = plan(WindowAction::LeftHalf, &context(half), &settings);
+        assert_eq!(two_thirds, Rect::new(0, 0, 1280, 1040));
+
+        let third = plan(WindowAction::LeftHalf, &context(two_thirds), &settings);
+        assert_eq!(third, Rect::new(0, 0, 640, 1040));
+
+        assert_eq!(plan(WindowAction::LeftHalf, &context(third), &settings), half);
+    }
+
+    #[test]
+    fn cycling_is_off_by_default() {
+        let settings = Settings::default();
+        let half = Rect::new(0, 0, 960, 1040);
+
+        assert_eq!(plan(WindowAction::LeftHalf, &context(half), &settings), half);
+    }
+
+    #[test]
+    fn repeated_half_moves_to_adjacent_display() {
+        let mut settings = Settings::default();
+        settings.cycling.mode = CyclingMode::Displays;
+        let second = Rect::new(1920, 0, 3840, 1040);
+        let context = Context {
+            work_area: WORK_AREA,
+            current: Rect::new(960, 0, 1920, 1040),
+            displays: vec![second, WORK_AREA],
+        };
+
+        assert_eq!(plan(WindowAction::RightHalf, &context, &settings),
+                   Rect::new(1920, 0, 2880, 1040));
+    }
+
+    #[test]
+    fn display_traversal_respects_order_and_wrap_around() {
+        let mut settings = Settings::default();
+        let left = Rect::new(0, 0, 1920, 1040);
+        let right = Rect::new(1920, 0, 3840, 1040);
+        let below = Rect::new(0, 1080, 1920, 2120);
+        let displays = [right, below, left];
+
+        assert_eq!(adjacent_display(&displays, &left, true, &settings), Some(below));
+        assert_eq!(adjacent_display(&displays, &right, true, &settings), Some(left));
+
+        settings.cycling.display_order = DisplayOrder::TopToBottom;
+        settings.cycling.wrap_around = false;
+        assert_eq!(adjacent_display(&displays, &left, true, &settings), Some(right));
+        assert_eq!(adjacent_display(&displays, &below, true, &settings), None);
+    }
+}
@@ -0,0 +1,108 @@
+#[cfg(test)]
+mod test_fancyzones_import {
+    use std::fs;
+
+    use rectangular::fancyzones_import::{import, FancyZonesImport};
+    use rectangular::geometry::FracRect;
+    use rectangular::import::ImportError;
+    use rectangular::settings::{self, CustomAction, CustomGroup, Gaps, Settings};
+
+    fn imported() -> FancyZonesImport {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"),
+                           "/tests/fixtures/fancyzones/custom-layouts.json");
+        import(&fs::read_to_string(path).unwrap()).unwrap()
+    }
+
+    fn rects(group: &CustomGroup) -> Vec<FracRect> {
+        group.actions.iter().map(|action| action.rect).collect()
+    }
+
+    #[test]
+    fn grid_zones_cover_their_cells() {
+        let imported = imported();
+        let coding = &imported.groups[0];
+
+        assert_eq!(coding.name, "Coding");
+        assert_eq!(coding.actions.iter().map(|action| &action.name[..]).collect::<Vec<_>>(),
+                   vec!["Zone 1", "Zone 2", "Zone 3", "Zone 4"]);
+        assert_eq!(rects(coding), vec![
+            FracRect::new(0.0, 0.0, 0.25, 1.0),
+            FracRect::new(0.25, 0.0, 0.5, 0.5),
+            FracRect::new(0.75, 0.0, 0.25, 0.5),
+            FracRect::new(0.25, 0.5, 0.75, 0.5),
+        ]);
+    }
+
+    #[test]
+    fn canvas_zones_are_relative_to_the_reference_size() {
+        let imported = imported();
+        let streaming = &imported.groups[1];
+
+        assert_eq!(streaming.name, "Streaming");
+        // The last zone sticks out of the reference area, and is clipped to it.
+        assert_eq!(rects(streaming), vec![
+            FracRect::new(0.0, 0.0, 0.6667, 1.0),
+            FracRect::new(0.6667, 0.0, 0.3333, 0.5),
+            FracRect::new(0.6667, 0.5, 0.3333, 0.5),
+        ]);
+    }
+
+    #[test]
+    fn spacing_of_the_first_layout_becomes_the_gaps() {
+        assert_eq!(imported().gaps, Some(Gaps { inner: 8, outer: 8 }));
+    }
+
+    #[test]
+    fn reports_everything_not_imported() {
+        let imported = imported();
+        let report: Vec<String> = imported.report.notes.iter().map(ToString::to_string).collect();
+
+        assert_eq!(imported.groups.len(), 3);
+        assert_eq!(report, vec![
+            "spacing of layout \"Wide\": the spacing of 16 px differs from 8 px of another \
+             layout, gaps are global in Rectangular",
+            "layout \"Spiral\": the layout type \"spiral\" is not supported",
+        ]);
+    }
+
+    #[test]
+    fn fragment_matches_imported_settings() {
+        let imported = imported();
+        let fragment = imported.to_toml();
+
+        let mut expected = Settings::default();
+        imported.apply_to(&mut expected);
+
+        assert!(fragment.starts_with("# Imported from FancyZones.\n#\n# Not imported:\n"));
+        assert_eq!(settings::parse(&fragment).unwrap().settings, expected);
+    }
+
+    #[test]
+    fn replaces_groups_with_the_same_name() {
+        let mut settings = Settings {
+            custom_groups: vec![
+                CustomGroup { name: "Coding".to_string(), actions: vec![] },
+                CustomGroup {
+                    name: "Mine".to_string(),
+                    actions: vec![CustomAction {
+                        name: "Left".to_string(),
+                        rect: FracRect::new(0.0, 0.0, 0.5, 1.0),
+                    }],
+                },
+            ],
+            ..Settings::default()
+        };
+
+        imported().apply_to(&mut settings);
+
+        let names: Vec<&str> = settings.custom_groups.iter().map(|group| &group.name[..]).collect();
+        assert_eq!(names, vec!["Mine", "Coding", "Streaming", "Wide"]);
+        assert_eq!(settings.custom_groups[1].actions.len(), 4);
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(matches!(import("{}"), Err(ImportError::Format(_))));
+        assert!(matches!(import("[1,"), Err(ImportError::Json(_))));
+    }
+}
@@ -0,0 +1,102 @@
+mod common;
+
+#[cfg(test)]
+mod test_foreground {
+    use rectangular::foreground::{is_eligible, ForegroundTracker};
+    use rectangular::window_system::WindowInfo;
+    use windows::Win32::Foundation::HWND;
+
+    use crate::common::{app, FakeWindowSystem};
+
+    #[test]
+    fn application_windows_are_eligible() {
+        assert!(is_eligible(&app("Notepad")));
+
+        assert!(!is_eligible(&WindowInfo { visible: false, ..app("Notepad") }));
+        assert!(!is_eligible(&WindowInfo { top_level: false, ..app("Edit") }));
+        assert!(!is_eligible(&WindowInfo { cloaked: true, ..app("Notepad") }));
+        assert!(!is_eligible(&WindowInfo { own: true, ..app("Rectangular") }));
+    }
+
+    #[test]
+    fn shell_windows_are_not_eligible() {
+        for class_name in ["Shell_TrayWnd", "Shell_SecondaryTrayWnd", "NotifyIconOverflowWindow",
+                           "Progman", "WorkerW"] {
+            assert!(!is_eligible(&app(class_name)), "{class_name}");
+        }
+    }
+
+    #[test]
+    fn nothing_is_tracked_at_first() {
+        let system = FakeWindowSystem::default();
+        assert_eq!(ForegroundTracker::default().target(&system), None);
+    }
+
+    #[test]
+    fn taskbar_and_control_center_keep_the_previous_target() {
+        let system = FakeWindowSystem::default();
+        let editor = system.add(1, app("Notepad"));
+        let taskbar = system.add(2, app("Shell_TrayWnd"));
+        let control_center = system.add(3, WindowInfo { own: true, ..app("Rectangular") });
+
+        // Clicking the notification icon activates the taskbar, and then the Control Center.
+        let mut tracker = ForegroundTracker::default();
+        for window in [editor, taskbar, control_center] {
+            tracker.foreground_changed(window, &system);
+        }
+
+        assert_eq!(tracker.target(&system), Some(editor));
+    }
+
+    #[test]
+    fn latest_eligible_window_wins() {
+        let system = FakeWindowSystem::default();
+        let editor = system.add(1, app("Notepad"));
+        let browser = system.add(2, app("Chrome_WidgetWin_1"));
+
+        let mut tracker = ForegroundTracker::default();
+        tracker.foreground_changed(editor, &system);
+        tracker.foreground_changed(browser, &system);
+
+        assert_eq!(tracker.target(&system), Some(browser));
+    }
+
+    #[test]
+    fn unknown_windows_are_ignored() {
+        let system = FakeWindowSystem::default();
+        let editor = system.add(1, app("Notepad"));
+
+        let mut tracker = ForegroundTracker::default();
+        tracker.foreground_changed(editor, &system);
+        tracker.foreground_changed(HWND(42), &system);
+
+        assert_eq!(tracker.target(&system), Some(editor));
+    }
+
+    #[test]
+    fn closed_window_is_no_target() {
+        let system = FakeWindowSystem::default();
+        let editor = system.add(1, app("Notepad"));
+
+        let mut tracker = ForegroundTracker::default();
+        tracker.foreground_changed(editor, &system);
+        system.close(editor);
+
+        assert_eq!(tracker.target(&system), None);
+    }
+
+    #[test]
+    fn hidden_window_is_no_target_until_shown_again() {
+        let system = FakeWindowSystem::default();
+        let editor = system.add(1, app("Notepad"));
+
+        let mut tracker = ForegroundTracker::default();
+        tracker.foreground_changed(editor, &system);
+
+        system.update(editor, |info| info.cloaked = true);
+        assert_eq!(tracker.target(&system), None);
+
+        system.update(editor, |info| info.cloaked = false);
+        assert_eq!(tracker.target(&system), Some(editor));
+    }
+}
@@ -0,0 +1,169 @@
+#[cfg(test)]
+mod test_grid_picker {
+    use rectangular::geometry::{FracRect, Rect};
+    use rectangular::grid_picker::{Cell, CellSpan, Grid, GridDrag, GridSize, MAX_CELLS};
+    use rectangular::settings::Gaps;
+
+    /// A 6×4 grid of 20×20 cells, starting at (100, 50).
+    fn grid() -> Grid {
+        Grid::new(GridSize::new(6, 4), Rect::new(100, 50, 220, 130))
+    }
+
+    #[test]
+    fn size_is_limited() {
+        assert_eq!(GridSize::new(0, 100), GridSize { columns: 1, rows: MAX_CELLS });
+        assert_eq!(Grid::new(GridSize { columns: 0, rows: 0 }, Rect::default()).size(),
+                   GridSize::new(1, 1));
+    }
+
+    #[test]
+    fn hit_test_finds_cells() {
+        let grid = grid();
+
+        assert_eq!(grid.hit_test(100, 50), Some(Cell::new(0, 0)));
+        assert_eq!(grid.hit_test(119, 69), Some(Cell::new(0, 0)));
+        assert_eq!(grid.hit_test(120, 70), Some(Cell::new(1, 1)));
+        assert_eq!(grid.hit_test(219, 129), Some(Cell::new(5, 3)));
+
+        // Right and bottom edges are exclusive, like in RECT.
+        assert_eq!(grid.hit_test(220, 60), None);
+        assert_eq!(grid.hit_test(150, 130), None);
+        assert_eq!(grid.hit_test(99, 60), None);
+    }
+
+    #[test]
+    fn hit_test_matches_cell_rectangles() {
+        // Cells of uneven sizes, as 100 pixels don't divide into 7 columns.
+        let grid = Grid::new(GridSize::new(7, 3), Rect::new(0, 0, 100, 50));
+
+        for cell in grid.cells() {
+            let rect = grid.span_rect(&CellSpan::single(cell));
+            assert_eq!(grid.hit_test(rect.left, rect.top), Some(cell));
+            assert_eq!(grid.hit_test(rect.right - 1, rect.bottom - 1), Some(cell));
+        }
+        assert_eq!(grid.cells().count(), 21);
+    }
+
+    #[test]
+    fn nearest_cell_clamps_points_outside() {
+        let grid = grid();
+
+        assert_eq!(grid.nearest_cell(0, 0), Some(Cell::new(0, 0)));
+        assert_eq!(grid.nearest_cell(1000, 60), Some(Cell::new(5, 0)));
+        assert_eq!(grid.nearest_cell(130, 1000), Some(Cell::new(1, 3)));
+        assert_eq!(Grid::new(GridSize::default(), Rect::default()).nearest_cell(0, 0), None);
+    }
+
+    #[test]
+    fn spans_are_normalized() {
+        let span = CellSpan::between(Cell::new(4, 0), Cell::new(1, 2));
+
+        assert_eq!(span, CellSpan { left: 1, top: 0, right: 4, bottom: 2 });
+        assert_eq!(span, CellSpan::between(Cell::new(1, 2), Cell::new(4, 0)));
+        assert_eq!((span.columns(), span.rows()), (4, 3));
+        assert!(span.contains(Cell::new(1, 1)) && !span.contains(Cell::new(5, 1)));
+    }
+
+    #[test]
+    fn spans_convert_to_fractions() {
+        let size = GridSize::new(6, 4);
+
+        let span = CellSpan::between(Cell::new(0, 0), Cell::new(5, 3));
+        assert!(span.to_fraction(size).approx_eq(&FracRect::FULL));
+
+        let span = CellSpan::between(Cell::new(2, 1), Cell::new(3, 2));
+        assert!(span.to_fraction(size).approx_eq(&FracRect::new(2.0 / 6.0, 0.25, 2.0 / 6.0, 0.5)));
+    }
+
+    #[test]
+    fn spans_convert_to_pixels_with_gaps() {
+        let size = GridSize::new(4, 2);
+        let work_area = Rect::new(0, 0, 5120, 1400);
+        let gaps = Gaps { inner: 10, outer: 20 };
+
+        // The outer gap at the edges of the work area, half of the inner one inside of it.
+        let left = CellSpan::between(Cell::new(0, 0), Cell::new(0, 1));
+        assert_eq!(left.target_rect(size, &work_area, &gaps), Rect::new(20, 20, 1285, 1380));
+
+        let middle = CellSpan::between(Cell::new(1, 0), Cell::new(2, 0));
+        assert_eq!(middle.target_rect(size, &work_area, &gaps), Rect::new(1295, 20, 3825, 695));
+
+        // Adjacent spans are separated by the full inner gap.
+        let right = CellSpan::between(Cell::new(3, 0), Cell::new(3, 1));
+        assert_eq!(right.target_rect(size, &work_area, &gaps).left - 3825, 10);
+    }
+
+    #[test]
+    fn spans_survive_packing() {
+        let span = CellSpan::between(Cell::new(31, 2), Cell::new(0, 17));
+        assert_eq!(CellSpan::unpack(span.pack()), span);
+    }
+
+    #[test]
+    fn drag_selects_the_span_between_press_and_release() {
+        let grid = grid();
+        let mut drag = GridDrag::default();
+
+        // Hovering highlights a single cell.
+        assert!(drag.move_to(&grid, 125, 55));
+        assert_eq!(drag.selection(), Some(CellSpan::single(Cell::new(1, 0))));
+        assert!(!drag.move_to(&grid, 126, 56));
+
+        assert!(drag.press(&grid, 125, 55));
+        assert!(drag.is_dragging());
+        assert!(drag.move_to(&grid, 165, 95));
+        assert_eq!(drag.selection(), Some(CellSpan::between(Cell::new(1, 0), Cell::new(3, 2))));
+
+        assert_eq!(drag.release(&grid, 165, 95),
+                   Some(CellSpan::between(Cell::new(1, 0), Cell::new(3, 2))));
+        assert!(!drag.is_dragging());
+        assert_eq!(drag.selection(), Some(CellSpan::single(Cell::new(3, 2))));
+    }
+
+    #[test]
+    fn drag_backwards_is_normalized() {
+        let grid = grid();
+        let mut drag = GridDrag::default();
+
+        drag.press(&grid, 215, 125);
+        assert_eq!(drag.release(&grid, 105, 75),
+                   Some(CellSpan::between(Cell::new(0, 1), Cell::new(5, 3))));
+    }
+
+    #[test]
+    fn drag_outside_of_the_grid_sticks_to_the_edge() {
+        let grid = grid();
+        let mut drag = GridDrag::default();
+
+        drag.press(&grid, 145, 75);
+        drag.move_to(&grid, 500, -20);
+        assert_eq!(drag.selection(), Some(CellSpan::between(Cell::new(2, 0), Cell::new(5, 1))));
+
+        // Leaving the window doesn't stop the drag.
+        drag.leave();
+        assert_eq!(drag.release(&grid, 500, 500),
+                   Some(CellSpan::between(Cell::new(2, 1), Cell::new(5, 3))));
+        assert_eq!(drag.selection(), None);
+    }
+
+    #[test]
+    fn press_outside_of_the_grid_is_ignored() {
+        let grid = grid();
+        let mut drag = GridDrag::default();
+
+        assert!(!drag.press(&grid, 10, 10));
+        assert!(!drag.is_dragging());
+        assert_eq!(drag.release(&grid, 150, 60), None);
+    }
+
+    #[test]
+    fn cancelled_drag_selects_nothing() {
+        let grid = grid();
+        let mut drag = GridDrag::default();
+
+        drag.press(&grid, 150, 60);
+        drag.cancel();
+
+        assert_eq!(drag.release(&grid, 150, 60), None);
+    }
+}
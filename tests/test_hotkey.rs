@@ -0,0 +1,36 @@
+#[cfg(test)]
+mod test_hotkey {
+    use rectangular::hotkey::{Hotkey, HotkeyParseError, KeySpec, Modifiers};
+
+    #[test]
+    fn parses_modifiers_and_key() {
+        let hotkey: Hotkey = "Ctrl+Alt+Left".parse().unwrap();
+
+        assert_eq!(hotkey, Hotkey::new(Modifiers::CTRL | Modifiers::ALT, KeySpec::Virtual(0x25)));
+    }
+
+    #[test]
+    fn parsing_is_case_and_whitespace_insensitive() {
+        let hotkey: Hotkey = "control + SHIFT + f5".parse().unwrap();
+
+        assert_eq!(hotkey, Hotkey::new(Modifiers::CTRL | Modifiers::SHIFT, KeySpec::Virtual(0x74)));
+    }
+
+    #[test]
+    fn formats_canonical_names() {
+        let hotkey: Hotkey = "win+shift+ctrl+alt+enter".parse().unwrap();
+
+        assert_eq!(hotkey.to_string(), "Ctrl+Alt+Shift+Win+Enter");
+        assert_eq!("Alt+[".parse::<Hotkey>().unwrap().to_string(), "Alt+[");
+    }
+
+    #[test]
+    fn reports_errors() {
+        assert_eq!("".parse::<Hotkey>(), Err(HotkeyParseError::Empty));
+        assert_eq!("Ctrl++A".parse::<Hotkey>(), Err(HotkeyParseError::Empty));
+        assert_eq!("Hyper+A".parse::<Hotkey>(),
+                   Err(HotkeyParseError::UnknownModifier("Hyper".to_string())));
+        assert_eq!("Ctrl+Foo".parse::<Hotkey>(),
+                   Err(HotkeyParseError::UnknownKey("Foo".to_string())));
+    }
+}
@@ -0,0 +1,103 @@
+#[cfg(test)]
+mod test_ignore {
+    use rectangular::ignore::{
+        app_name, find, is_ignored, matches, names_app, suspends_hotkeys, wildcard_match,
+        WindowIdentity,
+    };
+    use rectangular::settings::IgnoredApp;
+
+    fn window(executable: &str, class_name: &str, title: &str) -> WindowIdentity {
+        WindowIdentity {
+            executable: executable.to_string(),
+            class_name: class_name.to_string(),
+            title: title.to_string(),
+        }
+    }
+
+    fn remote_desktop() -> WindowIdentity {
+        window(r"C:\Windows\System32\mstsc.exe", "TscShellContainerClass", "work - Remote Desktop")
+    }
+
+    #[test]
+    fn apps_are_named_by_executable_or_path() {
+        let executable = r"C:\Windows\System32\mstsc.exe";
+
+        assert_eq!(app_name(executable), "mstsc.exe");
+        assert_eq!(app_name("mstsc.exe"), "mstsc.exe");
+        assert!(names_app("MSTSC.EXE", executable));
+        assert!(names_app(r"c:\windows\system32\mstsc.exe", executable));
+        assert!(names_app("C:/Windows/System32/mstsc.exe", executable));
+        assert!(!names_app(r"D:\Tools\mstsc.exe", executable));
+        assert!(!names_app("mstsc", executable));
+    }
+
+    #[test]
+    fn wildcards_match_any_text() {
+        assert!(wildcard_match("*", ""));
+        assert!(wildcard_match("", ""));
+        assert!(wildcard_match("Chrome_WidgetWin_?", "Chrome_WidgetWin_1"));
+        assert!(wildcard_match("*remote desktop", "work - Remote Desktop"));
+        assert!(wildcard_match("*a*b*", "xxaxxbxxb"));
+        assert!(wildcard_match("a*b", "abab"));
+        assert!(!wildcard_match("a*b", "abba "));
+        assert!(!wildcard_match("?", ""));
+        assert!(!wildcard_match("Meet", "Meet - Google Chrome"));
+        assert!(wildcard_match("Ünï*", "üNÏcode"));
+    }
+
+    #[test]
+    fn patterns_narrow_entries_down() {
+        let any = IgnoredApp::new("mstsc.exe");
+        let by_class = IgnoredApp { class: Some("Tsc*".to_string()), ..any.clone() };
+        let by_title = IgnoredApp { title: Some("*home*".to_string()), ..any.clone() };
+        let other = IgnoredApp::new("vmconnect.exe");
+
+        assert!(matches(&any, &remote_desktop()));
+        assert!(matches(&by_class, &remote_desktop()));
+        assert!(!matches(&by_title, &remote_desktop()));
+        assert!(!matches(&other, &remote_desktop()));
+        assert!(matches(&by_class, &window("mstsc.exe", "TscShellContainerClass", "")));
+        assert!(!matches(&by_class, &window("mstsc.exe", "#32770", "Remote Desktop Connection")));
+    }
+
+    #[test]
+    fn first_matching_entry_is_found() {
+        let ignored_apps = vec![
+            IgnoredApp { title: Some("*home*".to_string()), ..IgnoredApp::new("mstsc.exe") },
+            IgnoredApp { suspend_hotkeys: true, ..IgnoredApp::new("mstsc.exe") },
+        ];
+
+        assert_eq!(find(&ignored_apps, &remote_desktop()), Some(&ignored_apps[1]));
+        assert_eq!(find(&ignored_apps, &window("notepad.exe", "Notepad", "")), None);
+        assert_eq!(find(&[], &remote_desktop()), None);
+    }
+
+    #[test]
+    fn windows_are_ignored_by_matching_entries_only() {
+        let photoshop = window(r"C:\Program Files\Adobe\photoshop.exe", "Photoshop", "Untitled");
+        let ignored_apps = vec![IgnoredApp::new("Photoshop.exe"), IgnoredApp {
+            class: Some("TscShellContainerClass".to_string()),
+            ..IgnoredApp::new("mstsc.exe")
+        }];
+
+        assert!(is_ignored(&ignored_apps, &photoshop));
+        assert!(is_ignored(&ignored_apps, &remote_desktop()));
+        assert!(!is_ignored(&ignored_apps, &window(r"C:\Windows\System32\mstsc.exe", "#32770",
+                                                   "Remote Desktop Connection")));
+        assert!(!is_ignored(&ignored_apps, &window(r"C:\Photoshop.exe\app.exe", "App", "")));
+    }
+
+    #[test]
+    fn only_some_entries_suspend_hotkeys() {
+        let mut ignored_apps = vec![IgnoredApp::new("mstsc.exe")];
+        assert!(!suspends_hotkeys(&ignored_apps, &remote_desktop()));
+
+        ignored_apps.push(IgnoredApp {
+            title: Some("*work*".to_string()),
+            suspend_hotkeys: true,
+            ..IgnoredApp::new("mstsc.exe")
+        });
+        assert!(suspends_hotkeys(&ignored_apps, &remote_desktop()));
+        assert!(!suspends_hotkeys(&ignored_apps, &window("mstsc.exe", "", "home")));
+    }
+}
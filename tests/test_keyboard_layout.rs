@@ -0,0 +1,114 @@
+#[cfg(test)]
+mod test_keyboard_layout {
+    use rectangular::hotkey::{Hotkey, KeySpec};
+    use rectangular::keyboard_layout::{
+        fallback_table, hotkey_label, key_label, KeyMap, LayoutTable, resolve_vk, US_LAYOUT,
+    };
+
+    const US: u16 = 0x0409;
+    const GERMAN: u16 = 0x0407;
+    const POLISH: u16 = 0x0415;
+
+    fn layout(language_id: u16) -> &'static LayoutTable {
+        LayoutTable::find(language_id).unwrap()
+    }
+
+    /// A key map which doesn't know any key, like a layout without a table.
+    struct EmptyKeyMap;
+
+    impl KeyMap for EmptyKeyMap {
+        fn vk_from_scan(&self, _scan: u16) -> Option<u16> {
+            None
+        }
+
+        fn scan_from_vk(&self, _vk: u16) -> Option<u16> {
+            None
+        }
+
+        fn char_from_vk(&self, _vk: u16) -> Option<char> {
+            None
+        }
+    }
+
+    #[test]
+    fn parses_scan_codes() {
+        assert_eq!(KeySpec::from_name("sc:0x1A"), Some(KeySpec::Scan(0x1A)));
+        assert_eq!(KeySpec::from_name("sc:26"), Some(KeySpec::Scan(0x1A)));
+        assert_eq!(KeySpec::from_name("sc:0"), None);
+        assert_eq!(KeySpec::from_name("sc:zz"), None);
+        assert_eq!("Ctrl+Alt+sc:0x1a".parse::<Hotkey>().unwrap().to_string(),
+                   "Ctrl+Alt+sc:0x1A");
+    }
+
+    #[test]
+    fn same_physical_key_differs_between_layouts() {
+        assert_eq!(resolve_vk(KeySpec::Scan(0x1A), layout(US)), Some(0xDB));
+        assert_eq!(resolve_vk(KeySpec::Scan(0x1A), layout(GERMAN)), Some(0xBA));
+        assert_eq!(resolve_vk(KeySpec::Scan(0x1A), layout(POLISH)), Some(0xDB));
+    }
+
+    #[test]
+    fn same_virtual_key_differs_between_layouts() {
+        assert_eq!(layout(US).scan_from_vk(0xDB), Some(0x1A));
+        assert_eq!(layout(GERMAN).scan_from_vk(0xDB), Some(0x0C));
+    }
+
+    #[test]
+    fn german_layout_swaps_y_and_z() {
+        assert_eq!(key_label(KeySpec::Scan(0x15), layout(GERMAN)), "Z");
+        assert_eq!(key_label(KeySpec::Scan(0x2C), layout(GERMAN)), "Y");
+        assert_eq!(key_label(KeySpec::Scan(0x15), layout(US)), "Y");
+    }
+
+    #[test]
+    fn labels_show_layout_characters() {
+        let hotkey: Hotkey = "Ctrl+Alt+[".parse().unwrap();
+
+        assert_eq!(hotkey_label(&hotkey, layout(US)), "Ctrl+Alt+[");
+        assert_eq!(hotkey_label(&hotkey, layout(GERMAN)), "Ctrl+Alt+ß");
+        assert_eq!(hotkey_label(&"Ctrl+sc:0x1A".parse().unwrap(), layout(GERMAN)), "Ctrl+Ü");
+    }
+
+    #[test]
+    fn labels_fall_back_to_key_names() {
+        assert_eq!(hotkey_label(&"Win+Left".parse().unwrap(), layout(GERMAN)), "Win+Left");
+        assert_eq!(key_label(KeySpec::Virtual(0x74), layout(US)), "F5");
+        assert_eq!(key_label(KeySpec::Virtual(0xFF), layout(US)), "0xFF");
+    }
+
+    /// A key map of a German layout which doesn't know any key.
+    struct EmptyGermanKeyMap;
+
+    impl KeyMap for EmptyGermanKeyMap {
+        fn vk_from_scan(&self, _scan: u16) -> Option<u16> {
+            None
+        }
+
+        fn scan_from_vk(&self, _vk: u16) -> Option<u16> {
+            None
+        }
+
+        fn char_from_vk(&self, _vk: u16) -> Option<char> {
+            None
+        }
+
+        fn language_id(&self) -> Option<u16> {
+            Some(GERMAN)
+        }
+    }
+
+    #[test]
+    fn unknown_keys_fall_back_to_the_table_of_the_language() {
+        assert_eq!(resolve_vk(KeySpec::Scan(0x15), &EmptyGermanKeyMap), Some(0x5A));
+        assert_eq!(key_label(KeySpec::Scan(0x1A), &EmptyGermanKeyMap), "Ü");
+        assert_eq!(fallback_table(&EmptyGermanKeyMap).name, "German");
+        assert_eq!(fallback_table(&EmptyKeyMap).name, "US");
+    }
+
+    #[test]
+    fn unknown_scan_codes_fall_back_to_us_layout() {
+        assert_eq!(resolve_vk(KeySpec::Scan(0x1A), &EmptyKeyMap), US_LAYOUT.vk_from_scan(0x1A));
+        assert_eq!(resolve_vk(KeySpec::Scan(0x7F), &EmptyKeyMap), None);
+        assert_eq!(key_label(KeySpec::Scan(0x7F), &EmptyKeyMap), "sc:0x7F");
+    }
+}
@@ -0,0 +1,223 @@
+#[cfg(test)]
+mod test_migration {
+    use std::fs;
+    use std::path::PathBuf;
+
+    use rectangular::action::WindowAction;
+    use rectangular::migration::{backup_path, migrate, upgrade_file, CURRENT_VERSION};
+    use rectangular::settings::{self, CyclingMode, DisplayOrder, IgnoredApp, Location, Settings};
+
+    fn fixture(version: u32) -> String {
+        let path = format!("{}/tests/fixtures/settings/v{version}.toml",
+                           env!("CARGO_MANIFEST_DIR"));
+        fs::read_to_string(&path).unwrap_or_else(|_| panic!("missing fixture {path}"))
+    }
+
+    /// Create an empty directory for a test, unique for the test and the process.
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir()
+            .join(format!("rectangular-{name}-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    /// The settings described by every fixture.
+    fn expected() -> Settings {
+        let mut settings = Settings {
+            ignored_apps: vec![IgnoredApp::new("mstsc.exe")],
+            ..Default::default()
+        };
+        settings.hotkeys.0.insert(WindowAction::LeftHalf, "Ctrl+Shift+Left".parse().unwrap());
+        settings.hotkeys.0.remove(&WindowAction::Maximize);
+        settings.leader.hotkey = "Ctrl+Alt+L".parse().unwrap();
+        settings.leader.timeout = std::time::Duration::from_millis(2000);
+        settings.leader.bindings.retain(|binding| ["h", "l"].contains(&binding.keys.as_str()));
+        settings.gaps.inner = 8;
+        settings.gaps.outer = 4;
+        settings.cycling.mode = CyclingMode::Displays;
+        settings.cycling.display_order = DisplayOrder::TopToBottom;
+        settings.cycling.wrap_around = false;
+        settings
+    }
+
+    #[test]
+    fn every_version_parses_to_the_same_settings() {
+        for version in 0..=CURRENT_VERSION {
+            let loaded = settings::parse(&fixture(version))
+                .unwrap_or_else(|error| panic!("version {version}: {error}"));
+
+            assert_eq!(loaded.settings, expected(), "version {version}");
+            assert!(loaded.warnings.is_empty(), "version {version}: {:?}", loaded.warnings);
+        }
+    }
+
+    #[test]
+    fn every_old_version_migrates_to_current() {
+        for version in 0..CURRENT_VERSION {
+            let upgraded = migrate(&fixture(version)).unwrap().expect("an upgrade").text;
+
+            assert!(upgraded.starts_with(&format!("version = {CURRENT_VERSION}\n")));
+            assert_eq!(settings::parse(&upgraded).unwrap().settings, expected());
+            assert_eq!(migrate(&upgraded), Ok(None));
+        }
+    }
+
+    #[test]
+    fn displays_are_moved_to_cycling() {
+        let migrated = migrate(&fixture(1)).unwrap().unwrap();
+
+        assert!(migrated.settings_changed);
+        assert_eq!(migrated.version, 1);
+        let table: toml::Table = migrated.text.parse().unwrap();
+        assert!(!table.contains_key("displays"));
+        assert_eq!(table["cycling"]["display-order"].as_str(), Some("top-to-bottom"));
+        assert_eq!(table["cycling"]["wrap-around"].as_bool(), Some(false));
+    }
+
+    #[test]
+    fn current_version_is_not_migrated() {
+        assert_eq!(migrate(&fixture(CURRENT_VERSION)), Ok(None));
+    }
+
+    #[test]
+    fn future_version_is_rejected() {
+        let text = format!("version = {}\n[gaps]\ninner = 8", CURRENT_VERSION + 1);
+        let error = settings::parse(&text).unwrap_err();
+
+        assert_eq!(error.0.len(), 1);
+        assert_eq!(error.0[0].location.map(|location| location.line), Some(1));
+        assert!(error.0[0].message.contains("please update Rectangular"));
+        assert!(migrate(&text).is_err());
+    }
+
+    #[test]
+    fn invalid_version_is_rejected() {
+        assert!(settings::parse("version = -1").is_err());
+        assert!(settings::parse("version = \"1\"").is_err());
+    }
+
+    #[test]
+    fn old_file_is_upgraded_when_loaded() {
+        for version in 0..CURRENT_VERSION {
+            let dir = scratch_dir(&format!("load-v{version}"));
+            let path = dir.join("settings.toml");
+            fs::write(&path, fixture(version)).unwrap();
+
+            let loaded = settings::load(&path).unwrap();
+
+            assert_eq!(loaded.settings, expected(), "version {version}");
+            assert_eq!(loaded.warnings.len(), 1, "version {version}: {:?}", loaded.warnings);
+            assert!(loaded.warnings[0].message.contains(&format!("from version {version}")));
+            let upgraded = fs::read_to_string(&path).unwrap();
+            assert_eq!(migrate(&upgraded), Ok(None));
+            assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), fixture(version));
+
+            // The upgraded file is loaded as it is.
+            assert!(settings::load(&path).unwrap().warnings.is_empty());
+            assert_eq!(fs::read_to_string(&path).unwrap(), upgraded);
+
+            fs::remove_dir_all(dir).unwrap();
+        }
+    }
+
+    #[test]
+    fn loading_leaves_file_with_only_old_version_untouched() {
+        let dir = scratch_dir("load-version");
+        let path = dir.join("settings.toml");
+        fs::write(&path, "# Gaps.\n[gaps]\ninner = 8\n").unwrap();
+
+        let loaded = settings::load(&path).unwrap();
+
+        assert_eq!(loaded.settings.gaps.inner, 8);
+        assert!(loaded.warnings.is_empty(), "{:?}", loaded.warnings);
+        assert_eq!(fs::read_to_string(&path).unwrap(), "# Gaps.\n[gaps]\ninner = 8\n");
+        assert!(!backup_path(&path).exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn diagnostics_of_upgraded_file_point_into_it() {
+        let dir = scratch_dir("load-diagnostics");
+        let path = dir.join("settings.toml");
+        fs::write(&path, "version = 1\n\n[displays]\norder = \"top-to-bottom\"\nzoom = 2\n")
+            .unwrap();
+
+        // Before the upgrade, the unknown key is only in the upgraded content.
+        let parsed = settings::parse(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(parsed.warnings[0].location, None);
+        assert!(parsed.warnings[0].message.contains("upgraded from version 1"));
+
+        let loaded = settings::load(&path).unwrap();
+        let upgraded = fs::read_to_string(&path).unwrap();
+        let line = upgraded.lines().position(|line| line.starts_with("zoom")).unwrap() + 1;
+
+        assert_eq!(loaded.warnings.len(), 2, "{:?}", loaded.warnings);
+        assert_eq!(loaded.warnings[1].location, Some(Location { line, column: 1 }));
+        assert!(loaded.warnings[1].message.contains("cycling.zoom"));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn old_file_with_invalid_upgrade_is_left_alone() {
+        let dir = scratch_dir("load-invalid");
+        let path = dir.join("settings.toml");
+        let text = "version = 1\n\n[displays]\norder = \"diagonal\"\n";
+        fs::write(&path, text).unwrap();
+
+        let error = settings::load(&path).unwrap_err();
+
+        assert_eq!(error.0.len(), 1);
+        assert_eq!(error.0[0].location, None);
+        assert!(error.0[0].message.contains("upgraded from version 1"), "{}", error.0[0]);
+        assert_eq!(fs::read_to_string(&path).unwrap(), text);
+        assert!(!backup_path(&path).exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn old_file_is_upgraded_on_request_keeping_comments() {
+        let dir = scratch_dir("upgrade");
+        let path = dir.join("settings.toml");
+        let text = "# Gaps.\n[gaps]\ninner = 8\n";
+        fs::write(&path, text).unwrap();
+
+        assert!(upgrade_file(&path).unwrap());
+
+        let upgraded = fs::read_to_string(&path).unwrap();
+        assert_eq!(upgraded, format!("version = {CURRENT_VERSION}\n\n{text}"));
+        assert_eq!(fs::read_to_string(backup_path(&path)).unwrap(), text);
+        assert_eq!(migrate(&upgraded), Ok(None));
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn invalid_file_is_not_upgraded() {
+        let dir = scratch_dir("invalid");
+        let path = dir.join("settings.toml");
+        fs::write(&path, "[gaps]\ninner = -1\n").unwrap();
+
+        assert!(upgrade_file(&path).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "[gaps]\ninner = -1\n");
+        assert!(!backup_path(&path).exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+
+    #[test]
+    fn current_file_is_left_untouched() {
+        let dir = scratch_dir("untouched");
+        let path = dir.join("settings.toml");
+        fs::write(&path, fixture(CURRENT_VERSION)).unwrap();
+
+        assert!(!upgrade_file(&path).unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), fixture(CURRENT_VERSION));
+        assert!(!backup_path(&path).exists());
+
+        fs::remove_dir_all(dir).unwrap();
+    }
+}
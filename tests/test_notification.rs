@@ -0,0 +1,39 @@
+#[cfg(test)]
+mod test_notification {
+    use rectangular::notification::copy_to_wide;
+
+    fn wide(text: &str) -> Vec<u16> {
+        text.encode_utf16().collect()
+    }
+
+    #[test]
+    fn text_is_copied_as_utf16() {
+        let mut tip = [0xFFFF; 16];
+        copy_to_wide(&mut tip, "Größe – Zoom.exe");
+
+        assert_eq!(tip[..15], wide("Größe – Zoom.ex")[..]);
+        assert_eq!(tip[15], 0);
+    }
+
+    #[test]
+    fn long_text_is_truncated_on_a_character_boundary() {
+        // The emoji takes two units, only one of them would fit.
+        let mut title = [0; 4];
+        copy_to_wide(&mut title, "ab😀");
+        assert_eq!(title, [0x61, 0x62, 0, 0]);
+
+        let mut title = [0; 5];
+        copy_to_wide(&mut title, "ab😀c");
+        assert_eq!(title[..4], wide("ab😀")[..]);
+        assert_eq!(title[4], 0);
+    }
+
+    #[test]
+    fn shorter_text_clears_the_rest() {
+        let mut info = [0; 8];
+        copy_to_wide(&mut info, "Rectangular");
+        copy_to_wide(&mut info, "Pause");
+
+        assert_eq!(info, [0x50, 0x61, 0x75, 0x73, 0x65, 0, 0, 0]);
+    }
+}
@@ -0,0 +1,79 @@
+#[cfg(test)]
+mod test_paths {
+    use std::path::{Path, PathBuf};
+
+    use rectangular::paths::{resolve, DataPaths, Environment, PathSource};
+
+    fn environment() -> Environment {
+        Environment {
+            config: None,
+            executable_directory: Some(PathBuf::from(r"D:\Tools\Rectangular")),
+            home: Some(PathBuf::from(r"E:\Home")),
+            app_data: Some(PathBuf::from(r"C:\Users\user\AppData\Roaming")),
+        }
+    }
+
+    /// Resolve the paths, pretending that only the given files exist.
+    fn resolved(environment: &Environment, existing: &[&str]) -> Option<DataPaths> {
+        let existing: Vec<PathBuf> = existing.iter()
+            .map(|name| environment.executable_directory.clone().unwrap().join(name))
+            .collect();
+
+        resolve(environment, |path: &Path| existing.iter().any(|file| file == path))
+    }
+
+    #[test]
+    fn command_line_takes_precedence() {
+        let config = Path::new(r"F:\Configs").join("work.toml");
+        let environment = Environment { config: Some(config.clone()), ..environment() };
+        let paths = resolved(&environment, &["rectangular.portable"]).unwrap();
+
+        assert_eq!(paths.source, PathSource::CommandLine);
+        assert_eq!(paths.directory, PathBuf::from(r"F:\Configs"));
+        assert_eq!(paths.settings, config);
+    }
+
+    #[test]
+    fn relative_config_is_kept_in_the_current_directory() {
+        let environment = Environment { config: Some(PathBuf::from("work.toml")), ..environment() };
+        let paths = resolved(&environment, &[]).unwrap();
+
+        assert_eq!(paths.directory, PathBuf::from("."));
+        assert_eq!(paths.settings, PathBuf::from("work.toml"));
+    }
+
+    #[test]
+    fn marker_or_settings_next_to_the_executable_turn_on_portable_mode() {
+        for existing in ["rectangular.portable", "settings.toml"] {
+            let paths = resolved(&environment(), &[existing]).unwrap();
+
+            assert_eq!(paths.source, PathSource::Portable);
+            assert_eq!(paths.directory, PathBuf::from(r"D:\Tools\Rectangular"));
+            assert_eq!(paths.settings, Path::new(r"D:\Tools\Rectangular").join("settings.toml"));
+        }
+    }
+
+    #[test]
+    fn environment_variable_comes_before_app_data() {
+        let paths = resolved(&environment(), &["other.txt"]).unwrap();
+
+        assert_eq!(paths.source, PathSource::Environment);
+        assert_eq!(paths.directory, PathBuf::from(r"E:\Home"));
+        assert_eq!(paths.settings, Path::new(r"E:\Home").join("settings.toml"));
+    }
+
+    #[test]
+    fn app_data_is_the_last_resort() {
+        let environment = Environment { home: None, ..environment() };
+        let paths = resolved(&environment, &[]).unwrap();
+
+        assert_eq!(paths.source, PathSource::AppData);
+        assert_eq!(paths.directory,
+                   Path::new(r"C:\Users\user\AppData\Roaming").join("Rectangular"));
+        assert_eq!(paths.settings, Path::new(r"C:\Users\user\AppData\Roaming")
+            .join("Rectangular").join("settings.toml"));
+
+        let nothing = Environment { app_data: None, ..environment };
+        assert_eq!(resolved(&nothing, &[]), None);
+    }
+}
@@ -0,0 +1,89 @@
+#[cfg(test)]
+mod test_pause {
+    use std::time::{Duration, Instant};
+
+    use rectangular::action::WindowAction;
+    use rectangular::notification::gray_out;
+    use rectangular::pause::{active_shortcuts, PauseState, Shortcut};
+    use rectangular::settings::{parse, PauseSettings, Settings};
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    fn resuming_after(duration: Option<Duration>) -> PauseSettings {
+        PauseSettings { resume_after: duration, ..Default::default() }
+    }
+
+    #[test]
+    fn toggling_pauses_and_resumes() {
+        let now = Instant::now();
+        let mut state = PauseState::default();
+        assert!(!state.is_paused());
+
+        assert!(state.toggle(now, &resuming_after(None)));
+        assert!(state.is_paused());
+        assert_eq!(state.resume_at(), None);
+
+        // Without a time to resume, it stays paused.
+        assert!(!state.tick(now + 600 * MINUTE));
+        assert!(state.is_paused());
+
+        assert!(!state.toggle(now, &resuming_after(None)));
+        assert_eq!(state, PauseState::default());
+    }
+
+    #[test]
+    fn pause_ends_by_itself_when_configured() {
+        let now = Instant::now();
+        let mut state = PauseState::default();
+
+        state.toggle(now, &resuming_after(Some(5 * MINUTE)));
+        assert_eq!(state.resume_at(), Some(now + 5 * MINUTE));
+        assert_eq!(state.remaining(now + MINUTE), Some(4 * MINUTE));
+
+        assert!(!state.tick(now + 4 * MINUTE));
+        assert!(state.is_paused());
+        assert!(state.tick(now + 5 * MINUTE));
+        assert!(!state.is_paused());
+        assert_eq!(state.remaining(now + 5 * MINUTE), None);
+
+        // Resuming early forgets the time.
+        state.toggle(now, &resuming_after(Some(5 * MINUTE)));
+        state.toggle(now + MINUTE, &resuming_after(Some(5 * MINUTE)));
+        assert!(!state.tick(now + 10 * MINUTE));
+        assert!(!state.is_paused());
+    }
+
+    #[test]
+    fn only_the_pause_shortcut_stays_while_paused() {
+        let settings = Settings::default();
+        let pause = settings.pause.hotkey.unwrap();
+
+        assert_eq!(active_shortcuts(&settings, true), [(Shortcut::Pause, pause)]);
+
+        let running = active_shortcuts(&settings, false);
+        assert_eq!(running.len(), 2 + settings.hotkeys.0.len());
+        assert!(running.contains(&(Shortcut::Pause, pause)));
+        assert!(running.contains(&(Shortcut::Leader, settings.leader.hotkey)));
+        assert!(running.contains(&(Shortcut::Action(WindowAction::LeftHalf),
+                                   "Ctrl+Alt+Left".parse().unwrap())));
+    }
+
+    #[test]
+    fn pausing_can_be_left_without_a_shortcut() {
+        let settings = parse("[pause]\nhotkey = \"\"\n[hotkeys]\ncenter = \"\"").unwrap().settings;
+
+        assert!(active_shortcuts(&settings, true).is_empty());
+        assert!(!active_shortcuts(&settings, false).iter()
+            .any(|(shortcut, _)| matches!(shortcut, Shortcut::Pause
+                                                    | Shortcut::Action(WindowAction::Center))));
+    }
+
+    #[test]
+    fn paused_icon_is_a_lighter_gray() {
+        let mut pixels = [0, 0, 0, 255, 255, 255, 255, 128, 0, 0, 255, 0, 1, 2, 3];
+        gray_out(&mut pixels);
+
+        // Black, white, red, and a trailing partial pixel which is left alone.
+        assert_eq!(pixels, [127, 127, 127, 255, 255, 255, 255, 128, 165, 165, 165, 0, 1, 2, 3]);
+    }
+}
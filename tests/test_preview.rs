@@ -0,0 +1,145 @@
+mod common;
+
+#[cfg(test)]
+mod test_preview {
+    use rectangular::action::WindowAction;
+    use rectangular::engine::{placement, target_rect, Placement};
+    use rectangular::geometry::Rect;
+    use rectangular::preview::{OverlayUpdate, PreviewState};
+    use rectangular::settings::{Cycling, CyclingMode, Gaps, Settings};
+    use windows::Win32::Foundation::HWND;
+
+    use crate::common::{FakeWindowSystem, BORDER};
+
+    const LEFT_DISPLAY: Rect = Rect::new(0, 0, 1920, 1040);
+    const RIGHT_DISPLAY: Rect = Rect::new(1920, 0, 4480, 1400);
+
+    /// Two displays side by side, without windows.
+    fn system() -> FakeWindowSystem {
+        FakeWindowSystem::with_displays(&[LEFT_DISPLAY, RIGHT_DISPLAY])
+    }
+
+    fn settings_with_gaps() -> Settings {
+        Settings { gaps: Gaps { inner: 10, outer: 10 }, ..Default::default() }
+    }
+
+    #[test]
+    fn placement_compensates_the_frame() {
+        let system = system();
+        let window = system.add_at(1, Rect::new(100, 100, 900, 700), LEFT_DISPLAY);
+        let settings = settings_with_gaps();
+
+        let visible = target_rect(WindowAction::LeftHalf, &LEFT_DISPLAY,
+                                  &Rect::new(100, 100, 900, 700), &settings.gaps);
+        assert_eq!(placement(window, WindowAction::LeftHalf, &settings, &system),
+                   Some(Placement {
+                       visible,
+                       window: Rect::new(visible.left - BORDER, visible.top,
+                                         visible.right + BORDER, visible.bottom + BORDER),
+                   }));
+        assert_eq!(placement(HWND(2), WindowAction::LeftHalf, &settings, &system), None);
+    }
+
+    #[test]
+    fn hovering_shows_where_the_window_lands() {
+        let system = system();
+        let window = system.add_at(1, Rect::new(2000, 100, 2800, 700), RIGHT_DISPLAY);
+        let settings = settings_with_gaps();
+        let mut preview = PreviewState::default();
+
+        // The preview is on the display of the window, with gaps, but without invisible borders.
+        let update = preview.hover(Some(WindowAction::RightHalf), Some(window), &system,
+                                   &settings);
+        assert_eq!(update, OverlayUpdate::Show(Rect::new(3205, 10, 4470, 1390)));
+        assert_eq!(preview.shown(), Some(Rect::new(3205, 10, 4470, 1390)));
+    }
+
+    #[test]
+    fn moving_between_buttons_moves_the_overlay() {
+        let system = system();
+        let window = system.add_at(1, Rect::new(100, 100, 900, 700), LEFT_DISPLAY);
+        let settings = Settings::default();
+        let mut preview = PreviewState::default();
+
+        let updates: Vec<OverlayUpdate> = [Some(WindowAction::LeftHalf),
+                                           Some(WindowAction::LeftHalf),
+                                           Some(WindowAction::TopLeft), None, None]
+            .into_iter()
+            .map(|action| preview.hover(action, Some(window), &system, &settings))
+            .collect();
+
+        assert_eq!(updates, [OverlayUpdate::Show(Rect::new(0, 0, 960, 1040)),
+                             OverlayUpdate::Unchanged,
+                             OverlayUpdate::Show(Rect::new(0, 0, 960, 520)),
+                             OverlayUpdate::Hide,
+                             OverlayUpdate::Unchanged]);
+    }
+
+    #[test]
+    fn actions_with_the_same_region_keep_the_overlay() {
+        let system = system();
+        let window = system.add_at(1, Rect::new(100, 100, 900, 700), LEFT_DISPLAY);
+        let settings = Settings::default();
+        let mut preview = PreviewState::default();
+
+        preview.hover(Some(WindowAction::FirstTwoThirds), Some(window), &system, &settings);
+
+        // Two thirds of the width, so a 2/3 left half is the same region.
+        let settings = Settings {
+            cycling: Cycling {
+                mode: CyclingMode::Sizes,
+                sizes: vec![2.0 / 3.0],
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        system.move_to(window, Rect::new(0, 0, 1280, 1040));
+        assert_eq!(preview.hover(Some(WindowAction::LeftHalf), Some(window), &system, &settings),
+                   OverlayUpdate::Unchanged);
+    }
+
+    #[test]
+    fn nothing_is_shown_without_a_target() {
+        let system = system();
+        let settings = Settings::default();
+        let mut preview = PreviewState::default();
+
+        assert_eq!(preview.hover(Some(WindowAction::Maximize), None, &system, &settings),
+                   OverlayUpdate::Unchanged);
+        assert_eq!(preview.hover(Some(WindowAction::Maximize), Some(HWND(5)), &system,
+                                 &settings),
+                   OverlayUpdate::Unchanged);
+        assert_eq!(preview.shown(), None);
+    }
+
+    #[test]
+    fn hiding_the_control_center_hides_the_overlay() {
+        let system = system();
+        let window = system.add_at(1, Rect::new(100, 100, 900, 700), LEFT_DISPLAY);
+        let settings = Settings::default();
+        let mut preview = PreviewState::default();
+
+        preview.hover(Some(WindowAction::Maximize), Some(window), &system, &settings);
+        assert_eq!(preview.hide(), OverlayUpdate::Hide);
+        assert_eq!(preview.hide(), OverlayUpdate::Unchanged);
+
+        // The hovered action is forgotten, so refreshing doesn't bring the overlay back.
+        assert_eq!(preview.refresh(Some(window), &system, &settings), OverlayUpdate::Unchanged);
+    }
+
+    #[test]
+    fn refresh_follows_repeated_actions() {
+        let system = system();
+        let window = system.add_at(1, Rect::new(100, 100, 900, 700), LEFT_DISPLAY);
+        let mut settings = Settings::default();
+        settings.cycling.mode = CyclingMode::Sizes;
+        let mut preview = PreviewState::default();
+
+        preview.hover(Some(WindowAction::LeftHalf), Some(window), &system, &settings);
+
+        // Once the window is in place, the same action cycles to two thirds.
+        system.move_to(window, Rect::new(0, 0, 960, 1040));
+        assert_eq!(preview.refresh(Some(window), &system, &settings),
+                   OverlayUpdate::Show(Rect::new(0, 0, 1280, 1040)));
+    }
+}
@@ -0,0 +1,85 @@
+mod common;
+
+#[cfg(test)]
+mod test_raster {
+    use rectangular::display_list::{Color, DisplayList, TextAlign};
+    use rectangular::geometry::Rect;
+
+    use crate::common::raster::{render, Image, DIFF_COLOR};
+
+    const RED: Color = Color::rgb(255, 0, 0);
+    const BLUE: Color = Color::rgb(0, 0, 255);
+    const WHITE: Color = Color::rgb(255, 255, 255);
+
+    #[test]
+    fn fills_are_clipped_to_the_image() {
+        let mut list = DisplayList::new();
+        list.fill(Rect::new(-5, -5, 3, 2), RED);
+        let image = render(&list, 4, 4);
+
+        assert_eq!(image.pixel(0, 0), Some(RED));
+        assert_eq!(image.pixel(2, 1), Some(RED));
+        assert_eq!(image.pixel(3, 1), Some(Color::default()));
+        assert_eq!(image.pixel(2, 2), Some(Color::default()));
+        assert_eq!(image.pixel(4, 0), None);
+    }
+
+    #[test]
+    fn strokes_are_drawn_inside() {
+        let mut list = DisplayList::new();
+        list.fill(Rect::new(0, 0, 8, 8), WHITE);
+        list.stroke(Rect::new(1, 1, 7, 7), BLUE, 2);
+        let image = render(&list, 8, 8);
+
+        assert_eq!(image.pixel(0, 0), Some(WHITE));
+        assert_eq!(image.pixel(1, 1), Some(BLUE));
+        assert_eq!(image.pixel(2, 4), Some(BLUE));
+        assert_eq!(image.pixel(6, 6), Some(BLUE));
+        assert_eq!(image.pixel(3, 3), Some(WHITE));
+        assert_eq!(image.pixel(4, 4), Some(WHITE));
+        assert_eq!(image.pixel(7, 7), Some(WHITE));
+    }
+
+    #[test]
+    fn text_is_greeked_within_its_rectangle() {
+        let mut list = DisplayList::new();
+        list.fill(Rect::new(0, 0, 40, 20), WHITE);
+        list.text(Rect::new(0, 0, 20, 20), "A bcdefgh", BLUE, TextAlign::Left);
+        let image = render(&list, 40, 20);
+
+        let row = |y: i32| -> Vec<bool> {
+            (0..40).map(|x| image.pixel(x, y) == Some(BLUE)).collect()
+        };
+
+        // A capital letter is taller than a small one, and a space is empty.
+        assert!(row(8)[0] && !row(8)[16]);
+        assert!(row(13)[0] && !row(13)[8] && row(13)[16]);
+        // Nothing is drawn past the rectangle.
+        assert!(row(13)[19] && !row(13)[20]);
+    }
+
+    #[test]
+    fn png_round_trip_keeps_pixels() {
+        let mut list = DisplayList::new();
+        list.fill(Rect::new(0, 0, 30, 20), WHITE);
+        list.fill(Rect::new(5, 5, 25, 15), BLUE);
+        let image = render(&list, 30, 20);
+
+        assert_eq!(Image::from_png(&image.to_png()).unwrap(), image);
+    }
+
+    #[test]
+    fn diff_marks_differing_pixels() {
+        let image = Image::new(4, 3, WHITE);
+        let mut other = image.clone();
+        other.fill(&Rect::new(1, 1, 3, 2), RED);
+
+        let (count, diff) = image.diff(&other);
+        assert_eq!(count, 2);
+        assert_eq!(diff.pixel(1, 1), Some(DIFF_COLOR));
+        assert_eq!(diff.pixel(0, 0), Some(WHITE));
+
+        assert_eq!(image.diff(&image).0, 0);
+        assert_eq!(image.diff(&Image::new(4, 2, WHITE)).0, 12);
+    }
+}
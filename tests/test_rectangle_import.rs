@@ -0,0 +1,84 @@
+#[cfg(test)]
+mod test_rectangle_import {
+    use std::fs;
+
+    use rectangular::action::WindowAction;
+    use rectangular::hotkey::{Hotkey, Modifiers};
+    use rectangular::import::ImportError;
+    use rectangular::rectangle_import::{import, RectangleImport, RectangleImportOptions};
+    use rectangular::settings::{self, CyclingMode, Gaps, Settings};
+
+    fn fixture() -> String {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"),
+                           "/tests/fixtures/rectangle/RectangleConfig.json");
+        fs::read_to_string(path).unwrap()
+    }
+
+    fn imported(options: RectangleImportOptions) -> RectangleImport {
+        import(&fixture(), &options).unwrap()
+    }
+
+    fn hotkey(text: &str) -> Hotkey {
+        text.parse().unwrap()
+    }
+
+    #[test]
+    fn maps_shortcuts_to_actions() {
+        let imported = imported(RectangleImportOptions::default());
+
+        assert_eq!(imported.hotkeys.get(&WindowAction::LeftHalf), Some(&hotkey("Ctrl+Alt+Left")));
+        assert_eq!(imported.hotkeys.get(&WindowAction::Maximize), Some(&hotkey("Ctrl+Alt+Enter")));
+        assert_eq!(imported.hotkeys.get(&WindowAction::TopLeft), Some(&hotkey("Ctrl+Alt+U")));
+        assert_eq!(imported.hotkeys.get(&WindowAction::BottomHalf), None);
+    }
+
+    #[test]
+    fn command_is_mapped_to_the_chosen_modifier() {
+        let with_win = imported(RectangleImportOptions::default());
+        let with_shift = imported(RectangleImportOptions { command: Modifiers::SHIFT });
+
+        assert_eq!(with_win.hotkeys.get(&WindowAction::Center), Some(&hotkey("Ctrl+Win+C")));
+        assert_eq!(with_shift.hotkeys.get(&WindowAction::Center), Some(&hotkey("Ctrl+Shift+C")));
+    }
+
+    #[test]
+    fn carries_over_preferences() {
+        let imported = imported(RectangleImportOptions::default());
+
+        assert_eq!(imported.gaps, Some(Gaps { inner: 10, outer: 10 }));
+        assert_eq!(imported.cycling, Some(CyclingMode::Displays));
+    }
+
+    #[test]
+    fn reports_everything_not_imported() {
+        let report: Vec<String> = imported(RectangleImportOptions::default()).report.notes.iter()
+            .map(ToString::to_string)
+            .collect();
+
+        assert_eq!(report, vec![
+            "shortcut \"restore\": Rectangular has no such action",
+            "shortcut \"nextDisplay\": Rectangular has no such action",
+            "shortcut \"firstThird\": the key code 200 has no counterpart on Windows",
+            "shortcut \"lastThird\": Ctrl+Alt+Left is already used by \"left-half\"",
+            "preference \"almostMaximizeHeight\": the preference is not supported by Rectangular",
+        ]);
+    }
+
+    #[test]
+    fn fragment_matches_imported_settings() {
+        let imported = imported(RectangleImportOptions::default());
+        let fragment = imported.to_toml();
+
+        let mut expected = Settings::default();
+        imported.apply_to(&mut expected);
+
+        assert!(fragment.starts_with("# Imported from Rectangle.\n#\n# Not imported:\n"));
+        assert_eq!(settings::parse(&fragment).unwrap().settings, expected);
+    }
+
+    #[test]
+    fn rejects_other_files() {
+        assert!(matches!(import("{}", &Default::default()), Err(ImportError::Format(_))));
+        assert!(matches!(import("{", &Default::default()), Err(ImportError::Json(_))));
+    }
+}
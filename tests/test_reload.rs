@@ -0,0 +1,265 @@
+#[cfg(test)]
+mod test_reload {
+    use std::cell::RefCell;
+    use std::fs;
+    use std::sync::mpsc::{self, Receiver};
+    use std::time::{Duration, Instant};
+
+    use rectangular::action::WindowAction;
+    use rectangular::chord::LeaderConfig;
+    use rectangular::hotkey::Hotkey;
+    use rectangular::paths::{resolve, Environment};
+    use rectangular::reload::{
+        apply, reload, settings_directory, watch, ChangeSource, Debouncer, Reload, SettingsDiff,
+        SettingsTarget,
+    };
+    use rectangular::settings::{parse, ControlCenterSettings, Settings, ThemeSettings};
+
+    const DELAY: Duration = Duration::from_millis(300);
+
+    /// A target recording every call, and refusing shortcuts listed in `taken`.
+    #[derive(Default)]
+    struct FakeTarget {
+        calls: RefCell<Vec<String>>,
+        taken: Vec<Hotkey>,
+    }
+
+    impl SettingsTarget for FakeTarget {
+        fn register_hotkey(&self, action: WindowAction, hotkey: &Hotkey) -> bool {
+            self.calls.borrow_mut().push(format!("register {action} {hotkey}"));
+            !self.taken.contains(hotkey)
+        }
+
+        fn unregister_hotkey(&self, action: WindowAction) {
+            self.calls.borrow_mut().push(format!("unregister {action}"));
+        }
+
+        fn replace_leader(&self, leader: &LeaderConfig) -> bool {
+            self.calls.borrow_mut().push(format!("leader {}", leader.hotkey));
+            true
+        }
+
+        fn replace_pause_hotkey(&self, hotkey: Option<&Hotkey>) -> bool {
+            let name = hotkey.map_or("none".to_string(), ToString::to_string);
+            self.calls.borrow_mut().push(format!("pause {name}"));
+            hotkey.is_none_or(|hotkey| !self.taken.contains(hotkey))
+        }
+
+        fn rebuild_menu(&self, _settings: &Settings) {
+            self.calls.borrow_mut().push("menu".to_string());
+        }
+
+        fn configure_control_center(&self, _settings: ControlCenterSettings) {
+            self.calls.borrow_mut().push("control center".to_string());
+        }
+
+        fn apply_theme(&self, settings: &ThemeSettings) {
+            self.calls.borrow_mut().push(format!("theme {:?}", settings.preset));
+        }
+    }
+
+    /// Made-up changes of the settings directory, sent through a channel.
+    struct FakeChanges(Receiver<()>);
+
+    impl ChangeSource for FakeChanges {
+        fn wait(&mut self) -> bool {
+            self.0.recv().is_ok()
+        }
+    }
+
+    fn changed(active: &Settings, text: &str) -> (Settings, SettingsDiff) {
+        match reload(active, parse(text)) {
+            Reload::Changed { settings, diff, .. } => (*settings, diff),
+            other => panic!("expected changed settings, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn debouncer_waits_for_events_to_settle() {
+        let start = Instant::now();
+        let mut debouncer = Debouncer::new(DELAY);
+
+        assert!(!debouncer.tick(start));
+
+        debouncer.event(start);
+        debouncer.event(start + Duration::from_millis(200));
+        assert!(!debouncer.tick(start + Duration::from_millis(400)));
+        assert!(debouncer.tick(start + Duration::from_millis(500)));
+
+        // A series of events fires only once.
+        assert!(!debouncer.tick(start + Duration::from_millis(900)));
+        assert_eq!(debouncer.deadline(), None);
+    }
+
+    #[test]
+    fn watcher_reports_every_change_until_the_source_ends() {
+        let (changes, source) = mpsc::channel();
+        let (notify, notifications) = mpsc::channel();
+        let watcher = watch(FakeChanges(source), move || notify.send(()).unwrap());
+
+        for _ in 0..3 {
+            changes.send(()).unwrap();
+        }
+        drop(changes);
+        watcher.join().unwrap();
+
+        // An editor saving the file in a few steps still reloads it once.
+        let start = Instant::now();
+        let mut debouncer = Debouncer::new(DELAY);
+        for (index, _) in notifications.iter().enumerate() {
+            debouncer.event(start + Duration::from_millis(index as u64 * 10));
+        }
+
+        assert_eq!(debouncer.deadline(), Some(start + Duration::from_millis(20) + DELAY));
+        assert!(debouncer.tick(start + Duration::from_secs(1)));
+        assert!(!debouncer.tick(start + Duration::from_secs(2)));
+    }
+
+    #[test]
+    fn missing_settings_directory_is_created() {
+        let directory = std::env::temp_dir()
+            .join(format!("rectangular-reload-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&directory);
+        let environment = Environment { app_data: Some(directory.clone()), ..Default::default() };
+        let paths = resolve(&environment, |_| false).unwrap();
+
+        assert_eq!(settings_directory(&paths).unwrap(), directory.join("Rectangular"));
+        assert!(paths.directory.is_dir());
+        assert!(!paths.settings.exists());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn identical_settings_are_unchanged() {
+        let active = parse("[gaps]\ninner = 4").unwrap().settings;
+
+        assert_eq!(reload(&active, parse("[gaps] # same\ninner = 4")), Reload::Unchanged);
+    }
+
+    #[test]
+    fn invalid_file_is_rejected() {
+        let active = Settings::default();
+
+        assert!(matches!(reload(&active, parse("[gaps]\ninner = -1")), Reload::Rejected(_)));
+    }
+
+    #[test]
+    fn only_changed_hotkeys_are_reregistered() {
+        let active = Settings::default();
+        let (settings, diff) = changed(&active, "[hotkeys]\nleft-half = \"Ctrl+Shift+H\"");
+        let target = FakeTarget::default();
+
+        // The menu shows shortcuts next to actions.
+        assert!(diff.menu);
+        assert!(apply(&diff, &settings, &target).is_empty());
+        assert_eq!(*target.calls.borrow(), vec![
+            "unregister left-half",
+            "register left-half Ctrl+Shift+H",
+            "menu",
+        ]);
+    }
+
+    #[test]
+    fn swapped_hotkeys_are_unregistered_first() {
+        let active = Settings::default();
+        let text = "[hotkeys]\nleft-half = \"Ctrl+Alt+Right\"\nright-half = \"Ctrl+Alt+Left\"";
+        let (settings, diff) = changed(&active, text);
+        let target = FakeTarget::default();
+
+        apply(&diff, &settings, &target);
+        assert_eq!(*target.calls.borrow(), vec![
+            "unregister left-half",
+            "unregister right-half",
+            "register left-half Ctrl+Alt+Right",
+            "register right-half Ctrl+Alt+Left",
+            "menu",
+        ]);
+    }
+
+    #[test]
+    fn unbound_hotkey_is_only_unregistered() {
+        let active = Settings::default();
+        let (settings, diff) = changed(&active, "[hotkeys]\nmaximize = \"\"");
+        let target = FakeTarget::default();
+
+        apply(&diff, &settings, &target);
+        assert_eq!(*target.calls.borrow(), vec!["unregister maximize", "menu"]);
+    }
+
+    #[test]
+    fn unrelated_changes_need_no_work() {
+        let active = Settings::default();
+        let (settings, diff) = changed(&active, "[gaps]\ninner = 8\n[cycling]\nmode = \"none\"");
+        let target = FakeTarget::default();
+
+        assert!(diff.is_empty());
+        assert_eq!(settings.gaps.inner, 8);
+        apply(&diff, &settings, &target);
+        assert!(target.calls.borrow().is_empty());
+    }
+
+    #[test]
+    fn leader_and_control_center_changes_are_applied() {
+        let active = Settings::default();
+        let text = "[leader]\nhotkey = \"Ctrl+Alt+L\"\n\
+                    [control-center]\nposition = \"screen-center\"";
+        let (settings, diff) = changed(&active, text);
+        let target = FakeTarget::default();
+
+        assert!(diff.leader && diff.control_center && !diff.menu);
+        apply(&diff, &settings, &target);
+        assert_eq!(*target.calls.borrow(), vec!["leader Ctrl+Alt+L", "control center"]);
+    }
+
+    #[test]
+    fn theme_changes_are_applied() {
+        let active = Settings::default();
+        let (settings, diff) = changed(&active, "[theme]\npreset = \"dark\"");
+        let target = FakeTarget::default();
+
+        assert!(diff.theme && !diff.control_center && !diff.is_empty());
+        apply(&diff, &settings, &target);
+        assert_eq!(*target.calls.borrow(), vec!["theme Dark"]);
+
+        let (_, diff) = changed(&settings, "[theme]\npreset = \"dark\"\n\
+                                            [theme.colors]\naccent = \"#E81123\"");
+        assert!(diff.theme);
+    }
+
+    #[test]
+    fn pause_hotkey_changes_are_applied() {
+        let active = Settings::default();
+        let (settings, diff) = changed(&active, "[pause]\nhotkey = \"Ctrl+Alt+Shift+P\"");
+        let target = FakeTarget {
+            taken: vec!["Ctrl+Alt+Shift+P".parse().unwrap()],
+            ..Default::default()
+        };
+
+        // The menu shows the shortcut next to the item.
+        assert!(diff.pause && diff.menu);
+        assert_eq!(apply(&diff, &settings, &target), vec!["Ctrl+Alt+Shift+P".parse().unwrap()]);
+        assert_eq!(*target.calls.borrow(), vec!["pause Ctrl+Alt+Shift+P", "menu"]);
+
+        let (settings, diff) = changed(&settings, "[pause]\nhotkey = \"\"");
+        let target = FakeTarget::default();
+        assert!(apply(&diff, &settings, &target).is_empty());
+        assert_eq!(*target.calls.borrow(), vec!["pause none", "menu"]);
+
+        // The time to resume is read when pausing.
+        let (_, diff) = changed(&settings, "[pause]\nhotkey = \"\"\nresume-after-minutes = 5");
+        assert!(diff.is_empty());
+    }
+
+    #[test]
+    fn taken_hotkeys_are_reported() {
+        let active = Settings::default();
+        let (settings, diff) = changed(&active, "[hotkeys]\ncenter = \"Ctrl+Alt+X\"");
+        let target = FakeTarget {
+            taken: vec!["Ctrl+Alt+X".parse().unwrap()],
+            ..Default::default()
+        };
+
+        assert_eq!(apply(&diff, &settings, &target), vec!["Ctrl+Alt+X".parse().unwrap()]);
+    }
+}
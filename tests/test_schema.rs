@@ -0,0 +1,102 @@
+#[cfg(test)]
+mod test_schema {
+    use std::env;
+    use std::fs;
+
+    use serde_json::Value;
+
+    use rectangular::schema::{
+        settings_schema, settings_schema_text, CONTROL_CENTER_POSITIONS, CYCLING_MODES,
+        DISPLAY_ORDERS, THEME_PRESETS, TRAY_CLICKS,
+    };
+    use rectangular::settings::{parse, DEFAULT_SETTINGS};
+    use rectangular::theme::THEME_COLORS;
+
+    /// Set this variable to write the generated schema to the repository instead of comparing it.
+    const UPDATE_VARIABLE: &str = "RECTANGULAR_UPDATE_SCHEMA";
+
+    #[test]
+    fn checked_in_schema_is_up_to_date() {
+        let path = concat!(env!("CARGO_MANIFEST_DIR"), "/schema/settings.schema.json");
+        let generated = settings_schema_text();
+
+        if env::var_os(UPDATE_VARIABLE).is_some() {
+            fs::write(path, &generated).unwrap();
+        }
+
+        let checked_in = fs::read_to_string(path).unwrap_or_default().replace("\r\n", "\n");
+        assert!(checked_in == generated,
+                "{path} is stale, run the tests with {UPDATE_VARIABLE}=1 to update it");
+    }
+
+    #[test]
+    fn schema_is_valid_json() {
+        let parsed: Value = serde_json::from_str(&settings_schema_text()).unwrap();
+        assert_eq!(parsed, settings_schema());
+    }
+
+    /// Every key of the default settings file should be described by the schema.
+    #[test]
+    fn schema_covers_all_settings() {
+        fn check(table: &toml::Table, schema: &Value, path: &str) {
+            let properties = schema.get("properties").unwrap_or_else(|| panic!("{path}"));
+            for (key, value) in table {
+                let path = format!("{path}.{key}");
+                let property = properties.get(key).unwrap_or_else(|| panic!("{path} is missing"));
+
+                if let (toml::Value::Table(table), Some(_)) = (value, property.get("properties")) {
+                    check(table, property, &path);
+                }
+            }
+        }
+
+        let defaults: toml::Table = DEFAULT_SETTINGS.parse().unwrap();
+        check(&defaults, &settings_schema(), "settings");
+    }
+
+    #[test]
+    fn enum_names_are_accepted_by_the_parser() {
+        for (mode, name) in CYCLING_MODES {
+            let settings = parse(&format!("[cycling]\nmode = \"{name}\"")).unwrap().settings;
+            assert_eq!(settings.cycling.mode, mode);
+        }
+        for (order, name) in DISPLAY_ORDERS {
+            let text = format!("[cycling]\ndisplay-order = \"{name}\"");
+            assert_eq!(parse(&text).unwrap().settings.cycling.display_order, order);
+        }
+        for (position, name) in CONTROL_CENTER_POSITIONS {
+            let text = format!("[control-center]\nposition = \"{name}\"");
+            assert_eq!(parse(&text).unwrap().settings.control_center.position, position);
+        }
+        for (click, name) in TRAY_CLICKS {
+            let settings = parse(&format!("[tray]\nleft-click = \"{name}\"")).unwrap().settings;
+            assert_eq!(settings.tray.left_click, click);
+        }
+        for (preset, name) in THEME_PRESETS {
+            let settings = parse(&format!("[theme]\npreset = \"{name}\"")).unwrap().settings;
+            assert_eq!(settings.theme.preset, preset);
+        }
+        for (role, name) in THEME_COLORS {
+            let text = format!("[theme.colors]\n{name} = \"#123456\"");
+            let colors = parse(&text).unwrap().settings.theme.colors;
+            assert_eq!(colors.keys().collect::<Vec<_>>(), [&role]);
+        }
+    }
+
+    #[test]
+    fn shortcuts_have_defaults_and_may_be_empty() {
+        let schema = settings_schema();
+        let hotkeys = schema.get("properties").and_then(|properties| properties.get("hotkeys"))
+            .and_then(|hotkeys| hotkeys.get("properties"))
+            .unwrap();
+        let left_half = hotkeys.get("left-half").unwrap();
+        let optional = schema.get("definitions")
+            .and_then(|definitions| definitions.get("optional-hotkey"))
+            .and_then(|hotkey| hotkey.get("pattern"))
+            .and_then(Value::as_str)
+            .unwrap();
+
+        assert_eq!(left_half.get("default"), Some(&Value::from("Ctrl+Alt+Left")));
+        assert!(optional.starts_with(r"^\s*$|"));
+    }
+}
@@ -0,0 +1,305 @@
+#[cfg(test)]
+mod test_settings {
+    use std::time::Duration;
+
+    use rectangular::action::WindowAction;
+    use rectangular::display_list::Color;
+    use rectangular::geometry::{FracRect, Rect};
+    use rectangular::grid_picker::GridSize;
+    use rectangular::settings::{
+        parse, CyclingMode, Diagnostic, GridSettings, IgnoredApp, Location, Settings, Severity,
+        ThemePreset, TrayClick, DEFAULT_SETTINGS,
+    };
+    use rectangular::theme::ThemeColor;
+
+    fn rejected(text: &str) -> Vec<Diagnostic> {
+        parse(text).expect_err("Settings should be rejected.").0
+    }
+
+    #[test]
+    fn empty_file_gives_defaults() {
+        let loaded = parse("").unwrap();
+
+        assert_eq!(loaded.settings, Settings::default());
+        assert!(loaded.warnings.is_empty());
+    }
+
+    #[test]
+    fn missing_keys_keep_defaults() {
+        let loaded = parse("[gaps]\ninner = 8\n\n[tray]\nleft-click = \"context-menu\"\n").unwrap();
+        let settings = loaded.settings;
+
+        assert_eq!(settings.gaps.inner, 8);
+        assert_eq!(settings.gaps.outer, 0);
+        assert_eq!(settings.tray.left_click, TrayClick::ContextMenu);
+        assert!(settings.tray.show_notifications);
+        assert_eq!(settings.cycling, Default::default());
+    }
+
+    #[test]
+    fn hotkeys_are_merged_with_defaults() {
+        let text = "[hotkeys]\nleft-half = \"Win+Alt+H\"\nmaximize = \"\"\n";
+        let settings = parse(text).unwrap().settings;
+
+        assert_eq!(settings.hotkeys.get(WindowAction::LeftHalf).unwrap().to_string(),
+                   "Alt+Win+H");
+        assert_eq!(settings.hotkeys.get(WindowAction::Maximize), None);
+        assert_eq!(settings.hotkeys.get(WindowAction::RightHalf).unwrap().to_string(),
+                   "Ctrl+Alt+Right");
+    }
+
+    #[test]
+    fn leader_sequences_replace_defaults() {
+        let text = "[leader]\ntimeout-ms = 500\n\n[leader.sequences]\nh = \"left-half\"\n";
+        let leader = parse(text).unwrap().settings.leader;
+
+        assert_eq!(leader.timeout, Duration::from_millis(500));
+        assert_eq!(leader.bindings.len(), 1);
+        assert_eq!(leader.bindings[0].action, WindowAction::LeftHalf);
+    }
+
+    #[test]
+    fn unknown_keys_are_warnings_with_location() {
+        let text = "ignored-apps = [\"mstsc.exe\"]\n\
+                    [gaps]\ninner = 4\ninnner = 5\n\n\
+                    [colors]\nx = 1\n";
+        let loaded = parse(text).unwrap();
+
+        assert_eq!(loaded.settings.gaps.inner, 4);
+        assert_eq!(loaded.settings.ignored_apps, vec![IgnoredApp::new("mstsc.exe")]);
+        assert_eq!(loaded.warnings, vec![
+            Diagnostic::warning(Some(Location { line: 6, column: 2 }), "unknown key \"colors\""),
+            Diagnostic::warning(Some(Location { line: 4, column: 1 }),
+                                "unknown key \"gaps.innner\""),
+        ]);
+    }
+
+    #[test]
+    fn type_errors_are_reported_with_location() {
+        let errors = rejected("[gaps]\ninner = \"wide\"\n");
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].severity, Severity::Error);
+        assert_eq!(errors[0].location, Some(Location { line: 2, column: 9 }));
+        assert!(errors[0].to_string().starts_with("2:9: error: "), "{}", errors[0]);
+    }
+
+    #[test]
+    fn invalid_values_are_reported_with_location() {
+        let errors = rejected("[hotkeys]\nleft-half = \"Ctrl+Nope\"\n");
+        assert_eq!(errors[0].location, Some(Location { line: 2, column: 13 }));
+        assert!(errors[0].message.contains("unknown key \"Nope\""), "{}", errors[0]);
+
+        let errors = rejected("[cycling]\nmode = \"forever\"\n");
+        assert_eq!(errors[0].location, Some(Location { line: 2, column: 8 }));
+
+        let errors = rejected("[hotkeys]\nnot-an-action = \"Ctrl+A\"\n");
+        assert_eq!(errors[0].location.map(|location| location.line), Some(2));
+        assert!(errors[0].message.contains("unknown action"), "{}", errors[0]);
+    }
+
+    #[test]
+    fn syntax_errors_are_reported_with_location() {
+        let errors = rejected("[gaps]\ninner = \n");
+
+        assert_eq!(errors[0].location.map(|location| location.line), Some(2));
+    }
+
+    #[test]
+    fn conflicting_hotkeys_are_rejected() {
+        let errors = rejected("[hotkeys]\nright-half = \"Ctrl+Alt+Left\"\n");
+
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].location, Some(Location { line: 2, column: 1 }));
+        assert!(errors[0].message.contains("already used by left-half"), "{}", errors[0]);
+    }
+
+    #[test]
+    fn shortcuts_conflict_by_the_key_they_register() {
+        let errors = rejected("[hotkeys]\nright-half = \"Ctrl+Alt+sc:0x16\"\n");
+
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("already used by right-half"), "{}", errors[0]);
+    }
+
+    #[test]
+    fn shortcuts_conflicting_on_another_layout_are_rejected() {
+        // The key of `Y` on the US layout is `Z` on the German one.
+        let errors = rejected("[hotkeys]\ncenter = \"Ctrl+Alt+Z\"\n\
+                               maximize = \"Ctrl+Alt+sc:0x15\"\n");
+
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("on the German layout"), "{}", errors[0]);
+    }
+
+    #[test]
+    fn invalid_leader_sequences_are_rejected() {
+        let errors = rejected("[leader.sequences]\nt = \"top-half\"\ntl = \"top-left\"\n");
+
+        assert_eq!(errors[0].location.map(|location| location.line), Some(1));
+        assert!(errors[0].message.contains("prefix"), "{}", errors[0]);
+    }
+
+    #[test]
+    fn pause_settings_are_read() {
+        let pause = Settings::default().pause;
+        assert_eq!(pause.hotkey, Some("Ctrl+Alt+P".parse().unwrap()));
+        assert_eq!(pause.resume_after, None);
+
+        let text = "[pause]\nhotkey = \"Ctrl+Shift+F12\"\nresume-after-minutes = 30\n";
+        let pause = parse(text).unwrap().settings.pause;
+        assert_eq!(pause.hotkey, Some("Ctrl+Shift+F12".parse().unwrap()));
+        assert_eq!(pause.resume_after, Some(Duration::from_secs(30 * 60)));
+
+        let text = "[pause]\nhotkey = \"\"\nresume-after-minutes = 0\n";
+        let pause = parse(text).unwrap().settings.pause;
+        assert_eq!(pause.hotkey, None);
+        assert_eq!(pause.resume_after, None);
+    }
+
+    #[test]
+    fn conflicting_pause_shortcuts_are_rejected() {
+        let errors = rejected("[pause]\nhotkey = \"Ctrl+Alt+Space\"\n");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].location, Some(Location { line: 2, column: 1 }));
+        assert!(errors[0].message.contains("leader key"), "{}", errors[0]);
+
+        let errors = rejected("[pause]\nhotkey = \"Ctrl+Alt+Left\"\n");
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].message.contains("the pause shortcut"), "{}", errors[0]);
+    }
+
+    #[test]
+    fn ignored_apps_are_names_or_tables() {
+        let text = "ignored-apps = [\"mstsc.exe\", { app = \"vmconnect.exe\", \
+                    title = \"*Ubuntu*\", suspend-hotkeys = true }]\n";
+        let ignored_apps = parse(text).unwrap().settings.ignored_apps;
+
+        assert_eq!(ignored_apps, vec![IgnoredApp::new("mstsc.exe"), IgnoredApp {
+            title: Some("*Ubuntu*".to_string()),
+            suspend_hotkeys: true,
+            ..IgnoredApp::new("vmconnect.exe")
+        }]);
+    }
+
+    #[test]
+    fn invalid_ignored_apps_are_rejected() {
+        let errors = rejected("ignored-apps = [\"mstsc.exe\", { class = \"Chrome*\" }]\n");
+        assert_eq!(errors[0].location.map(|location| location.line), Some(1));
+        assert!(errors[0].message.contains("app"), "{}", errors[0]);
+
+        let errors = rejected("ignored-apps = [{ app = \"x.exe\", titel = \"\" }]\n");
+        assert!(errors[0].message.contains("titel"), "{}", errors[0]);
+
+        let errors = rejected("ignored-apps = [\"mstsc.exe\",\n    { app = \" \" }]\n");
+        assert_eq!(errors[0].location, Some(Location { line: 2, column: 7 }));
+        assert!(errors[0].message.contains("must not be empty"), "{}", errors[0]);
+    }
+
+    #[test]
+    fn invalid_cycling_sizes_are_rejected() {
+        let errors = rejected("[cycling]\nmode = \"sizes\"\nsizes = [0.5, 1.5]\n");
+        assert_eq!(errors[0].location, Some(Location { line: 3, column: 1 }));
+
+        let settings = parse("[cycling]\nmode = \"none\"\nsizes = []\n").unwrap().settings;
+        assert_eq!(settings.cycling.mode, CyclingMode::None);
+    }
+
+    #[test]
+    fn custom_groups_are_read() {
+        let text = "[[custom-groups]]\nname = \"Coding\"\n\n[[custom-groups.actions]]\n\
+                    name = \"Editor\"\n\
+                    rect = { x = 0.25, y = 0.0, width = 0.5, height = 1.0 }\n";
+        let groups = parse(text).unwrap().settings.custom_groups;
+
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].name, "Coding");
+        assert_eq!(groups[0].actions[0].name, "Editor");
+        assert_eq!(groups[0].actions[0].rect, FracRect::new(0.25, 0.0, 0.5, 1.0));
+    }
+
+    #[test]
+    fn invalid_custom_groups_are_rejected() {
+        let errors = rejected("[[custom-groups]]\nname = \"A\"\nactions = []\n\n\
+                               [[custom-groups]]\nname = \"A\"\n\n[[custom-groups.actions]]\n\
+                               name = \"Wide\"\n\
+                               rect = { x = 0.5, y = 0.0, width = 0.75, height = 1.0 }\n");
+
+        assert_eq!(errors.len(), 2);
+        assert_eq!(errors[0].location.map(|location| location.line), Some(6));
+        assert!(errors[0].message.contains("\"A\""), "{}", errors[0]);
+        assert_eq!(errors[1].location.map(|location| location.line), Some(10));
+        assert!(errors[1].message.contains("non-empty part"), "{}", errors[1]);
+    }
+
+    #[test]
+    fn grids_are_chosen_by_monitor_resolution() {
+        let text = "[control-center.grid]\ncolumns = 4\nrows = 3\n\n\
+                    [[control-center.grid.monitors]]\nwidth = 5120\nheight = 1440\n\
+                    columns = 8\nrows = 2\n";
+        let grid = parse(text).unwrap().settings.control_center.grid;
+
+        assert_eq!(grid.size_for(&Rect::new(-5120, 0, 0, 1440)), GridSize::new(8, 2));
+        assert_eq!(grid.size_for(&Rect::new(0, 0, 1920, 1080)), GridSize::new(4, 3));
+        assert_eq!(GridSettings::default().size_for(&Rect::new(0, 0, 5120, 1440)),
+                   GridSize::default());
+    }
+
+    #[test]
+    fn invalid_grids_are_rejected() {
+        let errors = rejected("[control-center.grid]\ncolumns = 0\n\n\
+                               [[control-center.grid.monitors]]\nwidth = 3440\nheight = 1440\n\
+                               columns = 8\nrows = 99\n\n\
+                               [[control-center.grid.monitors]]\nwidth = 3440\nheight = 1440\n\
+                               columns = 6\nrows = 2\n");
+
+        assert_eq!(errors.len(), 3);
+        assert_eq!(errors[0].location, Some(Location { line: 2, column: 1 }));
+        assert!(errors[0].message.contains("between 1 and 32"), "{}", errors[0]);
+        assert_eq!(errors[1].location, Some(Location { line: 8, column: 1 }));
+        assert_eq!(errors[2].location.map(|location| location.line), Some(10));
+        assert!(errors[2].message.contains("3440x1440"), "{}", errors[2]);
+    }
+
+    #[test]
+    fn control_center_size_is_read() {
+        let control_center = parse("[control-center]\nwidth = 640\nheight = 300\n").unwrap()
+            .settings.control_center;
+        assert_eq!((control_center.width, control_center.height), (Some(640), Some(300)));
+
+        let errors = rejected("[control-center]\nwidth = 640\nheight = 0\n");
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].location, Some(Location { line: 3, column: 1 }));
+        assert!(errors[0].message.contains("greater than zero"), "{}", errors[0]);
+    }
+
+    #[test]
+    fn theme_colors_are_read() {
+        let text = "[theme]\npreset = \"high-contrast\"\n\n\
+                    [theme.colors]\naccent = \"#e81123\"\nwork-area = \"#202020\"\n";
+        let theme = parse(text).unwrap().settings.theme;
+
+        assert_eq!(theme.preset, ThemePreset::HighContrast);
+        assert_eq!(theme.colors.into_iter().collect::<Vec<_>>(),
+                   [(ThemeColor::Accent, Color::rgb(0xE8, 0x11, 0x23)),
+                    (ThemeColor::WorkArea, Color::rgb(0x20, 0x20, 0x20))]);
+    }
+
+    #[test]
+    fn invalid_theme_colors_are_rejected() {
+        let errors = rejected("[theme.colors]\naccent = \"blue\"\n");
+        assert_eq!(errors[0].location.map(|location| location.line), Some(2));
+        assert!(errors[0].message.contains("invalid color \"blue\""), "{}", errors[0]);
+
+        let errors = rejected("[theme.colors]\nshadow = \"#000000\"\n");
+        assert!(errors[0].message.contains("shadow"), "{}", errors[0]);
+    }
+
+    #[test]
+    fn default_settings_file_matches_defaults() {
+        let loaded = parse(DEFAULT_SETTINGS).unwrap();
+
+        assert_eq!(loaded.settings, Settings::default());
+        assert!(loaded.warnings.is_empty(), "{:?}", loaded.warnings);
+    }
+}
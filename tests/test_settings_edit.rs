@@ -0,0 +1,161 @@
+#[cfg(test)]
+mod test_settings_edit {
+    use std::env;
+    use std::fs;
+
+    use rectangular::settings::parse;
+    use rectangular::settings::IgnoredApp;
+    use rectangular::settings_edit::{
+        save_app_ignored, save_control_center_size, set_app_ignored, set_integers,
+    };
+
+    const REMOTE_DESKTOP: &str = r"C:\Windows\System32\mstsc.exe";
+
+    fn set_size(text: &str) -> Option<String> {
+        set_integers(text, "control-center", &[("width", 600), ("height", 300)])
+    }
+
+    fn ignore(text: &str) -> Option<String> {
+        set_app_ignored(text, REMOTE_DESKTOP, true)
+    }
+
+    fn unignore(text: &str) -> Option<String> {
+        set_app_ignored(text, REMOTE_DESKTOP, false)
+    }
+
+    #[test]
+    fn existing_values_are_replaced_in_place() {
+        let text = "version = 1\n\n# The Control Center.\n[control-center]\nwidth = 580 # wide\n\
+                    position = \"screen-center\"\nheight = 266\n";
+
+        assert_eq!(set_size(text).unwrap(),
+                   "version = 1\n\n# The Control Center.\n[control-center]\nwidth = 600 # wide\n\
+                    position = \"screen-center\"\nheight = 300\n");
+    }
+
+    #[test]
+    fn missing_keys_are_added_below_the_header() {
+        let text = "[control-center] # popup\nposition = \"screen-center\"\nheight = 1\n\n\
+                    [control-center.grid]\ncolumns = 8\n";
+
+        assert_eq!(set_size(text).unwrap(),
+                   "[control-center] # popup\nwidth = 600\nposition = \"screen-center\"\n\
+                    height = 300\n\n[control-center.grid]\ncolumns = 8\n");
+        assert_eq!(set_size("[control-center]").unwrap(),
+                   "[control-center]\nwidth = 600\nheight = 300\n");
+    }
+
+    #[test]
+    fn missing_table_is_appended() {
+        assert_eq!(set_size("").unwrap(), "[control-center]\nwidth = 600\nheight = 300\n");
+        assert_eq!(set_size("[gaps]\ninner = 4").unwrap(),
+                   "[gaps]\ninner = 4\n\n[control-center]\nwidth = 600\nheight = 300\n");
+
+        // A table created by its sub-table may still get a header of its own.
+        let edited = set_size("[control-center.grid]\ncolumns = 8\n").unwrap();
+        let control_center = parse(&edited).unwrap().settings.control_center;
+        assert_eq!((control_center.width, control_center.height), (Some(600), Some(300)));
+        assert_eq!(control_center.grid.columns, 8);
+    }
+
+    #[test]
+    fn content_which_cant_be_edited_in_place_is_left_alone() {
+        assert_eq!(set_size("control-center = { position = \"near-tray\" }\n"), None);
+        assert_eq!(set_size("control-center.position = \"near-tray\"\n"), None);
+        assert_eq!(set_size("[control-center\n"), None);
+        assert_eq!(set_size("control-center = 5\n"), None);
+    }
+
+    #[test]
+    fn size_is_saved_to_the_file() {
+        let directory = env::temp_dir().join(format!("rectangular-settings-edit-{}",
+                                                     std::process::id()));
+        let path = directory.join("settings.toml");
+        let _ = fs::remove_dir_all(&directory);
+
+        // A missing file is created, with the current version.
+        assert!(save_control_center_size(&path, 600, 300).unwrap());
+        let settings = parse(&fs::read_to_string(&path).unwrap()).unwrap().settings;
+        assert_eq!(settings.control_center.width, Some(600));
+
+        // Saving the same size again doesn't touch the file.
+        assert!(!save_control_center_size(&path, 600, 300).unwrap());
+        assert!(save_control_center_size(&path, 640, 300).unwrap());
+        assert!(fs::read_to_string(&path).unwrap().contains("width = 640\n"));
+
+        // Files from before the version was introduced differ only by the version, and are
+        // edited as they are.
+        fs::write(&path, "[gaps]\ninner = 4\n").unwrap();
+        assert!(save_control_center_size(&path, 600, 300).unwrap());
+        assert!(fs::read_to_string(&path).unwrap().starts_with("[gaps]\ninner = 4\n"));
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+
+    #[test]
+    fn ignored_apps_are_appended_by_name() {
+        assert_eq!(ignore("version = 1\nignored-apps = [] # none\n").unwrap(),
+                   "version = 1\nignored-apps = [\"mstsc.exe\"] # none\n");
+        assert_eq!(ignore("ignored-apps = [\n    \"game.exe\", # fullscreen\n]\n").unwrap(),
+                   "ignored-apps = [\n    \"game.exe\", \"mstsc.exe\", # fullscreen\n]\n");
+        assert_eq!(ignore("ignored-apps = [{ app = \"game.exe\" }]").unwrap(),
+                   "ignored-apps = [{ app = \"game.exe\" }, \"mstsc.exe\"]");
+    }
+
+    #[test]
+    fn missing_ignore_list_is_added_before_tables() {
+        assert_eq!(ignore("# Settings.\nversion = 1\n\n[gaps]\ninner = 4\n").unwrap(),
+                   "# Settings.\nversion = 1\nignored-apps = [\"mstsc.exe\"]\n\n\
+                    [gaps]\ninner = 4\n");
+        assert_eq!(ignore("[gaps]\ninner = 4\n").unwrap(),
+                   "ignored-apps = [\"mstsc.exe\"]\n[gaps]\ninner = 4\n");
+        assert_eq!(ignore("version = 1").unwrap(), "version = 1\nignored-apps = [\"mstsc.exe\"]\n");
+        assert_eq!(unignore("version = 1\n").unwrap(), "version = 1\n");
+    }
+
+    #[test]
+    fn every_entry_of_the_app_is_removed() {
+        assert_eq!(unignore("ignored-apps = [\"MSTSC.exe\"]\n").unwrap(), "ignored-apps = []\n");
+        assert_eq!(unignore("ignored-apps = [\"mstsc.exe\", \"game.exe\"]\n").unwrap(),
+                   "ignored-apps = [\"game.exe\"]\n");
+        assert_eq!(unignore("ignored-apps = [\"game.exe\", \"mstsc.exe\"]\n").unwrap(),
+                   "ignored-apps = [\"game.exe\"]\n");
+
+        let text = "ignored-apps = [\n    \"mstsc.exe\",\n    \"game.exe\",\n    \
+                    { app = 'C:\\Windows\\System32\\mstsc.exe', title = \"*work*\" },\n    \
+                    \"vmconnect.exe\", # viewer\n    \"mstsc.exe\",\n]\n";
+        let edited = unignore(text).unwrap();
+        assert_eq!(edited, "ignored-apps = [\n    \"game.exe\",\n    \
+                            \"vmconnect.exe\", # viewer\n]\n");
+
+        let ignored_apps = parse(&edited).unwrap().settings.ignored_apps;
+        assert_eq!(ignored_apps, [IgnoredApp::new("game.exe"), IgnoredApp::new("vmconnect.exe")]);
+    }
+
+    #[test]
+    fn ignore_list_which_cant_be_edited_in_place_is_left_alone() {
+        assert_eq!(ignore("[[ignored-apps]]\napp = \"game.exe\"\n"), None);
+        assert_eq!(ignore("ignored-apps = \"mstsc.exe\"\n"), None);
+        assert_eq!(ignore("ignored-apps = [\n"), None);
+    }
+
+    #[test]
+    fn ignored_app_is_saved_to_the_file() {
+        let directory = env::temp_dir().join(format!("rectangular-ignored-apps-{}",
+                                                     std::process::id()));
+        let path = directory.join("settings.toml");
+        let _ = fs::remove_dir_all(&directory);
+
+        assert!(save_app_ignored(&path, REMOTE_DESKTOP, true).unwrap());
+        let settings = parse(&fs::read_to_string(&path).unwrap()).unwrap().settings;
+        assert_eq!(settings.ignored_apps, [IgnoredApp::new("mstsc.exe")]);
+
+        // The list is already as requested.
+        assert!(!save_app_ignored(&path, REMOTE_DESKTOP, true).unwrap());
+        assert!(save_app_ignored(&path, REMOTE_DESKTOP, false).unwrap());
+        let settings = parse(&fs::read_to_string(&path).unwrap()).unwrap().settings;
+        assert!(settings.ignored_apps.is_empty());
+
+        fs::remove_dir_all(&directory).unwrap();
+    }
+}
@@ -0,0 +1,110 @@
+#[cfg(test)]
+mod test_theme {
+    use std::collections::BTreeMap;
+
+    use rectangular::display_list::Color;
+    use rectangular::settings::{ThemePreset, ThemeSettings};
+    use rectangular::theme::{SystemAppearance, SystemColors, Theme, ThemeColor, THEME_COLORS};
+
+    /// The colors of the "Desert" high-contrast theme of Windows 11.
+    const DESERT: SystemColors = SystemColors {
+        window: Color::rgb(0xFF, 0xFA, 0xEF),
+        window_text: Color::rgb(0x3D, 0x3D, 0x3D),
+        highlight: Color::rgb(0x90, 0x39, 0x09),
+        hot_light: Color::rgb(0x1C, 0x5E, 0x75),
+        gray_text: Color::rgb(0x67, 0x67, 0x67),
+    };
+
+    // System colors are there in every appearance, to show that they matter only in high contrast.
+    const LIGHT: SystemAppearance = SystemAppearance {
+        dark: false,
+        high_contrast: false,
+        colors: DESERT,
+    };
+    const DARK: SystemAppearance = SystemAppearance { dark: true, ..LIGHT };
+    const HIGH_CONTRAST: SystemAppearance = SystemAppearance { high_contrast: true, ..LIGHT };
+
+    fn preset(preset: ThemePreset) -> ThemeSettings {
+        ThemeSettings { preset, ..Default::default() }
+    }
+
+    #[test]
+    fn system_preset_follows_the_system() {
+        let settings = ThemeSettings::default();
+
+        assert_eq!(Theme::resolve(&settings, LIGHT), Theme::LIGHT);
+        assert_eq!(Theme::resolve(&settings, DARK), Theme::DARK);
+        assert_eq!(Theme::resolve(&settings, HIGH_CONTRAST), Theme::from_system_colors(&DESERT));
+
+        // High contrast wins over the dark mode.
+        let both = SystemAppearance { dark: true, ..HIGH_CONTRAST };
+        assert_eq!(Theme::resolve(&settings, both), Theme::from_system_colors(&DESERT));
+    }
+
+    #[test]
+    fn system_colors_keep_the_layout_of_the_high_contrast_preset() {
+        let theme = Theme::from_system_colors(&DESERT);
+
+        assert_eq!(theme.background, DESERT.window);
+        assert_eq!(theme.text, DESERT.window_text);
+        assert_eq!(theme.hint, DESERT.gray_text);
+        assert_eq!(theme.accent, DESERT.highlight);
+        assert_eq!(theme.preview_border, DESERT.hot_light);
+
+        // Like in the preset, hovering over a button doesn't change its fill.
+        assert_eq!(theme.button_hovered, theme.background);
+        assert_eq!(theme.button_pressed, theme.background);
+    }
+
+    #[test]
+    fn explicit_presets_ignore_the_system() {
+        for system in [LIGHT, DARK, HIGH_CONTRAST] {
+            assert_eq!(Theme::resolve(&preset(ThemePreset::Light), system), Theme::LIGHT);
+            assert_eq!(Theme::resolve(&preset(ThemePreset::Dark), system), Theme::DARK);
+            assert_eq!(Theme::resolve(&preset(ThemePreset::HighContrast), system),
+                       Theme::HIGH_CONTRAST);
+        }
+    }
+
+    #[test]
+    fn overrides_replace_single_colors() {
+        let red = Color::rgb(0xE8, 0x11, 0x23);
+        let settings = ThemeSettings {
+            preset: ThemePreset::System,
+            colors: BTreeMap::from([(ThemeColor::Accent, red),
+                                    (ThemeColor::PreviewBorder, red)]),
+        };
+        let theme = Theme::resolve(&settings, DARK);
+
+        assert_eq!(theme, Theme { accent: red, preview_border: red, ..Theme::DARK });
+        assert_eq!(theme.color(ThemeColor::Accent), red);
+        assert_eq!(theme.color(ThemeColor::Background), Theme::DARK.background);
+    }
+
+    #[test]
+    fn every_role_has_its_own_color() {
+        let mut theme = Theme::default();
+        for (index, (role, _)) in THEME_COLORS.iter().enumerate() {
+            theme.apply(&BTreeMap::from([(*role, Color::rgb(index as u8, 0, 0))]));
+        }
+
+        for (index, (role, _)) in THEME_COLORS.iter().enumerate() {
+            assert_eq!(theme.color(*role), Color::rgb(index as u8, 0, 0), "{role:?}");
+        }
+    }
+
+    #[test]
+    fn text_stands_out_from_the_background() {
+        // A rough luminance, good enough to tell that the presets aren't mixed up.
+        let luminance = |color: Color| {
+            0.2126 * color.red as f64 + 0.7152 * color.green as f64 + 0.0722 * color.blue as f64
+        };
+
+        for theme in [Theme::LIGHT, Theme::DARK, Theme::HIGH_CONTRAST] {
+            for role in [ThemeColor::Text, ThemeColor::Hint, ThemeColor::Accent] {
+                let contrast = (luminance(theme.color(role)) - luminance(theme.background)).abs();
+                assert!(contrast > 90.0, "{role:?} of {theme:?}");
+            }
+        }
+    }
+}